@@ -0,0 +1,220 @@
+//! Machine-readable JSON form of the syntax tree, for external tools.
+
+use crate::error::json_escape;
+use crate::parser::{AstNode, Attribute, Literal, Type};
+
+/// Serializes `ast` as one JSON object. Every node is an object whose
+/// `"kind"` names its variant, with its fields alongside it and child nodes
+/// in arrays. Types are written in source syntax, such as `"[i32; 3]"`.
+pub fn to_json(ast: &AstNode) -> String {
+    let mut out = node(ast);
+    out.push('\n');
+    out
+}
+
+/// A JSON object built one field at a time, starting with its kind.
+struct Object {
+    fields: Vec<String>,
+}
+
+impl Object {
+    fn new(kind: &str) -> Self {
+        Object { fields: vec![format!("\"kind\":{}", string(kind))] }
+    }
+
+    fn field(mut self, name: &str, value: String) -> Self {
+        self.fields.push(format!("\"{}\":{}", name, value));
+        self
+    }
+
+    fn position(self, line: usize, column: usize) -> Self {
+        self.field("line", line.to_string()).field("column", column.to_string())
+    }
+
+    fn finish(self) -> String {
+        format!("{{{}}}", self.fields.join(","))
+    }
+}
+
+fn node(ast: &AstNode) -> String {
+    match ast {
+        AstNode::Module { name, items } => Object::new("Module")
+            .field("name", string(name))
+            .field("items", nodes(items)),
+        AstNode::TypeAlias { name, target } => Object::new("TypeAlias")
+            .field("name", string(name))
+            .field("target", ty(target)),
+        AstNode::StructDecl { name, fields } => Object::new("StructDecl")
+            .field("name", string(name))
+            .field("fields", typed_names(fields)),
+        AstNode::UnionDecl { name, fields } => Object::new("UnionDecl")
+            .field("name", string(name))
+            .field("fields", typed_names(fields)),
+        AstNode::Function { name, params, return_type, body, attributes, constant, line, column } => Object::new("Function")
+            .field("name", string(name))
+            .field("params", typed_names(params))
+            .field("return_type", optional(return_type.as_ref().map(ty)))
+            .field("attributes", array(attributes.iter().map(attribute)))
+            .field("constant", constant.to_string())
+            .field("body", nodes(body))
+            .position(*line, *column),
+        AstNode::VariableDecl { name, var_type, value, mutable } => Object::new("VariableDecl")
+            .field("name", string(name))
+            .field("type", optional(var_type.as_ref().map(ty)))
+            .field("value", optional_node(value))
+            .field("mutable", mutable.to_string()),
+        AstNode::TupleDestructure { names, value } => Object::new("TupleDestructure")
+            .field("names", array(names.iter().map(|(name, mutable)| {
+                format!("{{\"name\":{},\"mutable\":{}}}", string(name), mutable)
+            })))
+            .field("value", node(value)),
+        AstNode::ConstDecl { name, const_type, value } => Object::new("ConstDecl")
+            .field("name", string(name))
+            .field("type", ty(const_type))
+            .field("value", node(value)),
+        AstNode::Static { name, static_type, value, mutable } => Object::new("Static")
+            .field("name", string(name))
+            .field("type", ty(static_type))
+            .field("value", optional_node(value))
+            .field("mutable", mutable.to_string()),
+        AstNode::Return { value } => Object::new("Return")
+            .field("value", optional_node(value)),
+        AstNode::BinaryOp { left, op, right, line, column } => Object::new("BinaryOp")
+            .field("op", string(op))
+            .field("left", node(left))
+            .field("right", node(right))
+            .position(*line, *column),
+        AstNode::UnaryOp { op, operand } => Object::new("UnaryOp")
+            .field("op", string(op))
+            .field("operand", node(operand)),
+        AstNode::Literal(lit) => literal(lit),
+        AstNode::Identifier { name, line, column } => Object::new("Identifier")
+            .field("name", string(name))
+            .position(*line, *column),
+        AstNode::FunctionCall { name, args, line, column } => Object::new("FunctionCall")
+            .field("name", string(name))
+            .field("args", nodes(args))
+            .position(*line, *column),
+        AstNode::If { condition, then_branch, else_branch } => Object::new("If")
+            .field("condition", node(condition))
+            .field("then_branch", nodes(then_branch))
+            .field("else_branch", optional(else_branch.as_deref().map(nodes))),
+        AstNode::While { condition, body, label } => Object::new("While")
+            .field("condition", node(condition))
+            .field("body", nodes(body))
+            .field("label", optional_string(label)),
+        AstNode::For { iterator, range_start, range_end, inclusive, body, label } => Object::new("For")
+            .field("iterator", string(iterator))
+            .field("range_start", node(range_start))
+            .field("range_end", node(range_end))
+            .field("inclusive", inclusive.to_string())
+            .field("body", nodes(body))
+            .field("label", optional_string(label)),
+        AstNode::Loop { body, label } => Object::new("Loop")
+            .field("body", nodes(body))
+            .field("label", optional_string(label)),
+        AstNode::Block { body } => Object::new("Block")
+            .field("body", nodes(body)),
+        AstNode::BlockValue { value } => Object::new("BlockValue")
+            .field("value", node(value)),
+        AstNode::Defer { statement } => Object::new("Defer")
+            .field("statement", node(statement)),
+        AstNode::Break { label, value } => Object::new("Break")
+            .field("label", optional_string(label))
+            .field("value", optional_node(value)),
+        AstNode::Continue { label } => Object::new("Continue")
+            .field("label", optional_string(label)),
+        AstNode::Assignment { target, value, line, column } => Object::new("Assignment")
+            .field("target", string(target))
+            .field("value", node(value))
+            .position(*line, *column),
+        AstNode::PlaceAssignment { target, op, value, line, column } => Object::new("PlaceAssignment")
+            .field("target", node(target))
+            .field("op", optional_string(op))
+            .field("value", node(value))
+            .position(*line, *column),
+        AstNode::ArrayLiteral { elements } => Object::new("ArrayLiteral")
+            .field("elements", nodes(elements)),
+        AstNode::ArrayRepeat { value, count } => Object::new("ArrayRepeat")
+            .field("value", node(value))
+            .field("count", count.to_string()),
+        AstNode::ArrayIndex { array, index } => Object::new("ArrayIndex")
+            .field("array", node(array))
+            .field("index", node(index)),
+        AstNode::TupleLiteral { elements } => Object::new("TupleLiteral")
+            .field("elements", nodes(elements)),
+        AstNode::StructLiteral { name, fields, line, column } => Object::new("StructLiteral")
+            .field("name", string(name))
+            .field("fields", array(fields.iter().map(|(field, value)| {
+                format!("{{\"name\":{},\"value\":{}}}", string(field), node(value))
+            })))
+            .position(*line, *column),
+        AstNode::FieldAccess { object, field, line, column } => Object::new("FieldAccess")
+            .field("object", node(object))
+            .field("field", string(field))
+            .position(*line, *column),
+        AstNode::MethodCall { receiver, method, args, line, column } => Object::new("MethodCall")
+            .field("receiver", node(receiver))
+            .field("method", string(method))
+            .field("args", nodes(args))
+            .position(*line, *column),
+    }
+    .finish()
+}
+
+fn literal(lit: &Literal) -> Object {
+    let value = match lit {
+        Literal::Int(n) => n.to_string(),
+        Literal::UInt(n) => n.to_string(),
+        // JSON has no infinity, so a literal too large for f64 becomes null.
+        Literal::Float(f) if f.is_finite() => format!("{:?}", f),
+        Literal::Float(_) => "null".to_string(),
+        Literal::String(s) => string(s),
+        Literal::Bool(b) => b.to_string(),
+        Literal::Char(c) => string(&c.to_string()),
+    };
+    Object::new("Literal")
+        .field("type", ty(&lit.default_type()))
+        .field("value", value)
+}
+
+fn attribute(attribute: &Attribute) -> String {
+    Object::new("Attribute")
+        .field("name", string(&attribute.name))
+        .field("argument", optional_string(&attribute.argument))
+        .position(attribute.line, attribute.column)
+        .finish()
+}
+
+/// Parameters and fields, as `{"name": ..., "type": ...}` objects.
+fn typed_names(items: &[(String, Type)]) -> String {
+    array(items.iter().map(|(name, t)| format!("{{\"name\":{},\"type\":{}}}", string(name), ty(t))))
+}
+
+fn nodes(items: &[AstNode]) -> String {
+    array(items.iter().map(node))
+}
+
+fn optional_node(value: &Option<Box<AstNode>>) -> String {
+    optional(value.as_deref().map(node))
+}
+
+fn optional_string(value: &Option<String>) -> String {
+    optional(value.as_deref().map(string))
+}
+
+fn optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "null".to_string())
+}
+
+fn array(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.collect::<Vec<_>>().join(","))
+}
+
+fn ty(t: &Type) -> String {
+    string(&t.to_string())
+}
+
+fn string(s: &str) -> String {
+    format!("\"{}\"", json_escape(s))
+}
@@ -0,0 +1,147 @@
+//! Control-flow graphs of the generated assembly, rendered for Graphviz.
+
+/// A straight-line run of instructions and the blocks it can continue to.
+struct BasicBlock {
+    label: Option<String>,
+    lines: Vec<String>,
+    /// Jump targets, each with an edge label.
+    jumps: Vec<(String, &'static str)>,
+    /// Whether control can also fall into the next block.
+    falls_through: bool,
+}
+
+impl BasicBlock {
+    fn new(label: Option<String>) -> Self {
+        BasicBlock {
+            label,
+            lines: Vec::new(),
+            jumps: Vec::new(),
+            falls_through: true,
+        }
+    }
+}
+
+struct FunctionGraph {
+    name: String,
+    blocks: Vec<BasicBlock>,
+}
+
+/// Builds one graph per function in `asm` and renders them in DOT format.
+/// Blocks start at labels and end after jumps and returns.
+pub fn to_dot(asm: &str) -> String {
+    let mut out = String::new();
+    out.push_str("digraph cfg {\n");
+    out.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+    for function in split_functions(asm) {
+        render_function(&function, &mut out);
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn split_functions(asm: &str) -> Vec<FunctionGraph> {
+    let mut functions: Vec<FunctionGraph> = Vec::new();
+    let text = asm.split_once("section .text").map_or("", |(_, text)| text);
+    
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("global ") || trimmed.starts_with("extern ") {
+            continue;
+        }
+        
+        if let Some(label) = trimmed.strip_suffix(':') {
+            if is_local_label(label) {
+                if let Some(function) = functions.last_mut() {
+                    function.blocks.push(BasicBlock::new(Some(label.to_string())));
+                }
+            } else {
+                functions.push(FunctionGraph {
+                    name: label.to_string(),
+                    blocks: vec![BasicBlock::new(None)],
+                });
+            }
+            continue;
+        }
+        
+        let Some(function) = functions.last_mut() else {
+            continue;
+        };
+        let block = function.blocks.last_mut().unwrap();
+        block.lines.push(trimmed.to_string());
+        
+        let (mnemonic, operand) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+        let operand = operand.trim().to_string();
+        let ends_block = match mnemonic {
+            "jmp" => {
+                block.jumps.push((operand, ""));
+                block.falls_through = false;
+                true
+            }
+            "ret" => {
+                block.falls_through = false;
+                true
+            }
+            m if m.starts_with('j') => {
+                block.jumps.push((operand, "taken"));
+                true
+            }
+            _ => false,
+        };
+        if ends_block {
+            function.blocks.push(BasicBlock::new(None));
+        }
+    }
+    
+    // Unlabeled blocks left empty by a jump just before a label add nothing.
+    for function in &mut functions {
+        function.blocks.retain(|b| b.label.is_some() || !b.lines.is_empty());
+    }
+    functions
+}
+
+/// Labels generated inside a function body, as opposed to function names.
+fn is_local_label(label: &str) -> bool {
+    label.strip_prefix('L').is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn render_function(function: &FunctionGraph, out: &mut String) {
+    let node = |index: usize| format!("\"{}.{}\"", function.name, index);
+    
+    out.push_str(&format!("    subgraph \"cluster_{}\" {{\n", function.name));
+    out.push_str(&format!("        label=\"{}\";\n", function.name));
+    
+    for (index, block) in function.blocks.iter().enumerate() {
+        let mut text = String::new();
+        let heading = if index == 0 { Some(function.name.as_str()) } else { block.label.as_deref() };
+        if let Some(heading) = heading {
+            text.push_str(&format!("{}:\\l", dot_escape(heading)));
+        }
+        for line in &block.lines {
+            text.push_str(&format!("    {}\\l", dot_escape(line)));
+        }
+        out.push_str(&format!("        {} [label=\"{}\"];\n", node(index), text));
+    }
+    
+    for (index, block) in function.blocks.iter().enumerate() {
+        for (target, edge_label) in &block.jumps {
+            let Some(target_index) = function.blocks.iter().position(|b| b.label.as_ref() == Some(target)) else {
+                continue;
+            };
+            out.push_str(&format!("        {} -> {}", node(index), node(target_index)));
+            if !edge_label.is_empty() {
+                out.push_str(&format!(" [label=\"{}\"]", edge_label));
+            }
+            out.push_str(";\n");
+        }
+        if block.falls_through && index + 1 < function.blocks.len() {
+            let edge_label = if block.jumps.is_empty() { "" } else { " [label=\"not taken\"]" };
+            out.push_str(&format!("        {} -> {}{};\n", node(index), node(index + 1), edge_label));
+        }
+    }
+    
+    out.push_str("    }\n");
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
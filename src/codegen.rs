@@ -1,28 +1,220 @@
+use crate::const_eval::ConstFunctions;
 use crate::error::CompilerError;
-use crate::parser::{AstNode, Literal};
+use crate::optimizer;
+use crate::parser::{method_symbol, AstNode, Literal, Type};
 use std::collections::HashMap;
 
+#[derive(Clone)]
+struct LoopFrame {
+    label: Option<String>,
+    break_label: String,
+    continue_label: String,
+    /// Number of defer scopes open outside the loop.
+    defer_depth: usize,
+}
+
+/// A deferred statement and the variables visible where it was deferred.
+#[derive(Clone)]
+struct Deferred {
+    statement: AstNode,
+    variables: HashMap<String, Local>,
+}
+
+#[derive(Debug, Clone)]
+struct Local {
+    offset: i32,
+    var_type: Type,
+    /// The slot holds a pointer to the value (array parameters) rather than the value itself.
+    indirect: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Signature {
+    params: Vec<Type>,
+    return_type: Option<Type>,
+}
+
+/// Object format the assembly is written for, which decides the attributes
+/// given to each section.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Win64,
+    Elf64,
+}
+
+impl Target {
+    /// The NASM output format name, as passed to `nasm -f`.
+    pub fn nasm_format(self) -> &'static str {
+        match self {
+            Target::Win64 => "win64",
+            Target::Elf64 => "elf64",
+        }
+    }
+    
+    /// The `section` line that starts `name`, one of `.data`, `.bss` or `.text`.
+    fn section(self, name: &str) -> String {
+        let attributes = match (self, name) {
+            (Target::Win64, ".text") => "code align=16",
+            (Target::Win64, ".bss") => "bss align=8",
+            (Target::Win64, _) => "data align=8",
+            (Target::Elf64, ".text") => "progbits alloc exec nowrite align=16",
+            (Target::Elf64, ".bss") => "nobits alloc noexec write align=8",
+            (Target::Elf64, _) => "progbits alloc noexec write align=8",
+        };
+        format!("section {} {}\n", name, attributes)
+    }
+}
+
+/// Where a function or static was placed in the generated assembly.
+#[derive(Debug, Clone)]
+pub struct MapEntry {
+    pub symbol: String,
+    pub section: &'static str,
+    /// Offset from the start of the section: in bytes for `.data` and `.bss`,
+    /// and in instructions for `.text`, whose encoded size is the assembler's.
+    pub offset: usize,
+    /// Size in the same unit as `offset`.
+    pub size: usize,
+}
+
+/// A named variable in a function's frame, kept once the function is
+/// generated so it can be listed by `--dump-symbols`.
+#[derive(Debug, Clone)]
+pub struct FrameLocal {
+    pub name: String,
+    /// Distance below the frame pointer, as in `[rbp-offset]`.
+    pub offset: i32,
+    pub var_type: Type,
+    /// The slot holds the address of the value rather than the value itself.
+    pub indirect: bool,
+}
+
+/// Windows x64 integer argument registers, in order.
+const ARG_REGISTERS: [&str; 4] = ["rcx", "rdx", "r8", "r9"];
+
+/// Low bytes of the argument registers, for `bool` parameters.
+const ARG_BYTE_REGISTERS: [&str; 4] = ["cl", "dl", "r8b", "r9b"];
+
+/// Frames at least this large are touched a page at a time through `__chkstk`,
+/// since Windows grows the stack only through its guard page.
+const STACK_PROBE_SIZE: i32 = 4096;
+
+/// Largest stack frame a function may reserve, in bytes. Frame sizes are
+/// added up as `i64` and checked against this before they are narrowed.
+const MAX_FRAME_SIZE: i64 = 1 << 30;
+
+/// Frame slot of the stack protector's canary, just below the saved rbp so an
+/// overflowing local array reaches it before the return address.
+const CANARY_OFFSET: i32 = 8;
+
+/// Process exit code after a failed `assert` or a `panic`.
+const PANIC_EXIT_CODE: u32 = 101;
+
 pub struct CodeGenerator {
     opt_level: u8,
+    target: Target,
+    stack_protector: bool,
+    /// Whether the function being generated keeps an array in its frame.
+    frame_has_array: bool,
+    source_file: String,
     label_counter: usize,
+    /// Numbers the temporaries introduced by common subexpression elimination
+    /// and compound assignment.
+    temporary_counter: usize,
     string_literals: Vec<String>,
-    variables: HashMap<String, i32>,
+    variables: HashMap<String, Local>,
+    functions: HashMap<String, Signature>,
+    statics: HashMap<String, Type>,
+    local_functions: HashMap<String, String>,
+    /// Symbol names given with `@export`, by function name.
+    exports: HashMap<String, String>,
+    pending_functions: Vec<(String, AstNode)>,
+    const_functions: ConstFunctions,
+    current_label: String,
+    type_aliases: HashMap<String, Type>,
     stack_offset: i32,
-    loop_stack: Vec<(String, String)>, // (break_label, continue_label)
+    max_stack_offset: i32,
+    max_call_args: usize,
+    return_slot: Option<i32>,
+    loop_stack: Vec<LoopFrame>,
+    /// Statements deferred in each open block, outermost first.
+    defer_scopes: Vec<Vec<Deferred>>,
+    /// Bytes reserved below the return address by each function, in output order.
+    frame_sizes: Vec<(String, i32)>,
+    /// Functions and statics in output order, for `--map`.
+    symbol_map: Vec<MapEntry>,
+    /// Every variable declared in each function, by label in output order.
+    /// Shadowed and block-scoped variables are kept with their own slots.
+    frame_locals: Vec<(String, Vec<FrameLocal>)>,
 }
 
 impl CodeGenerator {
     pub fn new(opt_level: u8) -> Self {
         CodeGenerator {
             opt_level,
+            target: Target::Win64,
+            stack_protector: false,
+            frame_has_array: false,
+            source_file: "<input>".to_string(),
             label_counter: 0,
+            temporary_counter: 0,
             string_literals: Vec::new(),
             variables: HashMap::new(),
+            functions: HashMap::new(),
+            statics: HashMap::new(),
+            local_functions: HashMap::new(),
+            exports: HashMap::new(),
+            pending_functions: Vec::new(),
+            const_functions: ConstFunctions::default(),
+            current_label: String::new(),
+            type_aliases: HashMap::new(),
             stack_offset: 0,
+            max_stack_offset: 0,
+            max_call_args: 0,
+            return_slot: None,
             loop_stack: Vec::new(),
+            defer_scopes: Vec::new(),
+            frame_sizes: Vec::new(),
+            symbol_map: Vec::new(),
+            frame_locals: Vec::new(),
         }
     }
     
+    /// Each function's stack frame size in bytes, filled in by `to_assembly`.
+    pub fn frame_sizes(&self) -> &[(String, i32)] {
+        &self.frame_sizes
+    }
+    
+    /// The layout of every function and static, filled in by `to_assembly`.
+    pub fn symbol_map(&self) -> &[MapEntry] {
+        &self.symbol_map
+    }
+    
+    /// The variables of each function with their frame slots, filled in by
+    /// `to_assembly`.
+    pub fn frame_locals(&self) -> &[(String, Vec<FrameLocal>)] {
+        &self.frame_locals
+    }
+    
+    /// Guards each function that keeps an array in its frame with a canary,
+    /// checked by `__security_check_cookie` before returning.
+    pub fn with_stack_protector(mut self, enabled: bool) -> Self {
+        self.stack_protector = enabled;
+        self
+    }
+    
+    /// Sets the object format the sections are declared for.
+    pub fn with_target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+    
+    /// Sets the file name reported by failed assertions and panics.
+    pub fn with_source_file(mut self, source_file: &str) -> Self {
+        self.source_file = source_file.to_string();
+        self
+    }
+    
     pub fn generate(&mut self, ast: &AstNode) -> Result<String, CompilerError> {
         let mut output = String::new();
         self.generate_node(ast, &mut output)?;
@@ -36,23 +228,99 @@ impl CodeGenerator {
         self.label_counter = 0;
         self.loop_stack.clear();
         
+        if let AstNode::Module { items, .. } = ast {
+            for item in items {
+                if let AstNode::TypeAlias { name, target } = item {
+                    self.type_aliases.insert(name.clone(), target.clone());
+                }
+                if let AstNode::StructDecl { name, fields } = item {
+                    self.type_aliases.insert(name.clone(), Type::Struct { name: name.clone(), fields: fields.clone() });
+                }
+                if let AstNode::UnionDecl { name, fields } = item {
+                    self.type_aliases.insert(name.clone(), Type::Union { name: name.clone(), fields: fields.clone() });
+                }
+            }
+            self.const_functions = ConstFunctions::collect(items);
+            for item in items {
+                if let AstNode::Function { name, params, return_type, attributes, .. } = item {
+                    let signature = self.signature(params, return_type);
+                    self.functions.insert(name.clone(), signature);
+                    for attribute in attributes {
+                        if let ("export", Some(symbol)) = (attribute.name.as_str(), &attribute.argument) {
+                            self.exports.insert(name.clone(), symbol.clone());
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Initialized statics go in .data and the rest are zeroed in .bss.
+        let mut data = String::new();
+        let mut bss = String::new();
+        let mut static_sizes = Vec::new();
+        if let AstNode::Module { items, .. } = ast {
+            for item in items {
+                if let AstNode::Static { name, static_type, value, .. } = item {
+                    let static_type = self.resolve(static_type);
+                    let section = if value.is_some() { ".data" } else { ".bss" };
+                    static_sizes.push((format!("static_{}", symbol(name)), section, 8 * type_slots(&static_type)));
+                    match value {
+                        Some(value) => {
+                            let initializer = self.static_initializer(value, &static_type);
+                            data.push_str(&format!("    static_{}: dq {}\n", symbol(name), initializer));
+                        }
+                        None => bss.push_str(&format!("    static_{}: resq {}\n", symbol(name), type_slots(&static_type))),
+                    }
+                    self.statics.insert(name.clone(), static_type);
+                }
+            }
+        }
+        
         let mut code = String::new();
         self.generate_assembly_node(ast, &mut code)?;
         
+        // Statics follow the string literals in .data, which are only known now.
+        let functions = std::mem::take(&mut self.symbol_map);
+        let mut data_offset: usize = self.string_literals.iter().map(|s| s.len() + 1).sum();
+        let mut bss_offset = 0;
+        for (name, section, size) in static_sizes {
+            let offset = if section == ".data" { &mut data_offset } else { &mut bss_offset };
+            self.symbol_map.push(MapEntry { symbol: name, section, offset: *offset, size });
+            *offset += size;
+        }
+        self.symbol_map.extend(functions);
+        
         let mut asm = String::new();
         
-        asm.push_str("section .data\n");
+        asm.push_str(&self.target.section(".data"));
         if !self.string_literals.is_empty() {
             for (i, s) in self.string_literals.iter().enumerate() {
-                asm.push_str(&format!("    str_{}: db `{}`, 0\n", i, s.replace("\n", "\\n").replace("\r", "\\r")));
+                asm.push_str(&format!("    str_{}: db `{}`, 0\n", i, nasm_escape(s)));
             }
         }
-        asm.push_str("\n");
-        asm.push_str("section .bss\n\n");
-        asm.push_str("section .text\n");
+        asm.push_str(&data);
+        asm.push('\n');
+        asm.push_str(&self.target.section(".bss"));
+        asm.push_str(&bss);
+        asm.push('\n');
+        asm.push_str(&self.target.section(".text"));
         asm.push_str("    global main\n");
+        let mut exported: Vec<&String> = self.exports.values().collect();
+        exported.sort();
+        for symbol in exported {
+            asm.push_str(&format!("    global {}\n", symbol));
+        }
         asm.push_str("    extern ExitProcess\n");
-        asm.push_str("    extern printf\n\n");
+        asm.push_str("    extern printf\n");
+        asm.push_str("    extern putchar\n");
+        asm.push_str("    extern fflush\n");
+        asm.push_str("    extern fmod\n");
+        asm.push_str("    extern __chkstk\n");
+        if self.stack_protector {
+            asm.push_str("    extern __security_cookie\n");
+            asm.push_str("    extern __security_check_cookie\n");
+        }
+        asm.push('\n');
         
         asm.push_str(&code);
         
@@ -66,104 +334,324 @@ impl CodeGenerator {
                     self.generate_assembly_node(item, asm)?;
                 }
             }
-            AstNode::Function { name, body, .. } => {
-                asm.push_str(&format!("{}:\n", name));
-                asm.push_str("    push rbp\n");
-                asm.push_str("    mov rbp, rsp\n");
-                
-                self.variables.clear();
-                self.stack_offset = 0;
-                
-                let local_space = self.calculate_stack_space(body);
-                let total_space = ((local_space + 32 + 15) / 16) * 16; // Align to 16 bytes + shadow space
-                
-                if total_space > 0 {
-                    asm.push_str(&format!("    sub rsp, {}\n", total_space));
-                }
-                asm.push_str("\n");
-                
-                for stmt in body {
-                    self.generate_statement(stmt, asm)?;
-                }
-                
-                if !body.iter().any(|s| matches!(s, AstNode::Return { .. })) {
-                    asm.push_str("    xor eax, eax\n");
-                    asm.push_str("    leave\n");
-                    asm.push_str("    ret\n");
+            AstNode::Function { name, .. } => {
+                // Local functions are emitted after their parent, under `parent.name` labels.
+                self.local_functions.clear();
+                let label = self.exports.get(name).cloned().unwrap_or_else(|| symbol(name));
+                self.generate_function(&label, node, asm)?;
+                while !self.pending_functions.is_empty() {
+                    let (label, function) = self.pending_functions.remove(0);
+                    self.generate_function(&label, &function, asm)?;
                 }
-                
-                asm.push_str("\n");
             }
             _ => {}
         }
         Ok(())
     }
     
-    fn calculate_stack_space(&self, body: &[AstNode]) -> i32 {
-        let mut count = 0;
+    /// Emits the function in `node` under `label`.
+    fn generate_function(&mut self, label: &str, node: &AstNode, asm: &mut String) -> Result<(), CompilerError> {
+        if let AstNode::Function { params, return_type, body, attributes, .. } = node {
+            self.current_label = label.to_string();
+            self.variables.clear();
+            self.stack_offset = 0;
+            self.max_stack_offset = 0;
+            self.max_call_args = 0;
+            self.return_slot = None;
+            self.defer_scopes = vec![Vec::new()];
+            self.frame_has_array = false;
+            self.frame_locals.push((label.to_string(), Vec::new()));
+            if self.stack_protector {
+                self.allocate_slots(1);
+            }
+            
+            // The body is generated first so the frame can cover every slot it used.
+            let mut code = String::new();
+            let mut arg_index = 0;
+            
+            // Array and tuple results are written through a caller-provided pointer passed ahead of the arguments.
+            if return_type.as_ref().is_some_and(|t| is_aggregate(&self.resolve(t))) {
+                let offset = self.allocate_slots(1);
+                self.return_slot = Some(offset);
+                self.load_parameter(arg_index, offset, &Type::U64, &mut code);
+                arg_index += 1;
+            }
+            
+            for (param_name, param_type) in params {
+                let param_type = self.resolve(param_type);
+                let offset = if is_aggregate(&param_type) {
+                    let offset = self.allocate_slots(1);
+                    self.insert_local(param_name, Local {
+                        offset,
+                        var_type: param_type.clone(),
+                        indirect: true,
+                    });
+                    offset
+                } else {
+                    self.declare_local(param_name, param_type.clone())
+                };
+                self.load_parameter(arg_index, offset, &param_type, &mut code);
+                arg_index += 1;
+            }
+            
+            self.generate_statements(body, &mut code)?;
+            
+            // A @noreturn function never reaches the end of its body.
+            let noreturn = attributes.iter().any(|a| a.name == "noreturn");
+            if !noreturn && !body.iter().any(|s| matches!(s, AstNode::Return { .. })) {
+                self.generate_deferred(0, &mut code)?;
+                code.push_str("    xor eax, eax\n");
+                code.push_str("    leave\n");
+                code.push_str("    ret\n");
+            }
+            
+            if self.stack_protector && self.frame_has_array {
+                code = protect_frame(&code);
+            }
+            
+            // Packed bools can leave the deepest offset short of a whole slot.
+            let used = (i64::from(self.max_stack_offset) + 7) / 8 * 8;
+            let local_space = self.calculate_stack_space(body).max(used);
+            if local_space > MAX_FRAME_SIZE {
+                return Err(CompilerError::CodeGenError(format!(
+                    "Stack frame of function '{}' exceeds the {} byte limit",
+                    label, MAX_FRAME_SIZE
+                )));
+            }
+            // Fits, having been checked against the limit.
+            let local_space = local_space as i32;
+            let start = asm.len();
+            asm.push_str(&format!("{}:\n", label));
+            
+            // Leaf functions at -O2 address their slots off rsp and skip the frame pointer.
+            if self.opt_level >= 2 && !code.contains("    call ") {
+                reserve_stack(local_space, asm);
+                self.frame_sizes.push((label.to_string(), local_space));
+                asm.push('\n');
+                asm.push_str(&omit_frame_pointer(&code, local_space));
+            } else {
+                let outgoing_space = 32 + 8 * self.max_call_args.saturating_sub(ARG_REGISTERS.len()) as i32;
+                let total_space = ((local_space + outgoing_space + 15) / 16) * 16; // Align to 16 bytes + shadow space
+                
+                asm.push_str("    push rbp\n");
+                asm.push_str("    mov rbp, rsp\n");
+                reserve_stack(total_space, asm);
+                // The saved rbp is part of the frame too.
+                self.frame_sizes.push((label.to_string(), total_space + 8));
+                asm.push('\n');
+                asm.push_str(&code);
+            }
+            
+            asm.push('\n');
+            let offset = self.symbol_map.iter().map(|entry| entry.size).sum();
+            let size = asm[start..].lines()
+                .filter(|line| line.starts_with("    ") && !line.trim_start().starts_with(';'))
+                .count();
+            self.symbol_map.push(MapEntry { symbol: label.to_string(), section: ".text", offset, size });
+        }
+        Ok(())
+    }
+    
+    fn calculate_stack_space(&self, body: &[AstNode]) -> i64 {
+        self.local_bytes(body, 0).saturating_add(15) / 16 * 16
+    }
+    
+    /// Bytes taken by the variables declared in `body` on top of `offset`,
+    /// packing `bool`s the way `allocate_value` does. Saturates rather than
+    /// wrapping, so an oversized frame is reported.
+    fn local_bytes(&self, body: &[AstNode], mut offset: i64) -> i64 {
+        let slot = |offset: i64| (offset.saturating_add(7) / 8 * 8).saturating_add(8);
         for stmt in body {
             match stmt {
+                AstNode::VariableDecl { var_type: Some(Type::Bool), .. } | AstNode::ConstDecl { const_type: Type::Bool, .. } => {
+                    offset = offset.saturating_add(1);
+                }
                 AstNode::VariableDecl { .. } | AstNode::ConstDecl { .. } => {
-                    count += 1;
+                    offset = slot(offset);
                 }
                 AstNode::For { body, .. } => {
-                    count += 1; // for iterator variable
-                    count += self.calculate_stack_space(body);
+                    offset = slot(offset); // for iterator variable
+                    offset = self.local_bytes(body, offset);
                 }
-                AstNode::While { body, .. } | AstNode::Loop { body } => {
-                    count += self.calculate_stack_space(body);
+                AstNode::While { body, .. } | AstNode::Loop { body, .. } | AstNode::Block { body } => {
+                    offset = self.local_bytes(body, offset);
                 }
                 AstNode::If { then_branch, else_branch, .. } => {
-                    count += self.calculate_stack_space(then_branch);
+                    offset = self.local_bytes(then_branch, offset);
                     if let Some(else_body) = else_branch {
-                        count += self.calculate_stack_space(else_body);
+                        offset = self.local_bytes(else_body, offset);
                     }
                 }
                 _ => {}
             }
         }
-        ((count * 8 + 15) / 16) * 16
+        offset
     }
     
     fn generate_statement(&mut self, node: &AstNode, asm: &mut String) -> Result<(), CompilerError> {
         match node {
-            AstNode::VariableDecl { name, value, .. } => {
+            AstNode::VariableDecl { name, var_type, value, .. } => {
+                let var_type = var_type.as_ref().map(|t| self.resolve(t))
+                    .or_else(|| value.as_ref().and_then(|v| self.expr_type(v)))
+                    .unwrap_or(Type::I64);
+                
                 if let Some(val) = value {
-                    self.generate_expression(val, asm)?;
-                    
-                    self.stack_offset += 8;
-                    self.variables.insert(name.clone(), self.stack_offset);
-                    asm.push_str(&format!("    mov [rbp-{}], rax\n", self.stack_offset));
+                    self.initialize_local(name, var_type, val, asm)?;
                 } else {
-                    self.stack_offset += 8;
-                    self.variables.insert(name.clone(), self.stack_offset);
+                    self.declare_local(name, var_type);
                 }
             }
-            AstNode::ConstDecl { name, value, .. } => {
+            AstNode::ConstDecl { name, const_type, value } => {
+                let const_type = self.resolve(const_type);
+                self.initialize_local(name, const_type, value, asm)?;
+            }
+            AstNode::TupleDestructure { names, value } => {
+                let Some(Type::Tuple(types)) = self.expr_type(value) else {
+                    return Ok(());
+                };
+                let locals: Vec<Local> = names.iter().zip(&types)
+                    .map(|((name, _), elem_type)| {
+                        self.declare_local(name, elem_type.clone());
+                        self.variables[name].clone()
+                    })
+                    .collect();
+                let mark = self.stack_offset;
+                
                 self.generate_expression(value, asm)?;
+                let base = self.spill_rax(asm);
                 
-                self.stack_offset += 8;
-                self.variables.insert(name.clone(), self.stack_offset);
-                asm.push_str(&format!("    mov [rbp-{}], rax\n", self.stack_offset));
+                let mut element_offset = 0;
+                for local in &locals {
+                    asm.push_str(&format!("    mov rax, [rbp-{}]\n", base));
+                    if is_aggregate(&local.var_type) {
+                        asm.push_str(&format!("    lea rax, [rax+{}]\n", element_offset));
+                    } else {
+                        asm.push_str(&format!("    mov rax, [rax+{}]\n", element_offset));
+                    }
+                    self.store_local(local, asm);
+                    element_offset += 8 * type_slots(&local.var_type);
+                }
+                self.stack_offset = mark;
             }
             AstNode::Return { value } => {
+                let has_deferred = self.defer_scopes.iter().any(|scope| !scope.is_empty());
                 if let Some(val) = value {
                     self.generate_expression(val, asm)?;
+                    
+                    if let Some(slot) = self.return_slot {
+                        let slots = self.expr_type(val).map_or(1, |t| type_slots(&t));
+                        asm.push_str(&format!("    mov rdx, [rbp-{}]\n", slot));
+                        self.copy_slots(slots, asm);
+                        asm.push_str(&format!("    mov rax, [rbp-{}]\n", slot));
+                    }
+                    
+                    // The result is computed before deferred statements run.
+                    if has_deferred {
+                        let mark = self.stack_offset;
+                        let saved = self.spill_rax(asm);
+                        self.generate_deferred(0, asm)?;
+                        asm.push_str(&format!("    mov rax, [rbp-{}]\n", saved));
+                        self.stack_offset = mark;
+                    }
                 } else {
+                    self.generate_deferred(0, asm)?;
                     asm.push_str("    xor eax, eax\n");
                 }
                 
                 asm.push_str("    leave\n");
                 asm.push_str("    ret\n");
             }
-            AstNode::Assignment { target, value } => {
+            AstNode::Assignment { target, value, .. } => {
+                let mark = self.stack_offset;
                 self.generate_expression(value, asm)?;
                 
-                if let Some(&offset) = self.variables.get(target) {
-                    asm.push_str(&format!("    mov [rbp-{}], rax\n", offset));
+                if let Some(local) = self.variables.get(target).cloned() {
+                    self.store_local(&local, asm);
+                } else if let Some(static_type @ Type::Array(..)) = self.statics.get(target) {
+                    let slots = type_slots(static_type);
+                    asm.push_str(&format!("    lea rdx, [rel static_{}]\n", symbol(target)));
+                    self.copy_slots(slots, asm);
+                } else if let Some(static_type) = self.statics.get(target) {
+                    if *static_type == Type::F32 {
+                        round_to_f32(asm);
+                    }
+                    asm.push_str(&format!("    mov [rel static_{}], rax\n", symbol(target)));
                 }
+                self.stack_offset = mark;
+            }
+            AstNode::PlaceAssignment { target, op, value, line, column } => {
+                let mark = self.stack_offset;
+                let place_type = self.generate_place(target, asm)?;
+                let address = self.spill_rax(asm);
+                
+                match op {
+                    None => self.generate_expression(value, asm)?,
+                    Some(op) => {
+                        // The current value is read once into a temporary, and the
+                        // operator is then generated as for any other operands.
+                        asm.push_str("    mov rax, [rax]\n");
+                        if let AstNode::FieldAccess { object, .. } = &**target
+                            && matches!(self.expr_type(object).map(|t| self.resolve(&t)), Some(Type::Union { .. })) {
+                            self.normalize_integer(&place_type, asm);
+                        }
+                        let current = format!("place.{}", self.temporary_counter);
+                        self.temporary_counter += 1;
+                        let offset = self.spill_rax(asm);
+                        self.variables.insert(current.clone(), Local { offset, var_type: place_type.clone(), indirect: false });
+                        let combined = AstNode::BinaryOp {
+                            left: Box::new(AstNode::Identifier { name: current.clone(), line: *line, column: *column }),
+                            op: op.clone(),
+                            right: value.clone(),
+                            line: *line,
+                            column: *column,
+                        };
+                        self.generate_expression(&combined, asm)?;
+                        self.variables.remove(&current);
+                    }
+                }
+                
+                if is_aggregate(&place_type) {
+                    asm.push_str(&format!("    mov rdx, [rbp-{}]\n", address));
+                    self.copy_slots(type_slots(&place_type), asm);
+                } else {
+                    if place_type == Type::F32 {
+                        round_to_f32(asm);
+                    }
+                    asm.push_str(&format!("    mov rcx, [rbp-{}]\n", address));
+                    asm.push_str("    mov [rcx], rax\n");
+                }
+                self.stack_offset = mark;
             }
             AstNode::If { condition, then_branch, else_branch } => {
+                // At -O2 a constant condition keeps only the branch that runs.
+                if self.opt_level >= 2 {
+                    if let Some(value) = optimizer::constant_condition(condition) {
+                        let taken = if value { Some(then_branch) } else { else_branch.as_ref() };
+                        if let Some(body) = taken {
+                            self.generate_scope(body, asm)?;
+                        }
+                        return Ok(());
+                    }
+                    
+                    // Both values are computed and a cmov picks one, so there is no branch to mispredict.
+                    if let Some((target, then_value, else_value)) = optimizer::conditional_assignment(then_branch, else_branch.as_deref())
+                        && let Some(local) = self.variables.get(target).filter(|l| !is_aggregate(&l.var_type)).cloned() {
+                        let mark = self.stack_offset;
+                        self.generate_expression(condition, asm)?;
+                        let condition_slot = self.spill_rax(asm);
+                        self.generate_expression(else_value, asm)?;
+                        let else_slot = self.spill_rax(asm);
+                        self.generate_expression(then_value, asm)?;
+                        asm.push_str(&format!("    mov rcx, [rbp-{}]\n", else_slot));
+                        asm.push_str(&format!("    mov rdx, [rbp-{}]\n", condition_slot));
+                        asm.push_str("    test rdx, rdx\n");
+                        asm.push_str("    cmovz rax, rcx\n");
+                        self.store_local(&local, asm);
+                        self.stack_offset = mark;
+                        return Ok(());
+                    }
+                }
+                
                 let else_label = self.next_label();
                 let end_label = self.next_label();
                 
@@ -171,59 +659,75 @@ impl CodeGenerator {
                 asm.push_str("    test rax, rax\n");
                 asm.push_str(&format!("    jz {}\n", else_label));
                 
-                for stmt in then_branch {
-                    self.generate_statement(stmt, asm)?;
-                }
+                self.generate_scope(then_branch, asm)?;
                 asm.push_str(&format!("    jmp {}\n", end_label));
                 
                 asm.push_str(&format!("{}:\n", else_label));
                 if let Some(else_body) = else_branch {
-                    for stmt in else_body {
-                        self.generate_statement(stmt, asm)?;
-                    }
+                    self.generate_scope(else_body, asm)?;
                 }
                 
                 asm.push_str(&format!("{}:\n", end_label));
             }
-            AstNode::While { condition, body } => {
+            AstNode::Block { body } => {
+                self.generate_scope(body, asm)?;
+            }
+            AstNode::While { condition, body, label } => {
+                if self.opt_level >= 1
+                    && let Some(replacement) = optimizer::constant_while(condition, body, label) {
+                    for stmt in &replacement {
+                        self.generate_statement(stmt, asm)?;
+                    }
+                    return Ok(());
+                }
+                
                 let start_label = self.next_label();
                 let end_label = self.next_label();
                 
-                self.loop_stack.push((end_label.clone(), start_label.clone()));
+                self.push_loop(label, &end_label, &start_label);
                 
                 asm.push_str(&format!("{}:\n", start_label));
                 self.generate_expression(condition, asm)?;
                 asm.push_str("    test rax, rax\n");
                 asm.push_str(&format!("    jz {}\n", end_label));
                 
-                for stmt in body {
-                    self.generate_statement(stmt, asm)?;
-                }
+                self.generate_scope(body, asm)?;
                 
                 asm.push_str(&format!("    jmp {}\n", start_label));
                 asm.push_str(&format!("{}:\n", end_label));
                 
                 self.loop_stack.pop();
             }
-            AstNode::For { iterator, range_start, range_end, inclusive, body } => {
+            AstNode::For { iterator, range_start, range_end, inclusive, body, label } => {
+                if self.opt_level >= 3
+                    && let Some(replacement) = optimizer::unroll_for(iterator, range_start, range_end, *inclusive, body) {
+                    for stmt in &replacement {
+                        self.generate_statement(stmt, asm)?;
+                    }
+                    return Ok(());
+                }
+                
                 let start_label = self.next_label();
                 let end_label = self.next_label();
+                let increment_label = self.next_label();
+                
+                // The iterator and end value live only as long as the loop.
+                let outer = self.variables.clone();
+                let mark = self.stack_offset;
                 
                 self.generate_expression(range_start, asm)?;
-                self.stack_offset += 8;
-                self.variables.insert(iterator.clone(), self.stack_offset);
-                asm.push_str(&format!("    mov [rbp-{}], rax\n", self.stack_offset));
+                let iter_offset = self.declare_local(iterator, Type::I32);
+                asm.push_str(&format!("    mov [rbp-{}], rax\n", iter_offset));
                 
                 self.generate_expression(range_end, asm)?;
-                self.stack_offset += 8;
-                let end_offset = self.stack_offset;
+                let end_offset = self.allocate_slots(1);
                 asm.push_str(&format!("    mov [rbp-{}], rax\n", end_offset));
                 
-                self.loop_stack.push((end_label.clone(), start_label.clone()));
+                // `continue` must still advance the iterator, so it targets the increment.
+                self.push_loop(label, &end_label, &increment_label);
                 
                 asm.push_str(&format!("{}:\n", start_label));
                 
-                let iter_offset = *self.variables.get(iterator).unwrap();
                 asm.push_str(&format!("    mov rax, [rbp-{}]\n", iter_offset));
                 asm.push_str(&format!("    mov rcx, [rbp-{}]\n", end_offset));
                 asm.push_str("    cmp rax, rcx\n");
@@ -234,11 +738,16 @@ impl CodeGenerator {
                     asm.push_str(&format!("    jge {}\n", end_label));
                 }
                 
-                for stmt in body {
-                    self.generate_statement(stmt, asm)?;
-                }
+                self.generate_scope(body, asm)?;
                 
+                asm.push_str(&format!("{}:\n", increment_label));
                 asm.push_str(&format!("    mov rax, [rbp-{}]\n", iter_offset));
+                // An inclusive range stops once the end has run, so an end at the
+                // type's maximum never wraps the iterator.
+                if *inclusive {
+                    asm.push_str(&format!("    cmp rax, [rbp-{}]\n", end_offset));
+                    asm.push_str(&format!("    jge {}\n", end_label));
+                }
                 asm.push_str("    inc rax\n");
                 asm.push_str(&format!("    mov [rbp-{}], rax\n", iter_offset));
                 
@@ -246,39 +755,158 @@ impl CodeGenerator {
                 asm.push_str(&format!("{}:\n", end_label));
                 
                 self.loop_stack.pop();
-                self.stack_offset -= 8; // Clean up end value.
+                self.variables = outer;
+                self.stack_offset = mark;
             }
-            AstNode::Loop { body } => {
+            AstNode::Loop { body, label } => {
                 let start_label = self.next_label();
                 let end_label = self.next_label();
                 
-                self.loop_stack.push((end_label.clone(), start_label.clone()));
+                self.push_loop(label, &end_label, &start_label);
                 
                 asm.push_str(&format!("{}:\n", start_label));
                 
-                for stmt in body {
-                    self.generate_statement(stmt, asm)?;
-                }
+                self.generate_scope(body, asm)?;
                 
                 asm.push_str(&format!("    jmp {}\n", start_label));
                 asm.push_str(&format!("{}:\n", end_label));
                 
                 self.loop_stack.pop();
             }
-            AstNode::Break => {
-                if let Some((break_label, _)) = self.loop_stack.last() {
-                    asm.push_str(&format!("    jmp {}\n", break_label));
+            AstNode::Function { name, params, return_type, .. } => {
+                let label = format!("{}.{}", self.current_label, symbol(name));
+                let signature = self.signature(params, return_type);
+                self.functions.insert(name.clone(), signature);
+                self.local_functions.insert(name.clone(), label.clone());
+                self.pending_functions.push((label, node.clone()));
+            }
+            AstNode::Break { label, value } => {
+                if let Some(frame) = self.find_loop(label).cloned() {
+                    // The loop's value is left in rax at the end label.
+                    let has_deferred = self.defer_scopes[frame.defer_depth..].iter().any(|scope| !scope.is_empty());
+                    if let Some(value) = value {
+                        self.generate_expression(value, asm)?;
+                        if has_deferred {
+                            let mark = self.stack_offset;
+                            let saved = self.spill_rax(asm);
+                            self.generate_deferred(frame.defer_depth, asm)?;
+                            asm.push_str(&format!("    mov rax, [rbp-{}]\n", saved));
+                            self.stack_offset = mark;
+                        }
+                    } else {
+                        self.generate_deferred(frame.defer_depth, asm)?;
+                    }
+                    asm.push_str(&format!("    jmp {}\n", frame.break_label));
                 }
             }
-            AstNode::Continue => {
-                if let Some((_, continue_label)) = self.loop_stack.last() {
-                    asm.push_str(&format!("    jmp {}\n", continue_label));
+            AstNode::Continue { label } => {
+                if let Some(frame) = self.find_loop(label).cloned() {
+                    self.generate_deferred(frame.defer_depth, asm)?;
+                    asm.push_str(&format!("    jmp {}\n", frame.continue_label));
                 }
             }
+            AstNode::BlockValue { value } => {
+                self.generate_statement(value, asm)?;
+            }
+            AstNode::Defer { statement } => {
+                let deferred = Deferred {
+                    statement: (**statement).clone(),
+                    variables: self.variables.clone(),
+                };
+                self.defer_scopes.last_mut().unwrap().push(deferred);
+            }
             _ => {
+                let mark = self.stack_offset;
                 self.generate_expression(node, asm)?;
+                self.stack_offset = mark;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Generates `body` in its own scope. Names declared inside go out of
+    /// scope at the end, and their slots are reused by the code that follows.
+    fn generate_scope(&mut self, body: &[AstNode], asm: &mut String) -> Result<(), CompilerError> {
+        let outer = self.variables.clone();
+        let mark = self.stack_offset;
+        self.defer_scopes.push(Vec::new());
+        self.generate_statements(body, asm)?;
+        // A block ending in a jump has already run its deferred statements.
+        if !matches!(body.last(), Some(AstNode::Return { .. } | AstNode::Break { .. } | AstNode::Continue { .. })) {
+            // The value a block ends in is kept in rax past them.
+            let has_deferred = self.defer_scopes.last().is_some_and(|scope| !scope.is_empty());
+            if has_deferred && matches!(body.last(), Some(AstNode::BlockValue { .. } | AstNode::If { .. })) {
+                let saved = self.spill_rax(asm);
+                self.generate_deferred(self.defer_scopes.len() - 1, asm)?;
+                asm.push_str(&format!("    mov rax, [rbp-{}]\n", saved));
+            } else {
+                self.generate_deferred(self.defer_scopes.len() - 1, asm)?;
             }
         }
+        self.defer_scopes.pop();
+        self.variables = outer;
+        self.stack_offset = mark;
+        Ok(())
+    }
+    
+    /// Generates the statements of a block. At -O2 a store that is overwritten
+    /// before any read is dropped, keeping only the calls in its value.
+    fn generate_statements(&mut self, body: &[AstNode], asm: &mut String) -> Result<(), CompilerError> {
+        for (i, stmt) in body.iter().enumerate() {
+            let dead = self.opt_level >= 2
+                && optimizer::dead_store(body, i, |n| self.statics.contains_key(n)).is_some();
+            if !dead {
+                let split = if self.opt_level >= 2 {
+                    optimizer::common_subexpressions(stmt, &mut self.temporary_counter)
+                } else {
+                    None
+                };
+                match split {
+                    Some((temporaries, stmt)) => {
+                        for (name, value) in &temporaries {
+                            let var_type = self.expr_type(value).unwrap_or(Type::I64);
+                            self.initialize_local(name, var_type, value, asm)?;
+                        }
+                        self.generate_statement(&stmt, asm)?;
+                    }
+                    None => self.generate_statement(stmt, asm)?,
+                }
+                continue;
+            }
+            
+            let value = match stmt {
+                AstNode::VariableDecl { name, var_type, value: Some(value), .. } => {
+                    let var_type = var_type.as_ref().map(|t| self.resolve(t))
+                        .or_else(|| self.expr_type(value))
+                        .unwrap_or(Type::I64);
+                    self.declare_local(name, var_type);
+                    value
+                }
+                AstNode::Assignment { value, .. } => value,
+                _ => continue,
+            };
+            if optimizer::has_call(value) {
+                let mark = self.stack_offset;
+                self.generate_expression(value, asm)?;
+                self.stack_offset = mark;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Emits the statements deferred in defer scope `depth` and every scope
+    /// inside it, most recent first. Each sees the variables it was deferred with.
+    fn generate_deferred(&mut self, depth: usize, asm: &mut String) -> Result<(), CompilerError> {
+        let pending: Vec<Deferred> = self.defer_scopes[depth..].iter().rev()
+            .flat_map(|scope| scope.iter().rev().cloned())
+            .collect();
+        for deferred in pending {
+            let current = std::mem::replace(&mut self.variables, deferred.variables);
+            let mark = self.stack_offset;
+            self.generate_statement(&deferred.statement, asm)?;
+            self.stack_offset = mark;
+            self.variables = current;
+        }
         Ok(())
     }
     
@@ -289,44 +917,235 @@ impl CodeGenerator {
                     Literal::Int(n) => {
                         asm.push_str(&format!("    mov rax, {}\n", n));
                     }
+                    // Written as the bit pattern, which doesn't depend on how NASM reads a decimal above i64::MAX.
+                    Literal::UInt(n) => {
+                        asm.push_str(&format!("    mov rax, 0x{:016X}\n", n));
+                    }
                     Literal::Char(c) => {
                         asm.push_str(&format!("    mov rax, {}\n", *c as u32));
                     }
                     Literal::Bool(b) => {
                         asm.push_str(&format!("    mov rax, {}\n", if *b { 1 } else { 0 }));
                     }
+                    Literal::Float(f) => {
+                        asm.push_str(&format!("    mov rax, 0x{:016X}\n", f.to_bits()));
+                    }
                     Literal::String(s) => {
                         let index = self.string_literals.len();
                         self.string_literals.push(s.clone());
                         asm.push_str(&format!("    lea rax, [rel str_{}]\n", index));
                     }
-                    _ => {}
                 }
             }
-            AstNode::Identifier(name) => {
-                if let Some(&offset) = self.variables.get(name) {
-                    asm.push_str(&format!("    mov rax, [rbp-{}]\n", offset));
+            AstNode::Identifier { name, .. } => {
+                if let Some(local) = self.variables.get(name) {
+                    // Arrays and tuples evaluate to their address.
+                    if is_aggregate(&local.var_type) && !local.indirect {
+                        asm.push_str(&format!("    lea rax, [rbp-{}]\n", local.offset));
+                    } else if local.var_type == Type::Bool {
+                        asm.push_str(&format!("    movzx eax, byte [rbp-{}]\n", local.offset));
+                    } else {
+                        asm.push_str(&format!("    mov rax, [rbp-{}]\n", local.offset));
+                    }
+                } else if let Some(static_type) = self.statics.get(name) {
+                    // RIP-relative, so the image can be loaded at any address.
+                    if is_aggregate(static_type) {
+                        asm.push_str(&format!("    lea rax, [rel static_{}]\n", symbol(name)));
+                    } else {
+                        asm.push_str(&format!("    mov rax, [rel static_{}]\n", symbol(name)));
+                    }
+                }
+            }
+            AstNode::ArrayIndex { array, index } => {
+                let array_type = self.expr_type(array).map(|t| self.resolve(&t));
+                
+                self.generate_expression(index, asm)?;
+                let spill = self.spill_rax(asm);
+                
+                self.generate_expression(array, asm)?;
+                asm.push_str(&format!("    mov rcx, [rbp-{}]\n", spill));
+                
+                match array_type {
+                    Some(Type::Str) => asm.push_str("    movzx rax, byte [rax+rcx]\n"),
+                    Some(Type::Array(elem_type, _)) if is_aggregate(&elem_type) => {
+                        asm.push_str(&format!("    imul rcx, rcx, {}\n", 8 * type_slots(&elem_type)));
+                        asm.push_str("    lea rax, [rax+rcx]\n");
+                    }
+                    _ => asm.push_str("    mov rax, [rax+rcx*8]\n"),
+                }
+            }
+            AstNode::ArrayLiteral { elements } => {
+                let elem_slots = elements.first()
+                    .and_then(|e| self.expr_type(e))
+                    .map_or(1, |t| type_slots(&t));
+                let offset = self.allocate_slots(elem_slots * elements.len());
+                self.frame_has_array = true;
+                
+                for (i, element) in elements.iter().enumerate() {
+                    self.generate_expression(element, asm)?;
+                    let element_offset = offset - (8 * elem_slots * i) as i32;
+                    if elem_slots == 1 {
+                        asm.push_str(&format!("    mov [rbp-{}], rax\n", element_offset));
+                    } else {
+                        asm.push_str(&format!("    lea rdx, [rbp-{}]\n", element_offset));
+                        self.copy_slots(elem_slots, asm);
+                    }
+                }
+                
+                asm.push_str(&format!("    lea rax, [rbp-{}]\n", offset));
+            }
+            AstNode::TupleLiteral { elements } => {
+                let types: Vec<Type> = elements.iter()
+                    .map(|e| self.expr_type(e).unwrap_or(Type::I64))
+                    .collect();
+                let offset = self.allocate_slots(types.iter().map(type_slots).sum());
+                
+                let mut element_offset = offset;
+                for (element, elem_type) in elements.iter().zip(&types) {
+                    self.generate_expression(element, asm)?;
+                    if is_aggregate(elem_type) {
+                        asm.push_str(&format!("    lea rdx, [rbp-{}]\n", element_offset));
+                        self.copy_slots(type_slots(elem_type), asm);
+                    } else {
+                        asm.push_str(&format!("    mov [rbp-{}], rax\n", element_offset));
+                    }
+                    element_offset -= 8 * type_slots(elem_type) as i32;
                 }
+                
+                asm.push_str(&format!("    lea rax, [rbp-{}]\n", offset));
             }
-            AstNode::BinaryOp { left, op, right } => {
+            AstNode::StructLiteral { name, fields, .. } => {
+                let struct_type = self.resolve(&Type::Named(name.clone()));
+                let offset = self.allocate_slots(type_slots(&struct_type));
+                match &struct_type {
+                    Type::Struct { .. } => {}
+                    Type::Union { .. } => {
+                        // Slots past the field that is set read as zero.
+                        for slot in 0..type_slots(&struct_type) {
+                            asm.push_str(&format!("    mov qword [rbp-{}], 0\n", offset - 8 * slot as i32));
+                        }
+                    }
+                    _ => return Err(CompilerError::CodeGenError(format!("Unknown struct '{}'", name))),
+                }
+                
+                // Fields are evaluated in source order but laid out in declaration order.
+                for (field, value) in fields {
+                    let (slot, field_type) = field_slot(&struct_type, field).unwrap();
+                    let field_offset = offset - 8 * slot as i32;
+                    self.generate_expression(value, asm)?;
+                    if is_aggregate(field_type) {
+                        asm.push_str(&format!("    lea rdx, [rbp-{}]\n", field_offset));
+                        self.copy_slots(type_slots(field_type), asm);
+                    } else {
+                        if *field_type == Type::F32 {
+                            round_to_f32(asm);
+                        }
+                        asm.push_str(&format!("    mov [rbp-{}], rax\n", field_offset));
+                    }
+                }
+                
+                asm.push_str(&format!("    lea rax, [rbp-{}]\n", offset));
+            }
+            AstNode::FieldAccess { object, field, .. } => {
+                let object_type = self.expr_type(object).map(|t| self.resolve(&t));
+                let Some((slot, field_type)) = object_type.as_ref().and_then(|t| field_slot(t, field)) else {
+                    return Err(CompilerError::CodeGenError(format!("Cannot access field '{}'", field)));
+                };
+                let field_type = field_type.clone();
+                self.generate_expression(object, asm)?;
+                if is_aggregate(&field_type) {
+                    if slot > 0 {
+                        asm.push_str(&format!("    lea rax, [rax+{}]\n", 8 * slot));
+                    }
+                } else {
+                    asm.push_str(&format!("    mov rax, [rax+{}]\n", 8 * slot));
+                    // The slot may hold a wider field's value.
+                    if matches!(object_type, Some(Type::Union { .. })) {
+                        self.normalize_integer(&field_type, asm);
+                    }
+                }
+            }
+            AstNode::ArrayRepeat { value, count } => {
+                let count = *count as usize;
+                let elem_slots = self.expr_type(value).map_or(1, |t| type_slots(&t));
+                let offset = self.allocate_slots(elem_slots * count);
+                self.frame_has_array = true;
+                
+                self.generate_expression(value, asm)?;
+                if elem_slots == 1 {
+                    let loop_label = self.next_label();
+                    let end_label = self.next_label();
+                    
+                    asm.push_str(&format!("    lea rdx, [rbp-{}]\n", offset));
+                    asm.push_str("    xor rcx, rcx\n");
+                    asm.push_str(&format!("{}:\n", loop_label));
+                    asm.push_str(&format!("    cmp rcx, {}\n", count));
+                    asm.push_str(&format!("    jge {}\n", end_label));
+                    asm.push_str("    mov [rdx+rcx*8], rax\n");
+                    asm.push_str("    inc rcx\n");
+                    asm.push_str(&format!("    jmp {}\n", loop_label));
+                    asm.push_str(&format!("{}:\n", end_label));
+                } else {
+                    for i in 0..count {
+                        asm.push_str(&format!("    lea rdx, [rbp-{}]\n", offset - (8 * elem_slots * i) as i32));
+                        self.copy_slots(elem_slots, asm);
+                    }
+                }
+                
+                asm.push_str(&format!("    lea rax, [rbp-{}]\n", offset));
+            }
+            AstNode::BinaryOp { left, op, right, .. } => {
+                if self.opt_level >= 1 && self.generate_power_of_two(left, op, right, asm)? {
+                    return Ok(());
+                }
+                if (op == "==" || op == "!=")
+                    && let Some(array_type @ Type::Array(..)) = self.expr_type(left).map(|t| self.resolve(&t)) {
+                    return self.generate_array_comparison(left, op, right, &array_type, asm);
+                }
+                if matches!(op.as_str(), "+" | "-" | "*" | "/" | "%" | "<" | "<=" | ">" | ">=" | "==" | "!=")
+                    && let Some(float_type) = self.expr_type(left).map(|t| self.resolve(&t)).filter(Type::is_float) {
+                    return self.generate_float_binary(left, op, right, &float_type, asm);
+                }
+                
+                // The narrower of two integer operands is extended to the promoted type.
+                let (left_type, right_type) = self.operand_types(left, right);
+                let promoted = match (&left_type, &right_type) {
+                    (Some(l), Some(r)) if l != r && !matches!(op.as_str(), "<<" | ">>") => l.promote(r),
+                    _ => None,
+                };
+                
                 self.generate_expression(right, asm)?;
-                asm.push_str("    push rax\n");
+                if let (Some(promoted), Some(right_type)) = (&promoted, &right_type)
+                    && promoted != right_type {
+                    self.normalize_integer(right_type, asm);
+                }
+                let spill = self.spill_rax(asm);
                 
                 self.generate_expression(left, asm)?;
-                asm.push_str("    pop rcx\n");
+                if let (Some(promoted), Some(left_type)) = (&promoted, &left_type)
+                    && promoted != left_type {
+                    self.normalize_integer(left_type, asm);
+                }
+                asm.push_str(&format!("    mov rcx, [rbp-{}]\n", spill));
                 
+                // Unsigned operands divide with `div` and compare with the below/above conditions.
+                let unsigned = promoted.or(left_type).is_some_and(|t| t.is_integer() && !t.is_signed());
+                let (less, greater) = if unsigned { ("b", "a") } else { ("l", "g") };
                 match op.as_str() {
                     "+" => asm.push_str("    add rax, rcx\n"),
                     "-" => asm.push_str("    sub rax, rcx\n"),
                     "*" => asm.push_str("    imul rax, rcx\n"),
-                    "/" => {
-                        asm.push_str("    xor rdx, rdx\n");
-                        asm.push_str("    idiv rcx\n");
-                    }
-                    "%" => {
-                        asm.push_str("    xor rdx, rdx\n");
-                        asm.push_str("    idiv rcx\n");
-                        asm.push_str("    mov rax, rdx\n");
+                    "/" | "%" => {
+                        if unsigned {
+                            asm.push_str("    xor rdx, rdx\n");
+                            asm.push_str("    div rcx\n");
+                        } else {
+                            asm.push_str("    cqo\n");
+                            asm.push_str("    idiv rcx\n");
+                        }
+                        if op == "%" {
+                            asm.push_str("    mov rax, rdx\n");
+                        }
                     }
                     "==" => {
                         asm.push_str("    cmp rax, rcx\n");
@@ -340,36 +1159,64 @@ impl CodeGenerator {
                     }
                     "<" => {
                         asm.push_str("    cmp rax, rcx\n");
-                        asm.push_str("    setl al\n");
+                        asm.push_str(&format!("    set{} al\n", less));
                         asm.push_str("    movzx rax, al\n");
                     }
                     "<=" => {
                         asm.push_str("    cmp rax, rcx\n");
-                        asm.push_str("    setle al\n");
+                        asm.push_str(&format!("    set{}e al\n", less));
                         asm.push_str("    movzx rax, al\n");
                     }
                     ">" => {
                         asm.push_str("    cmp rax, rcx\n");
-                        asm.push_str("    setg al\n");
+                        asm.push_str(&format!("    set{} al\n", greater));
                         asm.push_str("    movzx rax, al\n");
                     }
                     ">=" => {
                         asm.push_str("    cmp rax, rcx\n");
-                        asm.push_str("    setge al\n");
+                        asm.push_str(&format!("    set{}e al\n", greater));
                         asm.push_str("    movzx rax, al\n");
                     }
+                    // Logical results are always exactly 0 or 1.
                     "&&" => {
-                        asm.push_str("    and rax, rcx\n");
+                        asm.push_str("    test rax, rax\n");
+                        asm.push_str("    setne al\n");
+                        asm.push_str("    test rcx, rcx\n");
+                        asm.push_str("    setne cl\n");
+                        asm.push_str("    and al, cl\n");
+                        asm.push_str("    movzx rax, al\n");
                     }
                     "||" => {
                         asm.push_str("    or rax, rcx\n");
+                        asm.push_str("    setne al\n");
+                        asm.push_str("    movzx rax, al\n");
+                    }
+                    // Both operands are already sign- or zero-extended, and so is the result.
+                    "&" => asm.push_str("    and rax, rcx\n"),
+                    "|" => asm.push_str("    or rax, rcx\n"),
+                    "^" => asm.push_str("    xor rax, rcx\n"),
+                    "<<" | ">>" => {
+                        let operand_type = self.expr_type(left).unwrap_or(Type::I64);
+                        let width = operand_type.bit_width().unwrap_or(64);
+                        
+                        asm.push_str(&format!("    and rcx, {}\n", width - 1));
+                        if op == "<<" {
+                            asm.push_str("    shl rax, cl\n");
+                            self.normalize_integer(&operand_type, asm);
+                        } else if operand_type.is_signed() {
+                            asm.push_str("    sar rax, cl\n");
+                        } else {
+                            asm.push_str("    shr rax, cl\n");
+                        }
                     }
                     _ => {}
                 }
             }
             AstNode::UnaryOp { op, operand } => {
                 self.generate_expression(operand, asm)?;
+                let is_float = self.expr_type(operand).is_some_and(|t| self.resolve(&t).is_float());
                 match op.as_str() {
+                    "-" if is_float => asm.push_str("    btc rax, 63\n"),
                     "-" => asm.push_str("    neg rax\n"),
                     "!" => {
                         asm.push_str("    test rax, rax\n");
@@ -379,21 +1226,71 @@ impl CodeGenerator {
                     _ => {}
                 }
             }
-            AstNode::FunctionCall { name, args } => {
-                if name == "print" && !args.is_empty() {
+            AstNode::FunctionCall { name, args, line, .. } => {
+                if name == "assert" {
+                    let ok_label = self.next_label();
+                    self.generate_expression(&args[0], asm)?;
+                    asm.push_str("    test rax, rax\n");
+                    asm.push_str(&format!("    jnz {}\n", ok_label));
+                    self.generate_abort("assertion failed", &args[1], *line, asm)?;
+                    asm.push_str(&format!("{}:\n", ok_label));
+                } else if name == "panic" {
+                    self.generate_abort("panic", &args[0], *line, asm)?;
+                } else if name == "print" && !args.is_empty() {
+                    let value_type = self.expr_type(&args[0]).map(|t| self.resolve(&t));
                     if let AstNode::Literal(Literal::String(s)) = &args[0] {
-                        let index = self.string_literals.len();
-                        self.string_literals.push(format!("{}\n", s));
-                        asm.push_str(&format!("    lea rcx, [rel str_{}]\n", index));
+                        self.emit_printf(&format!("{}\n", s), asm);
+                    } else if let Some(array_type @ Type::Array(..)) = value_type {
+                        self.generate_expression(&args[0], asm)?;
+                        self.generate_array_print(&array_type, "\n", asm);
                     } else {
+                        // Scalars are formatted by printf according to their type.
                         self.generate_expression(&args[0], asm)?;
-                        asm.push_str("    mov rcx, rax\n");
+                        asm.push_str("    mov rdx, rax\n");
+                        if value_type.as_ref().is_some_and(Type::is_float) {
+                            // A variadic float goes in both xmm1 and rdx.
+                            asm.push_str("    movq xmm1, rax\n");
+                        }
+                        self.emit_printf(&format!("{}\n", print_format(value_type.as_ref())), asm);
+                    }
+                } else if name == "print_hex" {
+                    // Narrow values are printed at their own width, so an i8 -1 is `ff`.
+                    self.generate_expression(&args[0], asm)?;
+                    match self.expr_type(&args[0]).and_then(|t| self.resolve(&t).bit_width()) {
+                        Some(8) => asm.push_str("    movzx eax, al\n"),
+                        Some(16) => asm.push_str("    movzx eax, ax\n"),
+                        Some(32) => asm.push_str("    mov eax, eax\n"),
+                        _ => {}
                     }
+                    asm.push_str("    mov rdx, rax\n");
+                    self.emit_printf("%llx", asm);
+                } else if name == "print_char" {
+                    self.generate_expression(&args[0], asm)?;
+                    asm.push_str("    mov ecx, eax\n");
                     asm.push_str("    sub rsp, 32\n");
-                    asm.push_str("    call printf\n");
+                    asm.push_str("    call putchar\n");
                     asm.push_str("    add rsp, 32\n");
+                } else if name == "printf" {
+                    self.generate_printf(args, asm)?;
+                } else if let Some(value) = self.const_functions.call(name, args, &HashMap::new()) {
+                    // A `const fn` called with constant arguments is evaluated here.
+                    self.generate_expression(&AstNode::Literal(value), asm)?;
+                } else if self.is_intrinsic(name) {
+                    self.generate_intrinsic(name, args, asm)?;
+                } else if name != "print" {
+                    self.generate_call(name, args, asm)?;
                 }
             }
+            AstNode::MethodCall { receiver, method, args, .. } => {
+                let Some(symbol) = self.method_target(receiver, method) else {
+                    return Err(CompilerError::CodeGenError(format!("Cannot call method '{}'", method)));
+                };
+                // The receiver is passed as the hidden first argument.
+                let mut call_args = vec![(**receiver).clone()];
+                call_args.extend(args.iter().cloned());
+                self.generate_call(&symbol, &call_args, asm)?;
+            }
+            AstNode::Loop { .. } | AstNode::If { .. } => self.generate_statement(node, asm)?,
             _ => {}
         }
         Ok(())
@@ -407,8 +1304,28 @@ impl CodeGenerator {
                     self.generate_node(item, output)?;
                 }
             }
-            AstNode::Function { name, params, return_type, body } => {
-                output.push_str(&format!("function {}(", name));
+            AstNode::TypeAlias { name, target } => {
+                output.push_str(&format!("type {} = {:?}\n", name, target));
+            }
+            AstNode::StructDecl { name, fields } => {
+                let fields: Vec<String> = fields.iter().map(|(f, t)| format!("{}: {:?}", f, t)).collect();
+                output.push_str(&format!("struct {} {{ {} }}\n", name, fields.join(", ")));
+            }
+            AstNode::UnionDecl { name, fields } => {
+                let fields: Vec<String> = fields.iter().map(|(f, t)| format!("{}: {:?}", f, t)).collect();
+                output.push_str(&format!("union {} {{ {} }}\n", name, fields.join(", ")));
+            }
+            AstNode::Static { name, static_type, mutable, .. } => {
+                output.push_str(&format!("static {}{}: {:?}\n", if *mutable { "mut " } else { "" }, name, static_type));
+            }
+            AstNode::Function { name, params, return_type, body, attributes, constant, .. } => {
+                for attribute in attributes {
+                    match &attribute.argument {
+                        Some(argument) => output.push_str(&format!("@{}({:?})\n", attribute.name, argument)),
+                        None => output.push_str(&format!("@{}\n", attribute.name)),
+                    }
+                }
+                output.push_str(&format!("{}function {}(", if *constant { "const " } else { "" }, name));
                 for (i, (param_name, param_type)) in params.iter().enumerate() {
                     if i > 0 {
                         output.push_str(", ");
@@ -434,9 +1351,873 @@ impl CodeGenerator {
         Ok(())
     }
     
+    /// Emits `*` and `/` by a constant power of two as shifts. Returns false,
+    /// emitting nothing, when the operation doesn't qualify.
+    fn generate_power_of_two(&mut self, left: &AstNode, op: &str, right: &AstNode, asm: &mut String) -> Result<bool, CompilerError> {
+        let (operand, shift) = match (op, power_of_two_exponent(left), power_of_two_exponent(right)) {
+            ("*", _, Some(k)) | ("/", _, Some(k)) => (left, k),
+            ("*", Some(k), None) => (right, k),
+            _ => return Ok(false),
+        };
+        
+        let operand_type = self.expr_type(operand).unwrap_or(Type::I64);
+        if !operand_type.is_integer() {
+            return Ok(false);
+        }
+        
+        self.generate_expression(operand, asm)?;
+        if shift == 0 {
+            return Ok(true);
+        }
+        
+        if op == "*" {
+            asm.push_str(&format!("    shl rax, {}\n", shift));
+        } else if operand_type.is_signed() {
+            // Division rounds toward zero, so bias negative values by 2^k - 1 first.
+            asm.push_str("    mov rcx, rax\n");
+            asm.push_str("    sar rcx, 63\n");
+            asm.push_str(&format!("    shr rcx, {}\n", 64 - shift));
+            asm.push_str("    add rax, rcx\n");
+            asm.push_str(&format!("    sar rax, {}\n", shift));
+        } else {
+            asm.push_str(&format!("    shr rax, {}\n", shift));
+        }
+        Ok(true)
+    }
+    
+    /// Function a method call on `receiver` compiles to.
+    fn method_target(&self, receiver: &AstNode, method: &str) -> Option<String> {
+        match self.resolve(&self.expr_type(receiver)?) {
+            Type::Struct { name, .. } => Some(method_symbol(&name, method)),
+            _ => None,
+        }
+    }
+    
+    /// Replaces alias names; semantic analysis has already rejected unknown ones.
+    fn resolve(&self, t: &Type) -> Type {
+        t.resolve(&self.type_aliases).unwrap_or_else(|_| t.clone())
+    }
+    
+    /// `min`, `max` and `abs` are compiled inline unless the program defines its own.
+    fn is_intrinsic(&self, name: &str) -> bool {
+        matches!(name, "min" | "max" | "abs")
+            && !self.functions.contains_key(name)
+            && !self.local_functions.contains_key(name)
+    }
+    
+    /// Operand type of an intrinsic call. A literal argument adopts the other's type.
+    fn intrinsic_type(&self, args: &[AstNode]) -> Option<Type> {
+        let typed = args.iter().find(|a| !matches!(a, AstNode::Literal(_))).or(args.first())?;
+        self.expr_type(typed).map(|t| self.resolve(&t))
+    }
+    
+    /// Emits `min`, `max` and `abs` without branches: `cmov` for integers,
+    /// `minsd`/`maxsd` and a sign-bit clear for floats.
+    fn generate_intrinsic(&mut self, name: &str, args: &[AstNode], asm: &mut String) -> Result<(), CompilerError> {
+        let operand_type = self.intrinsic_type(args).unwrap_or(Type::I64);
+        
+        if name == "abs" {
+            self.generate_expression(&args[0], asm)?;
+            if operand_type.is_float() {
+                asm.push_str("    btr rax, 63\n");
+            } else if operand_type.is_signed() {
+                asm.push_str("    mov rcx, rax\n");
+                asm.push_str("    neg rcx\n");
+                asm.push_str("    cmovns rax, rcx\n");
+            }
+            return Ok(());
+        }
+        
+        let mark = self.stack_offset;
+        self.generate_expression(&args[0], asm)?;
+        let left = self.spill_rax(asm);
+        self.generate_expression(&args[1], asm)?;
+        self.stack_offset = mark;
+        
+        if operand_type.is_float() {
+            let instruction = if name == "min" { "minsd" } else { "maxsd" };
+            asm.push_str(&format!("    movq xmm0, [rbp-{}]\n", left));
+            asm.push_str("    movq xmm1, rax\n");
+            asm.push_str(&format!("    {} xmm0, xmm1\n", instruction));
+            asm.push_str("    movq rax, xmm0\n");
+            return Ok(());
+        }
+        
+        // Take the second operand when it is smaller (min) or larger (max).
+        let condition = match (name, operand_type.is_signed()) {
+            ("min", true) => "g",
+            ("min", false) => "a",
+            (_, true) => "l",
+            (_, false) => "b",
+        };
+        asm.push_str("    mov rcx, rax\n");
+        asm.push_str(&format!("    mov rax, [rbp-{}]\n", left));
+        asm.push_str("    cmp rax, rcx\n");
+        asm.push_str(&format!("    cmov{} rax, rcx\n", condition));
+        Ok(())
+    }
+    
+    /// Re-extends `rax` from the low bits of `var_type` so narrow values stay canonical.
+    fn normalize_integer(&self, var_type: &Type, asm: &mut String) {
+        match var_type {
+            Type::I8 => asm.push_str("    movsx rax, al\n"),
+            Type::I16 => asm.push_str("    movsx rax, ax\n"),
+            Type::I32 => asm.push_str("    movsxd rax, eax\n"),
+            Type::U8 => asm.push_str("    movzx eax, al\n"),
+            Type::U16 => asm.push_str("    movzx eax, ax\n"),
+            Type::U32 => asm.push_str("    mov eax, eax\n"),
+            _ => {}
+        }
+    }
+    
+    fn push_loop(&mut self, label: &Option<String>, break_label: &str, continue_label: &str) {
+        self.loop_stack.push(LoopFrame {
+            label: label.clone(),
+            break_label: break_label.to_string(),
+            continue_label: continue_label.to_string(),
+            defer_depth: self.defer_scopes.len(),
+        });
+    }
+    
+    /// Innermost loop for an unlabeled `break`/`continue`, otherwise the matching labeled one.
+    fn find_loop(&self, label: &Option<String>) -> Option<&LoopFrame> {
+        match label {
+            None => self.loop_stack.last(),
+            Some(name) => self.loop_stack.iter().rev().find(|f| f.label.as_ref() == Some(name)),
+        }
+    }
+    
+    fn generate_call(&mut self, name: &str, args: &[AstNode], asm: &mut String) -> Result<(), CompilerError> {
+        let signature = self.functions.get(name).cloned();
+        let return_type = signature.as_ref().and_then(|s| s.return_type.clone());
+        
+        // An array result lives in the caller's frame, past the call's own temporaries.
+        let mut arg_slots = Vec::new();
+        let mut arg_types = Vec::new();
+        if let Some(ret) = return_type.as_ref().filter(|t| is_aggregate(t)) {
+            let result_offset = self.allocate_value(ret);
+            asm.push_str(&format!("    lea rax, [rbp-{}]\n", result_offset));
+            arg_slots.push(self.spill_rax(asm));
+            arg_types.push(Type::U64);
+        }
+        let mark = self.stack_offset;
+        
+        // Arrays evaluate to their address, so they are passed by pointer.
+        for (i, arg) in args.iter().enumerate() {
+            let arg_type = signature.as_ref().and_then(|s| s.params.get(i).cloned())
+                .or_else(|| self.expr_type(arg))
+                .unwrap_or(Type::I64);
+            self.generate_expression(arg, asm)?;
+            arg_slots.push(self.spill_rax(asm));
+            arg_types.push(arg_type);
+        }
+        self.max_call_args = self.max_call_args.max(arg_slots.len());
+        
+        // Floats go in xmm registers, and an f32 is passed in single precision.
+        for (i, slot) in arg_slots.iter().enumerate().skip(ARG_REGISTERS.len()) {
+            let destination = 32 + 8 * (i - ARG_REGISTERS.len());
+            if arg_types[i] == Type::F32 {
+                asm.push_str(&format!("    movq xmm0, [rbp-{}]\n", slot));
+                asm.push_str("    cvtsd2ss xmm0, xmm0\n");
+                asm.push_str(&format!("    movd [rsp+{}], xmm0\n", destination));
+            } else {
+                asm.push_str(&format!("    mov rax, [rbp-{}]\n", slot));
+                asm.push_str(&format!("    mov [rsp+{}], rax\n", destination));
+            }
+        }
+        for (i, (register, slot)) in ARG_REGISTERS.iter().zip(&arg_slots).enumerate() {
+            match arg_types[i] {
+                Type::F64 => asm.push_str(&format!("    movq xmm{}, [rbp-{}]\n", i, slot)),
+                Type::F32 => {
+                    asm.push_str(&format!("    movq xmm{}, [rbp-{}]\n", i, slot));
+                    asm.push_str(&format!("    cvtsd2ss xmm{0}, xmm{0}\n", i));
+                }
+                _ => asm.push_str(&format!("    mov {}, [rbp-{}]\n", register, slot)),
+            }
+        }
+        let label = self.local_functions.get(name).or_else(|| self.exports.get(name)).cloned()
+            .unwrap_or_else(|| symbol(name));
+        asm.push_str(&format!("    call {}\n", label));
+        
+        self.stack_offset = mark;
+        Ok(())
+    }
+    
+    /// Prints the array at `[rax]` as `[a, b, c]` followed by `suffix`, one
+    /// element at a time in a loop. Nested arrays are printed the same way.
+    fn generate_array_print(&mut self, array_type: &Type, suffix: &str, asm: &mut String) {
+        let Type::Array(elem_type, count) = array_type else {
+            return;
+        };
+        let elem_type = self.resolve(elem_type);
+        let base = self.spill_rax(asm);
+        let index = self.allocate_slots(1);
+        let loop_label = self.next_label();
+        let separator_label = self.next_label();
+        let end_label = self.next_label();
+        
+        self.emit_printf("[", asm);
+        asm.push_str(&format!("    mov qword [rbp-{}], 0\n", index));
+        asm.push_str(&format!("{}:\n", loop_label));
+        asm.push_str(&format!("    mov rax, [rbp-{}]\n", index));
+        asm.push_str(&format!("    cmp rax, {}\n", count));
+        asm.push_str(&format!("    jge {}\n", end_label));
+        asm.push_str("    test rax, rax\n");
+        asm.push_str(&format!("    jz {}\n", separator_label));
+        self.emit_printf(", ", asm);
+        asm.push_str(&format!("{}:\n", separator_label));
+        
+        asm.push_str(&format!("    mov rax, [rbp-{}]\n", index));
+        asm.push_str(&format!("    imul rax, {}\n", 8 * type_slots(&elem_type)));
+        asm.push_str(&format!("    add rax, [rbp-{}]\n", base));
+        if matches!(elem_type, Type::Array(..)) {
+            self.generate_array_print(&elem_type, "", asm);
+        } else {
+            asm.push_str("    mov rdx, [rax]\n");
+            if elem_type.is_float() {
+                asm.push_str("    movq xmm1, rdx\n");
+            }
+            self.emit_printf(print_format(Some(&elem_type)), asm);
+        }
+        
+        asm.push_str(&format!("    inc qword [rbp-{}]\n", index));
+        asm.push_str(&format!("    jmp {}\n", loop_label));
+        asm.push_str(&format!("{}:\n", end_label));
+        self.emit_printf(&format!("]{}", suffix), asm);
+    }
+    
+    /// Calls printf with `format` as its first argument; any others must
+    /// already be in place.
+    fn emit_printf(&mut self, format: &str, asm: &mut String) {
+        let index = self.string_literals.len();
+        self.string_literals.push(format.to_string());
+        asm.push_str(&format!("    lea rcx, [rel str_{}]\n", index));
+        asm.push_str("    sub rsp, 32\n");
+        asm.push_str("    call printf\n");
+        asm.push_str("    add rsp, 32\n");
+    }
+    
+    /// Calls C `printf` with the arguments as given. A variadic float is passed
+    /// as a double in both its integer and its xmm register.
+    fn generate_printf(&mut self, args: &[AstNode], asm: &mut String) -> Result<(), CompilerError> {
+        let mark = self.stack_offset;
+        let mut arg_slots = Vec::new();
+        for arg in args {
+            self.generate_expression(arg, asm)?;
+            arg_slots.push(self.spill_rax(asm));
+        }
+        self.max_call_args = self.max_call_args.max(arg_slots.len());
+        
+        for (i, slot) in arg_slots.iter().enumerate().skip(ARG_REGISTERS.len()) {
+            asm.push_str(&format!("    mov rax, [rbp-{}]\n", slot));
+            asm.push_str(&format!("    mov [rsp+{}], rax\n", 32 + 8 * (i - ARG_REGISTERS.len())));
+        }
+        for (i, (register, slot)) in ARG_REGISTERS.iter().zip(&arg_slots).enumerate() {
+            asm.push_str(&format!("    mov {}, [rbp-{}]\n", register, slot));
+            if self.expr_type(&args[i]).is_some_and(|t| self.resolve(&t).is_float()) {
+                asm.push_str(&format!("    movq xmm{}, {}\n", i, register));
+            }
+        }
+        asm.push_str("    call printf\n");
+        
+        self.stack_offset = mark;
+        Ok(())
+    }
+    
+    /// Prints `file:line: kind: message` and exits with `PANIC_EXIT_CODE`.
+    fn generate_abort(&mut self, kind: &str, message: &AstNode, line: usize, asm: &mut String) -> Result<(), CompilerError> {
+        self.generate_expression(message, asm)?;
+        asm.push_str("    mov rdx, rax\n");
+        
+        let index = self.string_literals.len();
+        self.string_literals.push(format!("{}:{}: {}: %s\n", self.source_file.replace('%', "%%"), line, kind));
+        asm.push_str(&format!("    lea rcx, [rel str_{}]\n", index));
+        asm.push_str("    sub rsp, 32\n");
+        asm.push_str("    call printf\n");
+        // ExitProcess skips the CRT's exit handling, so flush stdout first.
+        asm.push_str("    xor ecx, ecx\n");
+        asm.push_str("    call fflush\n");
+        asm.push_str(&format!("    mov ecx, {}\n", PANIC_EXIT_CODE));
+        asm.push_str("    call ExitProcess\n");
+        asm.push_str("    add rsp, 32\n");
+        Ok(())
+    }
+    
+    /// Spills incoming argument `index` into the local slot at `offset`.
+    fn load_parameter(&self, index: usize, offset: i32, param_type: &Type, asm: &mut String) {
+        if let Some(register) = ARG_REGISTERS.get(index) {
+            match param_type {
+                Type::F64 => asm.push_str(&format!("    movq [rbp-{}], xmm{}\n", offset, index)),
+                Type::F32 => {
+                    asm.push_str(&format!("    cvtss2sd xmm{0}, xmm{0}\n", index));
+                    asm.push_str(&format!("    movq [rbp-{}], xmm{}\n", offset, index));
+                }
+                Type::Bool => asm.push_str(&format!("    mov [rbp-{}], {}\n", offset, ARG_BYTE_REGISTERS[index])),
+                _ => asm.push_str(&format!("    mov [rbp-{}], {}\n", offset, register)),
+            }
+        } else {
+            // Stack arguments sit above the return address and the caller's shadow space.
+            let source = 48 + 8 * (index - ARG_REGISTERS.len());
+            if *param_type == Type::F32 {
+                asm.push_str(&format!("    movd xmm0, [rbp+{}]\n", source));
+                asm.push_str("    cvtss2sd xmm0, xmm0\n");
+                asm.push_str("    movq rax, xmm0\n");
+            } else {
+                asm.push_str(&format!("    mov rax, [rbp+{}]\n", source));
+            }
+            let register = if *param_type == Type::Bool { "al" } else { "rax" };
+            asm.push_str(&format!("    mov [rbp-{}], {}\n", offset, register));
+        }
+    }
+    
+    fn signature(&self, params: &[(String, Type)], return_type: &Option<Type>) -> Signature {
+        Signature {
+            params: params.iter().map(|(_, t)| self.resolve(t)).collect(),
+            return_type: return_type.as_ref().map(|t| self.resolve(t)),
+        }
+    }
+    
+    /// Declares `name` and stores the value of `value` into it. Temporaries used
+    /// while evaluating `value` are released afterwards.
+    fn initialize_local(&mut self, name: &str, var_type: Type, value: &AstNode, asm: &mut String) -> Result<(), CompilerError> {
+        let offset = self.allocate_value(&var_type);
+        let mark = self.stack_offset;
+        
+        self.generate_expression(value, asm)?;
+        
+        let local = Local { offset, var_type, indirect: false };
+        self.store_local(&local, asm);
+        self.insert_local(name, local);
+        self.stack_offset = mark;
+        Ok(())
+    }
+    
+    /// Leaves the address of the array element or field `place` in `rax` and returns its type.
+    fn generate_place(&mut self, place: &AstNode, asm: &mut String) -> Result<Type, CompilerError> {
+        match place {
+            AstNode::ArrayIndex { array, index } => {
+                let Some(Type::Array(elem_type, _)) = self.expr_type(array).map(|t| self.resolve(&t)) else {
+                    return Err(CompilerError::CodeGenError("Cannot assign to an element of a non-array".to_string()));
+                };
+                let elem_type = self.resolve(&elem_type);
+                
+                self.generate_expression(index, asm)?;
+                let spill = self.spill_rax(asm);
+                self.generate_expression(array, asm)?;
+                asm.push_str(&format!("    mov rcx, [rbp-{}]\n", spill));
+                if is_aggregate(&elem_type) {
+                    asm.push_str(&format!("    imul rcx, rcx, {}\n", 8 * type_slots(&elem_type)));
+                    asm.push_str("    lea rax, [rax+rcx]\n");
+                } else {
+                    asm.push_str("    lea rax, [rax+rcx*8]\n");
+                }
+                Ok(elem_type)
+            }
+            AstNode::FieldAccess { object, field, .. } => {
+                let object_type = self.expr_type(object).map(|t| self.resolve(&t));
+                let Some((slot, field_type)) = object_type.as_ref().and_then(|t| field_slot(t, field)) else {
+                    return Err(CompilerError::CodeGenError(format!("Cannot access field '{}'", field)));
+                };
+                let field_type = self.resolve(field_type);
+                self.generate_expression(object, asm)?;
+                if slot > 0 {
+                    asm.push_str(&format!("    lea rax, [rax+{}]\n", 8 * slot));
+                }
+                Ok(field_type)
+            }
+            _ => Err(CompilerError::CodeGenError("Invalid assignment target".to_string())),
+        }
+    }
+    
+    /// Stores `rax` into `local`; for arrays and tuples `rax` is the source address and the elements are copied.
+    fn store_local(&mut self, local: &Local, asm: &mut String) {
+        if is_aggregate(&local.var_type) {
+            if local.indirect {
+                asm.push_str(&format!("    mov rdx, [rbp-{}]\n", local.offset));
+            } else {
+                asm.push_str(&format!("    lea rdx, [rbp-{}]\n", local.offset));
+            }
+            self.copy_slots(type_slots(&local.var_type), asm);
+        } else {
+            if local.var_type == Type::F32 {
+                round_to_f32(asm);
+            }
+            if local.var_type == Type::Bool {
+                asm.push_str(&format!("    mov [rbp-{}], al\n", local.offset));
+            } else {
+                asm.push_str(&format!("    mov [rbp-{}], rax\n", local.offset));
+            }
+        }
+    }
+    
+    /// Copies `slots` quadwords from `[rax]` to `[rdx]`, leaving `rax` intact.
+    fn copy_slots(&mut self, slots: usize, asm: &mut String) {
+        if slots <= 4 {
+            for i in 0..slots {
+                asm.push_str(&format!("    mov rcx, [rax+{}]\n", 8 * i));
+                asm.push_str(&format!("    mov [rdx+{}], rcx\n", 8 * i));
+            }
+            return;
+        }
+        
+        let loop_label = self.next_label();
+        let end_label = self.next_label();
+        asm.push_str("    xor rcx, rcx\n");
+        asm.push_str(&format!("{}:\n", loop_label));
+        asm.push_str(&format!("    cmp rcx, {}\n", slots));
+        asm.push_str(&format!("    jge {}\n", end_label));
+        asm.push_str("    mov r8, [rax+rcx*8]\n");
+        asm.push_str("    mov [rdx+rcx*8], r8\n");
+        asm.push_str("    inc rcx\n");
+        asm.push_str(&format!("    jmp {}\n", loop_label));
+        asm.push_str(&format!("{}:\n", end_label));
+    }
+    
+    /// Floats are carried in `rax` as the bits of an `f64`; an `f32` result is
+    /// rounded to single precision. Ordering comparisons use `ucomisd` with the
+    /// operands arranged so that `seta`/`setae` yield false when either is NaN.
+    fn generate_float_binary(&mut self, left: &AstNode, op: &str, right: &AstNode, float_type: &Type, asm: &mut String) -> Result<(), CompilerError> {
+        self.generate_expression(right, asm)?;
+        let spill = self.spill_rax(asm);
+        self.generate_expression(left, asm)?;
+        asm.push_str(&format!("    mov rcx, [rbp-{}]\n", spill));
+        asm.push_str("    movq xmm0, rax\n");
+        asm.push_str("    movq xmm1, rcx\n");
+        
+        let (compare, set) = match op {
+            "+" | "-" | "*" | "/" | "%" => {
+                if op == "%" {
+                    // SSE has no remainder instruction; fmod takes its operands in xmm0 and xmm1.
+                    asm.push_str("    sub rsp, 32\n");
+                    asm.push_str("    call fmod\n");
+                    asm.push_str("    add rsp, 32\n");
+                } else {
+                    let instruction = match op {
+                        "+" => "addsd",
+                        "-" => "subsd",
+                        "*" => "mulsd",
+                        _ => "divsd",
+                    };
+                    asm.push_str(&format!("    {} xmm0, xmm1\n", instruction));
+                }
+                asm.push_str("    movq rax, xmm0\n");
+                if *float_type == Type::F32 {
+                    round_to_f32(asm);
+                }
+                return Ok(());
+            }
+            // An unordered result (a NaN operand) sets ZF and PF together.
+            "==" | "!=" => {
+                asm.push_str("    ucomisd xmm0, xmm1\n");
+                if op == "==" {
+                    asm.push_str("    sete al\n");
+                    asm.push_str("    setnp cl\n");
+                    asm.push_str("    and al, cl\n");
+                } else {
+                    asm.push_str("    setne al\n");
+                    asm.push_str("    setp cl\n");
+                    asm.push_str("    or al, cl\n");
+                }
+                asm.push_str("    movzx rax, al\n");
+                return Ok(());
+            }
+            "<" => ("xmm1, xmm0", "seta"),
+            "<=" => ("xmm1, xmm0", "setae"),
+            ">" => ("xmm0, xmm1", "seta"),
+            _ => ("xmm0, xmm1", "setae"),
+        };
+        asm.push_str(&format!("    ucomisd {}\n", compare));
+        asm.push_str(&format!("    {} al\n", set));
+        asm.push_str("    movzx rax, al\n");
+        Ok(())
+    }
+    
+    /// Compares two arrays slot by slot, leaving 1 or 0 in `rax`. Small arrays
+    /// are unrolled; larger ones loop, stopping at the first difference.
+    fn generate_array_comparison(&mut self, left: &AstNode, op: &str, right: &AstNode, array_type: &Type, asm: &mut String) -> Result<(), CompilerError> {
+        self.generate_expression(right, asm)?;
+        let spill = self.spill_rax(asm);
+        self.generate_expression(left, asm)?;
+        asm.push_str(&format!("    mov rcx, [rbp-{}]\n", spill));
+        
+        let slots = type_slots(array_type);
+        let differ_label = self.next_label();
+        let end_label = self.next_label();
+        if slots <= 4 {
+            for i in 0..slots {
+                asm.push_str(&format!("    mov rdx, [rax+{}]\n", 8 * i));
+                asm.push_str(&format!("    cmp rdx, [rcx+{}]\n", 8 * i));
+                asm.push_str(&format!("    jne {}\n", differ_label));
+            }
+        } else {
+            let loop_label = self.next_label();
+            asm.push_str("    xor r8, r8\n");
+            asm.push_str(&format!("{}:\n", loop_label));
+            asm.push_str("    mov rdx, [rax+r8*8]\n");
+            asm.push_str("    cmp rdx, [rcx+r8*8]\n");
+            asm.push_str(&format!("    jne {}\n", differ_label));
+            asm.push_str("    inc r8\n");
+            asm.push_str(&format!("    cmp r8, {}\n", slots));
+            asm.push_str(&format!("    jl {}\n", loop_label));
+        }
+        
+        let (same, differ) = if op == "==" { (1, 0) } else { (0, 1) };
+        asm.push_str(&format!("    mov rax, {}\n", same));
+        asm.push_str(&format!("    jmp {}\n", end_label));
+        asm.push_str(&format!("{}:\n", differ_label));
+        asm.push_str(&format!("    mov rax, {}\n", differ));
+        asm.push_str(&format!("{}:\n", end_label));
+        Ok(())
+    }
+    
+    /// Saves `rax` in a fresh frame slot. Unlike `push`, this keeps `rsp`
+    /// aligned for any call made before the value is reloaded.
+    fn spill_rax(&mut self, asm: &mut String) -> i32 {
+        let slot = self.allocate_slots(1);
+        asm.push_str(&format!("    mov [rbp-{}], rax\n", slot));
+        slot
+    }
+    
+    fn allocate_slots(&mut self, slots: usize) -> i32 {
+        // Saturates so an oversized frame is reported rather than wrapping.
+        let bytes = i32::try_from(slots.saturating_mul(8)).unwrap_or(i32::MAX);
+        // Slots stay 8-byte aligned after any packed bools.
+        let aligned = self.stack_offset.saturating_add(7) / 8 * 8;
+        self.stack_offset = aligned.saturating_add(bytes);
+        self.max_stack_offset = self.max_stack_offset.max(self.stack_offset);
+        self.stack_offset
+    }
+    
+    /// Allocates the slots for a value of type `t`, noting any array in it for
+    /// the stack protector.
+    fn allocate_value(&mut self, t: &Type) -> i32 {
+        self.frame_has_array |= contains_array(t);
+        // A bool takes a single byte, so consecutive ones share a slot.
+        if *t == Type::Bool {
+            self.stack_offset = self.stack_offset.saturating_add(1);
+            self.max_stack_offset = self.max_stack_offset.max(self.stack_offset);
+            return self.stack_offset;
+        }
+        self.allocate_slots(type_slots(t))
+    }
+    
+    fn declare_local(&mut self, name: &str, var_type: Type) -> i32 {
+        let offset = self.allocate_value(&var_type);
+        self.insert_local(name, Local { offset, var_type, indirect: false });
+        offset
+    }
+    
+    /// Makes `local` visible as `name` and records it for the function's
+    /// frame layout. Compiler temporaries, whose names hold a `.`, aren't recorded.
+    fn insert_local(&mut self, name: &str, local: Local) {
+        if !name.contains('.')
+            && let Some((_, locals)) = self.frame_locals.last_mut() {
+            locals.push(FrameLocal {
+                name: name.to_string(),
+                offset: local.offset,
+                var_type: local.var_type.clone(),
+                indirect: local.indirect,
+            });
+        }
+        self.variables.insert(name.to_string(), local);
+    }
+    
+    /// Resolved types of a binary operator's operands, where a bare integer
+    /// literal takes the other operand's integer type as it does in semantic
+    /// analysis.
+    fn operand_types(&self, left: &AstNode, right: &AstNode) -> (Option<Type>, Option<Type>) {
+        let left_type = self.expr_type(left).map(|t| self.resolve(&t));
+        let right_type = self.expr_type(right).map(|t| self.resolve(&t));
+        let is_int = |node: &AstNode| matches!(node, AstNode::Literal(Literal::Int(_)));
+        match (&left_type, &right_type) {
+            (_, Some(t)) if is_int(left) && t.is_integer() => (right_type.clone(), right_type),
+            (Some(t), _) if is_int(right) && t.is_integer() => (left_type.clone(), left_type),
+            _ => (left_type, right_type),
+        }
+    }
+    
+    /// Mirrors the semantic analyzer's typing rules for the nodes codegen cares about.
+    fn expr_type(&self, node: &AstNode) -> Option<Type> {
+        match node {
+            AstNode::Literal(lit) => Some(lit.default_type()),
+            AstNode::Identifier { name, .. } => self.variables.get(name).map(|local| local.var_type.clone())
+                .or_else(|| self.statics.get(name).cloned()),
+            AstNode::BinaryOp { left, op, right, .. } => match op.as_str() {
+                "==" | "!=" | "<" | "<=" | ">" | ">=" | "&&" | "||" => Some(Type::Bool),
+                "<<" | ">>" => self.expr_type(left),
+                _ => match self.operand_types(left, right) {
+                    (Some(left_type), Some(right_type)) => left_type.promote(&right_type).or(Some(left_type)),
+                    (left_type, _) => left_type,
+                },
+            },
+            AstNode::UnaryOp { operand, .. } => self.expr_type(operand),
+            AstNode::ArrayLiteral { elements } => {
+                let elem_type = elements.first().and_then(|e| self.expr_type(e)).unwrap_or(Type::I32);
+                Some(Type::Array(Box::new(elem_type), elements.len()))
+            }
+            AstNode::ArrayRepeat { value, count } => {
+                self.expr_type(value).map(|t| Type::Array(Box::new(t), *count as usize))
+            }
+            AstNode::TupleLiteral { elements } => {
+                elements.iter().map(|e| self.expr_type(e)).collect::<Option<Vec<_>>>().map(Type::Tuple)
+            }
+            AstNode::FunctionCall { name, args, .. } if self.is_intrinsic(name) => self.intrinsic_type(args),
+            AstNode::FunctionCall { name, .. } => {
+                self.functions.get(name).and_then(|s| s.return_type.clone())
+            }
+            AstNode::StructLiteral { name, .. } => Some(self.resolve(&Type::Named(name.clone()))),
+            AstNode::MethodCall { receiver, method, .. } => {
+                let symbol = self.method_target(receiver, method)?;
+                self.functions.get(&symbol).and_then(|s| s.return_type.clone())
+            }
+            AstNode::FieldAccess { object, field, .. } => {
+                field_slot(&self.resolve(&self.expr_type(object)?), field).map(|(_, t)| t.clone())
+            }
+            AstNode::ArrayIndex { array, .. } => match self.resolve(&self.expr_type(array)?) {
+                Type::Str => Some(Type::U8),
+                Type::Array(elem_type, _) => Some(*elem_type),
+                _ => None,
+            },
+            AstNode::Loop { body, label } => self.break_value_type(body, label, false),
+            AstNode::If { then_branch, else_branch, .. } => [Some(then_branch), else_branch.as_ref()].into_iter()
+                .flatten()
+                .find_map(|branch| match branch.last() {
+                    Some(AstNode::BlockValue { value }) => self.expr_type(value),
+                    Some(nested @ AstNode::If { .. }) => self.expr_type(nested),
+                    _ => None,
+                }),
+            _ => None,
+        }
+    }
+    
+    /// Type of the first typed `break` value in `body` that leaves the loop
+    /// labeled `label`. Unlabeled breaks in `nested` loops leave those instead.
+    fn break_value_type(&self, body: &[AstNode], label: &Option<String>, nested: bool) -> Option<Type> {
+        body.iter().find_map(|stmt| match stmt {
+            AstNode::Break { label: target, value: Some(value) } => {
+                let leaves = match target {
+                    None => !nested,
+                    Some(_) => target == label,
+                };
+                if leaves { self.expr_type(value) } else { None }
+            }
+            AstNode::If { then_branch, else_branch, .. } => self.break_value_type(then_branch, label, nested)
+                .or_else(|| else_branch.as_ref().and_then(|b| self.break_value_type(b, label, nested))),
+            AstNode::Block { body } => self.break_value_type(body, label, nested),
+            AstNode::While { body, .. } | AstNode::For { body, .. } | AstNode::Loop { body, .. } => {
+                self.break_value_type(body, label, true)
+            }
+            _ => None,
+        })
+    }
+    
+    /// Renders a static's constant initializer as a `dq` operand.
+    fn static_initializer(&mut self, value: &AstNode, static_type: &Type) -> String {
+        // An array is written as one quadword per element.
+        if let Type::Array(elem_type, size) = static_type {
+            let elements: Vec<String> = match value {
+                AstNode::ArrayLiteral { elements } => {
+                    elements.iter().map(|e| self.static_initializer(e, elem_type)).collect()
+                }
+                AstNode::ArrayRepeat { value, .. } => vec![self.static_initializer(value, elem_type); *size],
+                _ => vec!["0".to_string(); *size],
+            };
+            return elements.join(", ");
+        }
+        
+        let literal = match value {
+            AstNode::Literal(lit) => Some(lit.clone()),
+            _ => optimizer::fold_constant(value),
+        };
+        match literal {
+            Some(Literal::Int(n)) => n.to_string(),
+            Some(Literal::UInt(n)) => format!("0x{:016X}", n),
+            Some(Literal::Bool(b)) => (b as i32).to_string(),
+            Some(Literal::Char(c)) => (c as u32).to_string(),
+            Some(Literal::Float(f)) if *static_type == Type::F32 => format!("0x{:016X}", (f as f32 as f64).to_bits()),
+            Some(Literal::Float(f)) => format!("0x{:016X}", f.to_bits()),
+            Some(Literal::String(s)) => {
+                let index = self.string_literals.len();
+                self.string_literals.push(s);
+                format!("str_{}", index)
+            }
+            None => "0".to_string(),
+        }
+    }
+    
     fn next_label(&mut self) -> String {
         let label = format!("L{}", self.label_counter);
         self.label_counter += 1;
         label
     }
+}
+
+/// Number of 8-byte stack slots a value of type `t` occupies. Saturates, so
+/// nested arrays too large to count still fail the frame size check.
+fn type_slots(t: &Type) -> usize {
+    match t {
+        Type::Array(elem_type, size) => size.saturating_mul(type_slots(elem_type)),
+        Type::Tuple(types) => types.iter().map(type_slots).fold(0, usize::saturating_add),
+        Type::Struct { fields, .. } => fields.iter().map(|(_, t)| type_slots(t)).fold(0, usize::saturating_add),
+        Type::Union { fields, .. } => fields.iter().map(|(_, t)| type_slots(t)).max().unwrap_or(1),
+        _ => 1,
+    }
+}
+
+fn contains_array(t: &Type) -> bool {
+    match t {
+        Type::Array(..) => true,
+        Type::Tuple(types) => types.iter().any(contains_array),
+        Type::Struct { fields, .. } | Type::Union { fields, .. } => fields.iter().any(|(_, t)| contains_array(t)),
+        _ => false,
+    }
+}
+
+/// Slot index of `field` within a struct or union, counted from its start, and
+/// the field's type. Every field of a union starts at its first slot.
+fn field_slot<'a>(t: &'a Type, field: &str) -> Option<(usize, &'a Type)> {
+    let (fields, union) = match t {
+        Type::Struct { fields, .. } => (fields, false),
+        Type::Union { fields, .. } => (fields, true),
+        _ => return None,
+    };
+    let mut slot = 0;
+    for (name, field_type) in fields {
+        if name == field {
+            return Some((slot, field_type));
+        }
+        if !union {
+            slot += type_slots(field_type);
+        }
+    }
+    None
+}
+
+/// printf conversion for a scalar of type `t`.
+fn print_format(t: Option<&Type>) -> &'static str {
+    match t {
+        Some(Type::Str) => "%s",
+        Some(Type::Char) => "%c",
+        Some(Type::F32 | Type::F64) => "%g",
+        Some(Type::U8 | Type::U16 | Type::U32 | Type::U64) => "%llu",
+        _ => "%lld",
+    }
+}
+
+/// The assembly symbol for a function or static named `name`. Identifiers may
+/// use any Unicode letter, but NASM and GNU as accept only ASCII, so a name
+/// with other characters is written as `_$` followed by the name with each
+/// such character replaced by `$` and six hex digits of its code point:
+/// `café` becomes `_$caf$0000e9`. No source identifier contains `$`, so the
+/// result never clashes with another name.
+fn symbol(name: &str) -> String {
+    if name.is_ascii() {
+        return name.to_string();
+    }
+    let mut out = String::from("_$");
+    for c in name.chars() {
+        if c.is_ascii() {
+            out.push(c);
+        } else {
+            out.push_str(&format!("${:06x}", c as u32));
+        }
+    }
+    out
+}
+
+/// Rounds the `f64` in `rax` to the nearest `f32` value.
+fn round_to_f32(asm: &mut String) {
+    asm.push_str("    movq xmm0, rax\n");
+    asm.push_str("    cvtsd2ss xmm0, xmm0\n");
+    asm.push_str("    cvtss2sd xmm0, xmm0\n");
+    asm.push_str("    movq rax, xmm0\n");
+}
+
+/// Values held in memory and passed around by address rather than in `rax`.
+fn is_aggregate(t: &Type) -> bool {
+    matches!(t, Type::Array(..) | Type::Tuple(_) | Type::Struct { .. } | Type::Union { .. })
+}
+
+/// Moves rsp down by `bytes`. `__chkstk` touches each page of a large frame,
+/// taking its size in rax and leaving rsp unchanged.
+fn reserve_stack(bytes: i32, asm: &mut String) {
+    if bytes >= STACK_PROBE_SIZE {
+        asm.push_str(&format!("    mov eax, {}\n", bytes));
+        asm.push_str("    call __chkstk\n");
+        asm.push_str("    sub rsp, rax\n");
+    } else if bytes > 0 {
+        asm.push_str(&format!("    sub rsp, {}\n", bytes));
+    }
+}
+
+/// Stores the canary on entry and checks it before every `leave`, keeping rax and xmm0.
+fn protect_frame(code: &str) -> String {
+    let mut out = String::with_capacity(code.len());
+    out.push_str("    mov rax, [rel __security_cookie]\n");
+    out.push_str("    xor rax, rbp\n");
+    out.push_str(&format!("    mov [rbp-{}], rax\n", CANARY_OFFSET));
+    for line in code.lines() {
+        if line == "    leave" {
+            out.push_str(&format!("    mov rcx, [rbp-{}]\n", CANARY_OFFSET));
+            out.push_str("    xor rcx, rbp\n");
+            out.push_str("    call __security_check_cookie\n");
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Rewrites a function body generated for an rbp frame to run without one,
+/// with rsp `frame` bytes below the return address. `[rbp-N]` slots become
+/// `[rsp+frame-N]`, incoming stack arguments at `[rbp+K]` become
+/// `[rsp+frame+K-8]`, and `leave` just releases the frame.
+fn omit_frame_pointer(code: &str, frame: i32) -> String {
+    let mut out = String::with_capacity(code.len());
+    for line in code.lines() {
+        if line == "    leave" {
+            if frame > 0 {
+                out.push_str(&format!("    add rsp, {}\n", frame));
+            }
+            continue;
+        }
+        
+        let mut rest = line;
+        while let Some(start) = rest.find("[rbp") {
+            let end = start + rest[start..].find(']').unwrap();
+            let displacement: i32 = rest[start + 4..end].parse().unwrap();
+            let offset = if displacement < 0 { frame + displacement } else { frame + displacement - 8 };
+            out.push_str(&rest[..start]);
+            out.push_str(&format!("[rsp+{}]", offset));
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+        out.push('\n');
+    }
+    out
+}
+
+/// `k` when `node` is the integer literal `2^k`.
+fn power_of_two_exponent(node: &AstNode) -> Option<u32> {
+    match node {
+        AstNode::Literal(Literal::Int(n)) if *n > 0 && n & (n - 1) == 0 => Some(n.trailing_zeros()),
+        _ => None,
+    }
+}
+
+/// Escapes `s` for a NASM backtick string. Non-ASCII text passes through as UTF-8.
+fn nasm_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '`' => out.push_str("\\`"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\x7f' => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
 }
\ No newline at end of file
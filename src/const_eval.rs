@@ -0,0 +1,283 @@
+//! Compile-time evaluation of `const fn` calls. Constants get their values
+//! before semantic analysis, so a constant initialized from a `const fn` call
+//! can be used as an array length.
+
+use crate::error::CompilerError;
+use crate::optimizer;
+use crate::parser::{AstNode, Literal, Type};
+use std::collections::HashMap;
+
+/// Deepest chain of nested `const fn` calls evaluated before giving up.
+const MAX_CALL_DEPTH: usize = 256;
+
+struct ConstFunction {
+    params: Vec<String>,
+    return_type: Option<Type>,
+    body: Vec<AstNode>,
+}
+
+/// Result of running statements: fall through to the next one, or return.
+enum Flow {
+    Next,
+    Return(Literal),
+}
+
+/// The program's top-level `const fn` definitions, by name.
+#[derive(Default)]
+pub struct ConstFunctions {
+    functions: HashMap<String, ConstFunction>,
+}
+
+/// Checks every `const fn` in `module`, then replaces constant initializers
+/// with their values and named array lengths with the constants' values.
+pub fn evaluate(module: &mut AstNode) -> Result<(), CompilerError> {
+    let AstNode::Module { items, .. } = module else {
+        return Ok(());
+    };
+    let functions = ConstFunctions::collect(items);
+    for item in items.iter() {
+        if let AstNode::Function { name, body, constant: true, line, column, .. } = item
+            && let Some(what) = body.iter().find_map(|stmt| functions.unsupported(stmt)) {
+            return Err(CompilerError::SemanticError(format!(
+                "Function '{}' is declared const but uses {}, which cannot be evaluated at compile time",
+                name, what
+            )).at(*line, *column));
+        }
+    }
+    for item in items.iter_mut() {
+        functions.substitute(item, &mut HashMap::new())?;
+    }
+    Ok(())
+}
+
+impl ConstFunctions {
+    pub fn collect(items: &[AstNode]) -> Self {
+        let mut functions = HashMap::new();
+        for item in items {
+            if let AstNode::Function { name, params, return_type, body, constant: true, .. } = item {
+                functions.insert(name.clone(), ConstFunction {
+                    params: params.iter().map(|(param, _)| param.clone()).collect(),
+                    return_type: return_type.clone(),
+                    body: body.clone(),
+                });
+            }
+        }
+        ConstFunctions { functions }
+    }
+    
+    /// Evaluates `name(args)`. Returns `None` when `name` is not a `const fn`,
+    /// an argument is not constant, evaluation overflows, divides by zero or
+    /// recurses too deeply, or the result doesn't fit the return type; the
+    /// call is then left for run time.
+    pub fn call(&self, name: &str, args: &[AstNode], constants: &HashMap<String, Literal>) -> Option<Literal> {
+        let args = args.iter().map(|arg| self.eval(arg, constants, 0)).collect::<Option<Vec<_>>>()?;
+        self.call_with(name, args, 0)
+    }
+    
+    fn call_with(&self, name: &str, args: Vec<Literal>, depth: usize) -> Option<Literal> {
+        let function = self.functions.get(name)?;
+        if depth >= MAX_CALL_DEPTH || args.len() != function.params.len() {
+            return None;
+        }
+        let mut env: HashMap<String, Literal> = function.params.iter().cloned().zip(args).collect();
+        let Flow::Return(value) = self.run(&function.body, &mut env, depth)? else {
+            return None;
+        };
+        if let (Literal::Int(n), Some((min, max))) = (&value, function.return_type.as_ref().and_then(Type::int_range))
+            && !(min..=max).contains(&(*n as i128)) {
+            return None;
+        }
+        Some(value)
+    }
+    
+    fn run(&self, body: &[AstNode], env: &mut HashMap<String, Literal>, depth: usize) -> Option<Flow> {
+        for stmt in body {
+            let nested = match stmt {
+                AstNode::Return { value: Some(value) } => {
+                    return Some(Flow::Return(self.eval(value, env, depth)?));
+                }
+                AstNode::VariableDecl { name, value: Some(value), .. } | AstNode::ConstDecl { name, value, .. } => {
+                    let value = self.eval(value, env, depth)?;
+                    env.insert(name.clone(), value);
+                    continue;
+                }
+                AstNode::BlockValue { value } => {
+                    self.eval(value, env, depth)?;
+                    continue;
+                }
+                AstNode::If { condition, then_branch, else_branch } => {
+                    let taken = match self.eval(condition, env, depth)? {
+                        Literal::Bool(taken) => taken,
+                        Literal::Int(n) => n != 0,
+                        _ => return None,
+                    };
+                    if taken { Some(then_branch) } else { else_branch.as_ref() }
+                }
+                AstNode::Block { body } => Some(body),
+                _ => return None,
+            };
+            if let Some(nested) = nested
+                && let Flow::Return(value) = self.run(nested, &mut env.clone(), depth)? {
+                return Some(Flow::Return(value));
+            }
+        }
+        Some(Flow::Next)
+    }
+    
+    fn eval(&self, node: &AstNode, env: &HashMap<String, Literal>, depth: usize) -> Option<Literal> {
+        // Operators are applied to the evaluated operands by the constant folder.
+        let literal = |value: Literal| Box::new(AstNode::Literal(value));
+        match node {
+            AstNode::Literal(lit @ (Literal::Int(_) | Literal::Bool(_))) => Some(lit.clone()),
+            AstNode::Identifier { name, .. } => env.get(name).cloned(),
+            AstNode::UnaryOp { op, operand } => optimizer::fold_constant(&AstNode::UnaryOp {
+                op: op.clone(),
+                operand: literal(self.eval(operand, env, depth)?),
+            }),
+            AstNode::BinaryOp { left, op, right, line, column } => optimizer::fold_constant(&AstNode::BinaryOp {
+                left: literal(self.eval(left, env, depth)?),
+                op: op.clone(),
+                right: literal(self.eval(right, env, depth)?),
+                line: *line,
+                column: *column,
+            }),
+            AstNode::FunctionCall { name, args, .. } => {
+                let args = args.iter().map(|arg| self.eval(arg, env, depth)).collect::<Option<Vec<_>>>()?;
+                self.call_with(name, args, depth + 1)
+            }
+            _ => None,
+        }
+    }
+    
+    /// Describes the first thing in a `const fn` body statement that the
+    /// evaluator can't run, if any.
+    fn unsupported(&self, node: &AstNode) -> Option<String> {
+        let first = |nodes: &[AstNode]| nodes.iter().find_map(|n| self.unsupported(n));
+        match node {
+            AstNode::Return { value: Some(value) }
+            | AstNode::VariableDecl { value: Some(value), .. }
+            | AstNode::ConstDecl { value, .. }
+            | AstNode::BlockValue { value } => self.unsupported(value),
+            AstNode::Return { value: None } => Some("a return without a value".to_string()),
+            AstNode::VariableDecl { value: None, .. } => Some("a variable without an initializer".to_string()),
+            AstNode::If { condition, then_branch, else_branch } => self.unsupported(condition)
+                .or_else(|| first(then_branch))
+                .or_else(|| else_branch.as_deref().and_then(first)),
+            AstNode::Block { body } => first(body),
+            AstNode::Literal(Literal::Int(_) | Literal::Bool(_)) | AstNode::Identifier { .. } => None,
+            AstNode::Literal(lit) => Some(format!("a value of type {}", lit.default_type())),
+            AstNode::UnaryOp { operand, .. } => self.unsupported(operand),
+            AstNode::BinaryOp { left, right, .. } => self.unsupported(left).or_else(|| self.unsupported(right)),
+            AstNode::FunctionCall { name, args, .. } if self.functions.contains_key(name) => first(args),
+            AstNode::FunctionCall { name, .. } => Some(format!("a call to non-const function '{}'", name)),
+            AstNode::While { .. } | AstNode::For { .. } | AstNode::Loop { .. } => Some("a loop".to_string()),
+            AstNode::Assignment { .. } => Some("an assignment".to_string()),
+            AstNode::ArrayLiteral { .. } | AstNode::ArrayRepeat { .. } | AstNode::ArrayIndex { .. } => {
+                Some("an array".to_string())
+            }
+            AstNode::TupleLiteral { .. } | AstNode::TupleDestructure { .. } => Some("a tuple".to_string()),
+            AstNode::StructLiteral { .. } | AstNode::FieldAccess { .. } | AstNode::MethodCall { .. } => {
+                Some("a struct".to_string())
+            }
+            AstNode::Function { .. } => Some("a local function".to_string()),
+            _ => Some("a statement other than 'let', 'const', 'if' and 'return'".to_string()),
+        }
+    }
+    
+    /// Replaces constant initializers with their values and named array
+    /// lengths with the value of the constant in scope.
+    fn substitute(&self, node: &mut AstNode, constants: &mut HashMap<String, Literal>) -> Result<(), CompilerError> {
+        let body = |nodes: &mut Vec<AstNode>, constants: &HashMap<String, Literal>| {
+            let mut scope = constants.clone();
+            nodes.iter_mut().try_for_each(|n| self.substitute(n, &mut scope))
+        };
+        match node {
+            AstNode::Function { params, return_type, body: function_body, .. } => {
+                for (_, param_type) in params.iter_mut() {
+                    resolve_lengths(param_type, &HashMap::new())?;
+                }
+                if let Some(return_type) = return_type {
+                    resolve_lengths(return_type, &HashMap::new())?;
+                }
+                // A local function can't see the enclosing function's constants.
+                body(function_body, &HashMap::new())?;
+            }
+            AstNode::StructDecl { fields, .. } | AstNode::UnionDecl { fields, .. } => {
+                for (_, field_type) in fields.iter_mut() {
+                    resolve_lengths(field_type, constants)?;
+                }
+            }
+            AstNode::TypeAlias { target, .. } => resolve_lengths(target, constants)?,
+            AstNode::Static { static_type, .. } => resolve_lengths(static_type, constants)?,
+            AstNode::VariableDecl { name, var_type, .. } => {
+                if let Some(var_type) = var_type {
+                    resolve_lengths(var_type, constants)?;
+                }
+                constants.remove(name);
+            }
+            AstNode::ConstDecl { name, const_type, value } => {
+                resolve_lengths(const_type, constants)?;
+                match self.eval(value, constants, 0) {
+                    Some(lit) => {
+                        **value = AstNode::Literal(lit.clone());
+                        constants.insert(name.clone(), lit);
+                    }
+                    None => {
+                        constants.remove(name);
+                    }
+                }
+            }
+            AstNode::TupleDestructure { names, .. } => {
+                for (name, _) in names {
+                    constants.remove(name);
+                }
+            }
+            AstNode::If { then_branch, else_branch, .. } => {
+                body(then_branch, constants)?;
+                if let Some(else_branch) = else_branch {
+                    body(else_branch, constants)?;
+                }
+            }
+            AstNode::For { iterator, body: loop_body, .. } => {
+                let mut scope = constants.clone();
+                scope.remove(iterator);
+                body(loop_body, &scope)?;
+            }
+            AstNode::While { body: nodes, .. } | AstNode::Loop { body: nodes, .. } | AstNode::Block { body: nodes } => {
+                body(nodes, constants)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn resolve_lengths(t: &mut Type, constants: &HashMap<String, Literal>) -> Result<(), CompilerError> {
+    match t {
+        Type::ConstLengthArray(elem_type, name) => {
+            resolve_lengths(elem_type, constants)?;
+            let size = match constants.get(name.as_str()) {
+                Some(Literal::Int(n)) if *n >= 0 => *n as usize,
+                Some(_) => {
+                    return Err(CompilerError::SemanticError(
+                        format!("Array length '{}' must be a non-negative integer constant", name)
+                    ));
+                }
+                None => {
+                    return Err(CompilerError::SemanticError(
+                        format!("Array length '{}' is not a constant with a value known at compile time", name)
+                    ));
+                }
+            };
+            *t = Type::Array(elem_type.clone(), size);
+        }
+        Type::Array(elem_type, _) => resolve_lengths(elem_type, constants)?,
+        Type::Tuple(types) => {
+            for elem_type in types {
+                resolve_lengths(elem_type, constants)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
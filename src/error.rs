@@ -9,20 +9,178 @@ pub enum CompilerError {
     IoError(String),
     AssemblyError(String),
     LinkError(String),
+    Located {
+        error: Box<CompilerError>,
+        line: usize,
+        column: usize,
+    },
+    /// Several independent errors collected before giving up.
+    Multiple(Vec<CompilerError>),
+}
+
+impl CompilerError {
+    /// Attaches a source position, keeping the innermost one if already located.
+    pub fn at(self, line: usize, column: usize) -> Self {
+        match self {
+            CompilerError::Located { .. } => self,
+            error => CompilerError::Located {
+                error: Box::new(error),
+                line,
+                column,
+            },
+        }
+    }
+
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            CompilerError::Located { line, column, .. } => Some((*line, *column)),
+            CompilerError::Multiple(errors) => errors.first().and_then(|e| e.location()),
+            _ => None,
+        }
+    }
+
+    /// Flattens `Multiple` into its individual errors.
+    pub fn into_errors(self) -> Vec<CompilerError> {
+        match self {
+            CompilerError::Multiple(errors) => errors.into_iter().flat_map(|e| e.into_errors()).collect(),
+            error => vec![error],
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CompilerError::LexerError(msg)
+            | CompilerError::ParseError(msg)
+            | CompilerError::SemanticError(msg)
+            | CompilerError::CodeGenError(msg)
+            | CompilerError::IoError(msg)
+            | CompilerError::AssemblyError(msg)
+            | CompilerError::LinkError(msg) => msg,
+            CompilerError::Located { error, .. } => error.message(),
+            CompilerError::Multiple(errors) => errors.first().map_or("", |e| e.message()),
+        }
+    }
+
+    pub fn phase(&self) -> &'static str {
+        match self {
+            CompilerError::LexerError(_) => "lexer",
+            CompilerError::ParseError(_) => "parser",
+            CompilerError::SemanticError(_) => "semantic",
+            CompilerError::CodeGenError(_) => "codegen",
+            CompilerError::IoError(_) => "io",
+            CompilerError::AssemblyError(_) => "assembler",
+            CompilerError::LinkError(_) => "linker",
+            CompilerError::Located { error, .. } => error.phase(),
+            CompilerError::Multiple(errors) => errors.first().map_or("", |e| e.phase()),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            CompilerError::LexerError(_) => "Lexer error",
+            CompilerError::ParseError(_) => "Parse error",
+            CompilerError::SemanticError(_) => "Semantic error",
+            CompilerError::CodeGenError(_) => "Code generation error",
+            CompilerError::IoError(_) => "IO error",
+            CompilerError::AssemblyError(_) => "Assembly error",
+            CompilerError::LinkError(_) => "Link error",
+            CompilerError::Located { error, .. } => error.kind(),
+            CompilerError::Multiple(errors) => errors.first().map_or("Error", |e| e.kind()),
+        }
+    }
 }
 
 impl fmt::Display for CompilerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            CompilerError::LexerError(msg) => write!(f, "Lexer error: {}", msg),
-            CompilerError::ParseError(msg) => write!(f, "Parse error: {}", msg),
-            CompilerError::SemanticError(msg) => write!(f, "Semantic error: {}", msg),
-            CompilerError::CodeGenError(msg) => write!(f, "Code generation error: {}", msg),
-            CompilerError::IoError(msg) => write!(f, "IO error: {}", msg),
-            CompilerError::AssemblyError(msg) => write!(f, "Assembly error: {}", msg),
-            CompilerError::LinkError(msg) => write!(f, "Link error: {}", msg),
+        match self.location() {
+            Some((line, column)) => write!(f, "{} at line {}, column {}: {}", self.kind(), line, column, self.message()),
+            None => write!(f, "{}: {}", self.kind(), self.message()),
         }
     }
 }
 
 impl std::error::Error for CompilerError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// A reportable message, either an error that stopped compilation or a warning.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub phase: &'static str,
+}
+
+impl Diagnostic {
+    pub fn warning(phase: &'static str, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message,
+            line: None,
+            column: None,
+            phase,
+        }
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        let position = |value: Option<usize>| value.map_or("null".to_string(), |v| v.to_string());
+        format!(
+            "{{\"severity\":\"{}\",\"message\":\"{}\",\"line\":{},\"column\":{},\"phase\":\"{}\"}}",
+            self.severity.as_str(),
+            json_escape(&self.message),
+            position(self.line),
+            position(self.column),
+            self.phase
+        )
+    }
+}
+
+impl From<&CompilerError> for Diagnostic {
+    fn from(error: &CompilerError) -> Self {
+        let location = error.location();
+        Diagnostic {
+            severity: Severity::Error,
+            message: error.message().to_string(),
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+            phase: error.phase(),
+        }
+    }
+}
+
+pub fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
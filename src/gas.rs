@@ -0,0 +1,214 @@
+//! Translation of the generated NASM assembly to GNU as syntax (AT&T), for
+//! building with the GNU toolchain instead of NASM and the Microsoft linker.
+
+const REGISTERS: [&str; 52] = [
+    "rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "rsp",
+    "r8", "r9", "r10", "r11", "r12", "r13", "r14", "r15",
+    "eax", "ebx", "ecx", "edx", "esi", "edi", "ebp", "esp",
+    "ax", "bx", "cx", "dx", "al", "bl", "cl", "dl",
+    "r8d", "r9d", "r10d", "r11d", "r8b", "r9b",
+    "xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7",
+    "xmm8", "xmm9", "xmm10", "xmm11", "xmm12", "xmm13",
+];
+
+/// Rewrites `asm` line by line. Operands are reversed and written with `%`
+/// registers, `$` immediates and `disp(base,index,scale)` memory references.
+pub fn to_gas(asm: &str) -> String {
+    let mut out = String::new();
+    for line in asm.lines() {
+        out.push_str(&translate_line(line));
+        out.push('\n');
+    }
+    out
+}
+
+fn translate_line(line: &str) -> String {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.ends_with(':') {
+        return line.to_string();
+    }
+    // GNU as takes its section attributes from the name.
+    if let Some(section) = trimmed.strip_prefix("section ") {
+        return section.split_whitespace().next().unwrap_or(section).to_string();
+    }
+    if let Some(name) = trimmed.strip_prefix("global ") {
+        return format!("    .globl {}", symbol(name));
+    }
+    if let Some(name) = trimmed.strip_prefix("extern ") {
+        return format!("    .extern {}", symbol(name));
+    }
+    if let Some((label, data)) = trimmed.split_once(": ") {
+        return format!("    {}: {}", label, data_directive(data));
+    }
+    format!("    {}", instruction(trimmed))
+}
+
+/// MinGW's stack probe has the same contract as MSVC's `__chkstk`: the size in
+/// rax, rsp left unchanged.
+fn symbol(name: &str) -> &str {
+    match name {
+        "__chkstk" => "___chkstk_ms",
+        _ => name,
+    }
+}
+
+fn data_directive(data: &str) -> String {
+    if let Some(text) = data.strip_prefix("db `").and_then(|d| d.strip_suffix("`, 0")) {
+        return format!(".asciz \"{}\"", gas_string(&nasm_unescape(text)));
+    }
+    if let Some(value) = data.strip_prefix("dq ") {
+        return format!(".quad {}", value);
+    }
+    if let Some(count) = data.strip_prefix("resq ") {
+        let count: usize = count.trim().parse().unwrap_or(1);
+        return format!(".zero {}", 8 * count);
+    }
+    data.to_string()
+}
+
+/// Decodes the escapes written by the code generator in a NASM backquoted string.
+fn nasm_unescape(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                bytes.push(u8::from_str_radix(&hex, 16).unwrap_or(0));
+            }
+            Some(other) => bytes.push(other as u8),
+            None => bytes.push(b'\\'),
+        }
+    }
+    bytes
+}
+
+/// Escapes bytes for a GNU as string, using octal for anything unprintable.
+fn gas_string(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for &b in bytes {
+        match b {
+            b'"' => out.push_str("\\\""),
+            b'\\' => out.push_str("\\\\"),
+            0x20..=0x7e => out.push(b as char),
+            _ => out.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+    out
+}
+
+fn instruction(line: &str) -> String {
+    let (mnemonic, operands) = line.split_once(' ').unwrap_or((line, ""));
+    let operands: Vec<&str> = if operands.is_empty() {
+        Vec::new()
+    } else {
+        operands.split(", ").map(str::trim).collect()
+    };
+
+    if mnemonic == "call" || mnemonic.starts_with('j') {
+        return format!("{} {}", mnemonic, symbol(operands[0]));
+    }
+
+    let mnemonic = match mnemonic {
+        "movzx" | "movsx" => extend_mnemonic(mnemonic, operands[1], operands[0]),
+        "movsxd" => "movslq".to_string(),
+        "cqo" => "cqto".to_string(),
+        // Without a register to imply it, the operand size goes on the mnemonic.
+        _ => match operands.iter().find_map(|o| size_suffix(o)) {
+            Some(suffix) if !operands.iter().any(|o| is_register(o)) => format!("{}{}", mnemonic, suffix),
+            _ => mnemonic.to_string(),
+        },
+    };
+    if operands.is_empty() {
+        return mnemonic;
+    }
+    let operands: Vec<String> = operands.iter().rev().map(|o| operand(o)).collect();
+    format!("{} {}", mnemonic, operands.join(", "))
+}
+
+/// `movzx`/`movsx` spell out both sizes in AT&T syntax, as in `movzbq`.
+fn extend_mnemonic(mnemonic: &str, source: &str, destination: &str) -> String {
+    let from = match source.split_whitespace().next() {
+        Some("byte") | Some("al" | "bl" | "cl" | "dl") => 'b',
+        _ => 'w',
+    };
+    let to = if destination.starts_with('e') { 'l' } else { 'q' };
+    format!("{}{}{}", &mnemonic[..4], from, to)
+}
+
+fn size_suffix(operand: &str) -> Option<char> {
+    match operand.split_whitespace().next()? {
+        "byte" => Some('b'),
+        "word" => Some('w'),
+        "dword" => Some('l'),
+        "qword" => Some('q'),
+        _ => None,
+    }
+}
+
+fn is_register(operand: &str) -> bool {
+    REGISTERS.contains(&operand)
+}
+
+fn operand(operand: &str) -> String {
+    let operand = match size_suffix(operand) {
+        Some(_) => operand.split_once(' ').map_or(operand, |(_, rest)| rest),
+        None => operand,
+    };
+    if is_register(operand) {
+        return format!("%{}", operand);
+    }
+    if let Some(address) = operand.strip_prefix('[').and_then(|o| o.strip_suffix(']')) {
+        return memory(address);
+    }
+    if operand.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+        return format!("${}", operand);
+    }
+    operand.to_string()
+}
+
+/// Converts `base+index*scale+disp` to `disp(%base,%index,scale)`, and
+/// `rel symbol` to `symbol(%rip)`.
+fn memory(address: &str) -> String {
+    if let Some(name) = address.strip_prefix("rel ") {
+        return format!("{}(%rip)", name);
+    }
+
+    let mut base = None;
+    let mut index = None;
+    let mut displacement = 0i64;
+    let mut term = String::new();
+    let mut negative = false;
+    for c in address.chars().chain(std::iter::once('+')) {
+        if c != '+' && c != '-' {
+            term.push(c);
+            continue;
+        }
+        if let Some((register, scale)) = term.split_once('*') {
+            index = Some((register.to_string(), scale.to_string()));
+        } else if is_register(&term) && base.is_some() {
+            index = Some((term.clone(), "1".to_string()));
+        } else if is_register(&term) {
+            base = Some(term.clone());
+        } else if let Ok(value) = term.parse::<i64>() {
+            displacement += if negative { -value } else { value };
+        }
+        term.clear();
+        negative = c == '-';
+    }
+
+    let displacement = if displacement == 0 { String::new() } else { displacement.to_string() };
+    let base = base.map_or(String::new(), |b| format!("%{}", b));
+    match index {
+        Some((register, scale)) => format!("{}({},%{},{})", displacement, base, register, scale),
+        None => format!("{}({})", displacement, base),
+    }
+}
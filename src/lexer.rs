@@ -1,28 +1,152 @@
 use crate::error::CompilerError;
+use std::collections::HashSet;
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     Module, Import, Fn, Let, Mut, Const, Return, If, Else, While, For, Loop, Break, Continue,
-    Struct, Enum, Union, Type, Pub, Unsafe, Defer,
+    Struct, Enum, Union, Impl, Type, Pub, Unsafe, Defer, Static,
     I8, I16, I32, I64, U8, U16, U32, U64, F32, F64, Bool, Char, Void, Str,
     
     IntLiteral(i64),
+    /// An integer above `i64::MAX` that still fits a `u64`.
+    UIntLiteral(u64),
     FloatLiteral(f64),
     StringLiteral(String),
     CharLiteral(char),
     BoolLiteral(bool),
     
     Identifier(String),
+    Label(String),
     
     Plus, Minus, Star, Slash, Percent,
     Equal, EqualEqual, NotEqual, Less, LessEqual, Greater, GreaterEqual,
     AmpAmp, PipePipe, Bang,
     Amp, Pipe, Caret, Tilde, LessLess, GreaterGreater,
+    PlusEqual, MinusEqual, StarEqual, SlashEqual, PercentEqual,
+    AmpEqual, PipeEqual, CaretEqual, LessLessEqual, GreaterGreaterEqual,
     LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
-    Semicolon, Comma, Dot, Colon, ColonColon, Arrow, FatArrow,
+    Semicolon, Comma, Dot, DotDot, DotDotDot, DotDotEq, Colon, ColonColon, Arrow, FatArrow, At,
     Eof,
 }
 
+impl TokenType {
+    /// Source spelling of keywords and punctuation.
+    fn spelling(&self) -> Option<&'static str> {
+        let s = match self {
+            TokenType::Module => "module",
+            TokenType::Import => "import",
+            TokenType::Fn => "fn",
+            TokenType::Let => "let",
+            TokenType::Mut => "mut",
+            TokenType::Const => "const",
+            TokenType::Return => "return",
+            TokenType::If => "if",
+            TokenType::Else => "else",
+            TokenType::While => "while",
+            TokenType::For => "for",
+            TokenType::Loop => "loop",
+            TokenType::Break => "break",
+            TokenType::Continue => "continue",
+            TokenType::Struct => "struct",
+            TokenType::Enum => "enum",
+            TokenType::Union => "union",
+            TokenType::Impl => "impl",
+            TokenType::Type => "type",
+            TokenType::Pub => "pub",
+            TokenType::Unsafe => "unsafe",
+            TokenType::Defer => "defer",
+            TokenType::Static => "static",
+            TokenType::I8 => "i8",
+            TokenType::I16 => "i16",
+            TokenType::I32 => "i32",
+            TokenType::I64 => "i64",
+            TokenType::U8 => "u8",
+            TokenType::U16 => "u16",
+            TokenType::U32 => "u32",
+            TokenType::U64 => "u64",
+            TokenType::F32 => "f32",
+            TokenType::F64 => "f64",
+            TokenType::Bool => "bool",
+            TokenType::Char => "char",
+            TokenType::Void => "void",
+            TokenType::Str => "str",
+            TokenType::BoolLiteral(true) => "true",
+            TokenType::BoolLiteral(false) => "false",
+            TokenType::Plus => "+",
+            TokenType::Minus => "-",
+            TokenType::Star => "*",
+            TokenType::Slash => "/",
+            TokenType::Percent => "%",
+            TokenType::Equal => "=",
+            TokenType::EqualEqual => "==",
+            TokenType::NotEqual => "!=",
+            TokenType::Less => "<",
+            TokenType::LessEqual => "<=",
+            TokenType::Greater => ">",
+            TokenType::GreaterEqual => ">=",
+            TokenType::AmpAmp => "&&",
+            TokenType::PipePipe => "||",
+            TokenType::Bang => "!",
+            TokenType::Amp => "&",
+            TokenType::Pipe => "|",
+            TokenType::Caret => "^",
+            TokenType::Tilde => "~",
+            TokenType::LessLess => "<<",
+            TokenType::GreaterGreater => ">>",
+            TokenType::PlusEqual => "+=",
+            TokenType::MinusEqual => "-=",
+            TokenType::StarEqual => "*=",
+            TokenType::SlashEqual => "/=",
+            TokenType::PercentEqual => "%=",
+            TokenType::AmpEqual => "&=",
+            TokenType::PipeEqual => "|=",
+            TokenType::CaretEqual => "^=",
+            TokenType::LessLessEqual => "<<=",
+            TokenType::GreaterGreaterEqual => ">>=",
+            TokenType::LeftParen => "(",
+            TokenType::RightParen => ")",
+            TokenType::LeftBrace => "{",
+            TokenType::RightBrace => "}",
+            TokenType::LeftBracket => "[",
+            TokenType::RightBracket => "]",
+            TokenType::Semicolon => ";",
+            TokenType::Comma => ",",
+            TokenType::Dot => ".",
+            TokenType::DotDot => "..",
+            TokenType::DotDotDot => "...",
+            TokenType::DotDotEq => "..=",
+            TokenType::Colon => ":",
+            TokenType::ColonColon => "::",
+            TokenType::Arrow => "->",
+            TokenType::FatArrow => "=>",
+            TokenType::At => "@",
+            _ => return None,
+        };
+        Some(s)
+    }
+}
+
+/// Describes a token for error messages, e.g. `';'` or `identifier 'foo'`.
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(spelling) = self.spelling() {
+            return write!(f, "'{}'", spelling);
+        }
+        match self {
+            TokenType::IntLiteral(n) => write!(f, "number {}", n),
+            TokenType::UIntLiteral(n) => write!(f, "number {}", n),
+            TokenType::FloatLiteral(x) => write!(f, "number {:?}", x),
+            TokenType::StringLiteral(s) => write!(f, "string \"{}\"", s.escape_default()),
+            TokenType::CharLiteral(c) => write!(f, "character '{}'", c.escape_default()),
+            TokenType::Identifier(name) => write!(f, "identifier '{}'", name),
+            TokenType::Label(name) => write!(f, "label '{}", name),
+            TokenType::Eof => write!(f, "end of file"),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
@@ -35,38 +159,55 @@ pub struct Lexer {
     position: usize,
     line: usize,
     column: usize,
+    /// Set once `Eof` or an error has been yielded.
+    finished: bool,
+    /// Symbols defined with `-D`, tested by `#if`.
+    defines: HashSet<String>,
+    /// Positions of the `#if`s whose regions are being lexed.
+    open_conditionals: Vec<(usize, usize)>,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Self {
+        // Editors on Windows often start UTF-8 files with a byte order mark.
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
         Lexer {
             input: input.chars().collect(),
             position: 0,
             line: 1,
             column: 1,
+            finished: false,
+            defines: HashSet::new(),
+            open_conditionals: Vec::new(),
         }
     }
     
+    pub fn with_defines(mut self, defines: &[String]) -> Self {
+        self.defines = defines.iter().cloned().collect();
+        self
+    }
+    
     pub fn tokenize(&mut self) -> Result<Vec<Token>, CompilerError> {
-        let mut tokens = Vec::new();
+        self.collect()
+    }
+    
+    /// Scans the token after any whitespace and comments, or `Eof` at the end.
+    fn scan_token(&mut self) -> Result<Token, CompilerError> {
+        self.skip_whitespace()?;
         
-        loop {
-            self.skip_whitespace();
-            
-            if self.is_at_end() {
-                tokens.push(Token {
-                    token_type: TokenType::Eof,
-                    line: self.line,
-                    column: self.column,
-                });
-                break;
+        if self.is_at_end() {
+            if let Some(&(line, column)) = self.open_conditionals.last() {
+                return Err(CompilerError::LexerError("Unterminated #if".to_string()).at(line, column));
             }
-            
-            let token = self.next_token()?;
-            tokens.push(token);
+            return Ok(Token {
+                token_type: TokenType::Eof,
+                line: self.line,
+                column: self.column,
+            });
         }
         
-        Ok(tokens)
+        let (line, column) = (self.line, self.column);
+        self.next_token().map_err(|e| e.at(line, column))
     }
     
     fn next_token(&mut self) -> Result<Token, CompilerError> {
@@ -75,16 +216,6 @@ impl Lexer {
         
         let c = self.current_char();
         
-        if c == '/' && self.peek() == Some('/') {
-            self.skip_line_comment();
-            return self.next_token();
-        }
-        
-        if c == '/' && self.peek() == Some('*') {
-            self.skip_block_comment()?;
-            return self.next_token();
-        }
-        
         if c.is_ascii_digit() {
             return Ok(Token {
                 token_type: self.read_number()?,
@@ -109,6 +240,14 @@ impl Lexer {
             });
         }
         
+        if c == '\'' && self.is_label_start() {
+            return Ok(Token {
+                token_type: self.read_label(),
+                line,
+                column,
+            });
+        }
+        
         if c == '\'' {
             return Ok(Token {
                 token_type: self.read_char()?,
@@ -118,19 +257,19 @@ impl Lexer {
         }
         
         let token_type = match c {
-            '+' => { self.advance(); TokenType::Plus }
+            '+' => { self.advance(); self.or_equal(TokenType::Plus, TokenType::PlusEqual) }
             '-' => {
                 self.advance();
                 if self.current_char() == '>' {
                     self.advance();
                     TokenType::Arrow
                 } else {
-                    TokenType::Minus
+                    self.or_equal(TokenType::Minus, TokenType::MinusEqual)
                 }
             }
-            '*' => { self.advance(); TokenType::Star }
-            '/' => { self.advance(); TokenType::Slash }
-            '%' => { self.advance(); TokenType::Percent }
+            '*' => { self.advance(); self.or_equal(TokenType::Star, TokenType::StarEqual) }
+            '/' => { self.advance(); self.or_equal(TokenType::Slash, TokenType::SlashEqual) }
+            '%' => { self.advance(); self.or_equal(TokenType::Percent, TokenType::PercentEqual) }
             '=' => {
                 self.advance();
                 if self.current_char() == '=' {
@@ -159,7 +298,7 @@ impl Lexer {
                     TokenType::LessEqual
                 } else if self.current_char() == '<' {
                     self.advance();
-                    TokenType::LessLess
+                    self.or_equal(TokenType::LessLess, TokenType::LessLessEqual)
                 } else {
                     TokenType::Less
                 }
@@ -171,7 +310,7 @@ impl Lexer {
                     TokenType::GreaterEqual
                 } else if self.current_char() == '>' {
                     self.advance();
-                    TokenType::GreaterGreater
+                    self.or_equal(TokenType::GreaterGreater, TokenType::GreaterGreaterEqual)
                 } else {
                     TokenType::Greater
                 }
@@ -182,7 +321,7 @@ impl Lexer {
                     self.advance();
                     TokenType::AmpAmp
                 } else {
-                    TokenType::Amp
+                    self.or_equal(TokenType::Amp, TokenType::AmpEqual)
                 }
             }
             '|' => {
@@ -191,11 +330,12 @@ impl Lexer {
                     self.advance();
                     TokenType::PipePipe
                 } else {
-                    TokenType::Pipe
+                    self.or_equal(TokenType::Pipe, TokenType::PipeEqual)
                 }
             }
-            '^' => { self.advance(); TokenType::Caret }
+            '^' => { self.advance(); self.or_equal(TokenType::Caret, TokenType::CaretEqual) }
             '~' => { self.advance(); TokenType::Tilde }
+            '@' => { self.advance(); TokenType::At }
             '(' => { self.advance(); TokenType::LeftParen }
             ')' => { self.advance(); TokenType::RightParen }
             '{' => { self.advance(); TokenType::LeftBrace }
@@ -204,7 +344,23 @@ impl Lexer {
             ']' => { self.advance(); TokenType::RightBracket }
             ';' => { self.advance(); TokenType::Semicolon }
             ',' => { self.advance(); TokenType::Comma }
-            '.' => { self.advance(); TokenType::Dot }
+            '.' => {
+                self.advance();
+                if self.current_char() == '.' {
+                    self.advance();
+                    if self.current_char() == '.' {
+                        self.advance();
+                        TokenType::DotDotDot
+                    } else if self.current_char() == '=' {
+                        self.advance();
+                        TokenType::DotDotEq
+                    } else {
+                        TokenType::DotDot
+                    }
+                } else {
+                    TokenType::Dot
+                }
+            }
             ':' => {
                 self.advance();
                 if self.current_char() == ':' {
@@ -216,9 +372,9 @@ impl Lexer {
             }
             _ => {
                 return Err(CompilerError::LexerError(format!(
-                    "Unexpected character '{}' at line {}, column {}",
-                    c, line, column
-                )));
+                    "Unexpected character '{}'",
+                    c
+                )).at(line, column));
             }
         };
         
@@ -251,10 +407,12 @@ impl Lexer {
             "struct" => TokenType::Struct,
             "enum" => TokenType::Enum,
             "union" => TokenType::Union,
+            "impl" => TokenType::Impl,
             "type" => TokenType::Type,
             "pub" => TokenType::Pub,
             "unsafe" => TokenType::Unsafe,
             "defer" => TokenType::Defer,
+            "static" => TokenType::Static,
             "i8" => TokenType::I8,
             "i16" => TokenType::I16,
             "i32" => TokenType::I32,
@@ -276,6 +434,18 @@ impl Lexer {
     }
     
     fn read_number(&mut self) -> Result<TokenType, CompilerError> {
+        if self.current_char() == '0' {
+            let radix = match self.peek() {
+                Some('x') | Some('X') => Some(16),
+                Some('b') | Some('B') => Some(2),
+                Some('o') | Some('O') => Some(8),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                return self.read_radix_number(radix);
+            }
+        }
+        
         let mut num_str = String::new();
         let mut is_float = false;
         
@@ -285,9 +455,11 @@ impl Lexer {
         }
         
         if !self.is_at_end() && self.current_char() == '.' {
-            if self.peek() == Some('.') {
-                // This is a range operator, not a float. return the integer we've read so far
-                return Ok(TokenType::IntLiteral(num_str.parse().unwrap()));
+            // A second dot starts a range (`1..5`) and a name a field or method
+            // (`1.foo`), so the dot is left for the next token. Anything else
+            // makes a float, including a trailing dot (`1.`).
+            if self.peek().is_some_and(|c| c == '.' || c.is_alphabetic() || c == '_') {
+                return Self::parse_int(&num_str, 10);
             }
             
             is_float = true;
@@ -301,12 +473,42 @@ impl Lexer {
         }
         
         if is_float {
-            Ok(TokenType::FloatLiteral(num_str.parse().unwrap()))
+            num_str.parse()
+                .map(TokenType::FloatLiteral)
+                .map_err(|_| CompilerError::LexerError(format!("Invalid float literal: {}", num_str)))
         } else {
-            Ok(TokenType::IntLiteral(num_str.parse().unwrap()))
+            Self::parse_int(&num_str, 10)
         }
     }
     
+    /// Reads `0x`, `0b` or `0o` literals. `_` may separate digits.
+    fn read_radix_number(&mut self, radix: u32) -> Result<TokenType, CompilerError> {
+        self.advance(); // Skip '0'
+        let prefix = self.current_char();
+        self.advance();
+        
+        let mut digits = String::new();
+        while !self.is_at_end() && (self.current_char().is_digit(radix) || self.current_char() == '_') {
+            if self.current_char() != '_' {
+                digits.push(self.current_char());
+            }
+            self.advance();
+        }
+        
+        if digits.is_empty() {
+            return Err(CompilerError::LexerError(format!("Expected digits after '0{}'", prefix)));
+        }
+        Self::parse_int(&digits, radix)
+    }
+    
+    /// An integer too large for an `i64` is read as a `u64`, the only type that can hold it.
+    fn parse_int(digits: &str, radix: u32) -> Result<TokenType, CompilerError> {
+        i64::from_str_radix(digits, radix)
+            .map(TokenType::IntLiteral)
+            .or_else(|_| u64::from_str_radix(digits, radix).map(TokenType::UIntLiteral))
+            .map_err(|_| CompilerError::LexerError(format!("Integer literal too large: {}", digits)))
+    }
+    
     fn read_string(&mut self) -> Result<TokenType, CompilerError> {
         self.advance();
         let mut s = String::new();
@@ -317,18 +519,11 @@ impl Lexer {
                 if self.is_at_end() {
                     return Err(CompilerError::LexerError("Unterminated string".to_string()));
                 }
-                match self.current_char() {
-                    'n' => s.push('\n'),
-                    't' => s.push('\t'),
-                    'r' => s.push('\r'),
-                    '\\' => s.push('\\'),
-                    '"' => s.push('"'),
-                    _ => s.push(self.current_char()),
-                }
+                s.push(self.read_escape()?);
             } else {
                 s.push(self.current_char());
+                self.advance();
             }
-            self.advance();
         }
         
         if self.is_at_end() {
@@ -346,51 +541,146 @@ impl Lexer {
             return Err(CompilerError::LexerError("Unterminated character".to_string()));
         }
         
-        let ch = if self.current_char() == '\\' {
-            self.advance();
-            match self.current_char() {
-                'n' => '\n',
-                't' => '\t',
-                'r' => '\r',
-                '\\' => '\\',
-                '\'' => '\'',
-                _ => self.current_char(),
+        let ch = match self.current_char() {
+            '\'' => {
+                return Err(CompilerError::LexerError("Empty character literal".to_string()));
+            }
+            '\\' => {
+                self.advance();
+                if self.is_at_end() {
+                    return Err(CompilerError::LexerError("Unterminated character".to_string()));
+                }
+                self.read_escape()?
+            }
+            c => {
+                self.advance();
+                c
             }
-        } else {
-            self.current_char()
         };
         
-        self.advance();
-        
-        if self.is_at_end() || self.current_char() != '\'' {
+        if self.is_at_end() || self.current_char() == '\n' {
             return Err(CompilerError::LexerError("Unterminated character".to_string()));
         }
+        if self.current_char() != '\'' {
+            return Err(CompilerError::LexerError(
+                "Character literal must contain exactly one character".to_string()
+            ));
+        }
         
         self.advance();
         Ok(TokenType::CharLiteral(ch))
     }
     
-    fn skip_whitespace(&mut self) {
+    /// Decodes the escape sequence after a backslash, shared by string and
+    /// character literals. Leaves the lexer just past the sequence.
+    fn read_escape(&mut self) -> Result<char, CompilerError> {
+        let c = self.current_char();
+        self.advance();
+        
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '\'' => Ok('\''),
+            '"' => Ok('"'),
+            'x' => {
+                let digits = self.read_hex_digits(2);
+                if digits.len() != 2 {
+                    return Err(CompilerError::LexerError(
+                        "Expected two hex digits after '\\x'".to_string()
+                    ));
+                }
+                let value = u32::from_str_radix(&digits, 16).unwrap();
+                if value > 0x7F {
+                    return Err(CompilerError::LexerError(format!(
+                        "Hex escape '\\x{}' out of range (must be at most \\x7F)",
+                        digits
+                    )));
+                }
+                Ok(char::from(value as u8))
+            }
+            'u' => {
+                if self.is_at_end() || self.current_char() != '{' {
+                    return Err(CompilerError::LexerError("Expected '{' after '\\u'".to_string()));
+                }
+                self.advance();
+                
+                let digits = self.read_hex_digits(6);
+                if digits.is_empty() || self.is_at_end() || self.current_char() != '}' {
+                    return Err(CompilerError::LexerError(
+                        "Unicode escape must be '\\u{...}' with 1 to 6 hex digits".to_string()
+                    ));
+                }
+                self.advance();
+                
+                let value = u32::from_str_radix(&digits, 16).unwrap();
+                char::from_u32(value).ok_or_else(|| CompilerError::LexerError(format!(
+                    "Invalid unicode escape '\\u{{{}}}'",
+                    digits
+                )))
+            }
+            other => Err(CompilerError::LexerError(format!("Unknown escape '\\{}'", other))),
+        }
+    }
+    
+    fn read_hex_digits(&mut self, max: usize) -> String {
+        let mut digits = String::new();
+        while digits.len() < max && !self.is_at_end() && self.current_char().is_ascii_hexdigit() {
+            digits.push(self.current_char());
+            self.advance();
+        }
+        digits
+    }
+    
+    /// `'name` is a loop label unless a closing quote follows the name, as in
+    /// `'a'` (or the malformed `'ab'`, which `read_char` then rejects).
+    fn is_label_start(&self) -> bool {
+        let mut i = self.position + 1;
+        if !matches!(self.input.get(i), Some(c) if c.is_alphabetic() || *c == '_') {
+            return false;
+        }
+        while matches!(self.input.get(i), Some(c) if c.is_alphanumeric() || *c == '_') {
+            i += 1;
+        }
+        self.input.get(i) != Some(&'\'')
+    }
+    
+    fn read_label(&mut self) -> TokenType {
+        self.advance();
+        let mut s = String::new();
+        
+        while !self.is_at_end() && (self.current_char().is_alphanumeric() || self.current_char() == '_') {
+            s.push(self.current_char());
+            self.advance();
+        }
+        
+        TokenType::Label(s)
+    }
+    
+    /// Skips whitespace and comments up to the next token.
+    fn skip_whitespace(&mut self) -> Result<(), CompilerError> {
         while !self.is_at_end() {
             match self.current_char() {
-                ' ' | '\t' | '\r' => self.advance(),
-                '\n' => {
-                    self.line += 1;
-                    self.column = 1;
-                    self.position += 1;
-                }
+                ' ' | '\t' | '\r' | '\n' => self.advance(),
+                '/' if self.peek() == Some('/') => self.skip_line_comment(),
+                '/' if self.peek() == Some('*') => self.skip_block_comment()?,
+                '#' => self.read_directive()?,
                 _ => break,
             }
         }
+        Ok(())
     }
     
     fn skip_line_comment(&mut self) {
-        while !self.is_at_end() && self.current_char() != '\n' {
+        while !self.is_at_end() && !matches!(self.current_char(), '\n' | '\r') {
             self.advance();
         }
     }
     
     fn skip_block_comment(&mut self) -> Result<(), CompilerError> {
+        let (line, column) = (self.line, self.column);
         self.advance(); // Skip '/'
         self.advance(); // Skip '*'
         
@@ -400,20 +690,121 @@ impl Lexer {
                 self.advance(); // Skip '/'
                 return Ok(());
             }
-            if self.current_char() == '\n' {
-                self.line += 1;
-                self.column = 1;
-                self.position += 1;
-            } else {
+            self.advance();
+        }
+        
+        Err(CompilerError::LexerError("Unterminated block comment".to_string()).at(line, column))
+    }
+    
+    /// Handles `#if NAME` and `#endif`. The region after an `#if` whose
+    /// symbol isn't defined is skipped up to the matching `#endif`.
+    fn read_directive(&mut self) -> Result<(), CompilerError> {
+        let (line, column) = (self.line, self.column);
+        self.advance(); // Skip '#'
+        
+        match self.read_word().as_str() {
+            "if" => {
+                while !self.is_at_end() && matches!(self.current_char(), ' ' | '\t') {
+                    self.advance();
+                }
+                let symbol = self.read_word();
+                if symbol.is_empty() {
+                    return Err(CompilerError::LexerError("Expected a symbol after #if".to_string()).at(line, column));
+                }
+                if self.defines.contains(&symbol) {
+                    self.open_conditionals.push((line, column));
+                } else {
+                    self.skip_conditional_region(line, column)?;
+                }
+            }
+            "endif" => {
+                if self.open_conditionals.pop().is_none() {
+                    return Err(CompilerError::LexerError("#endif without a matching #if".to_string()).at(line, column));
+                }
+            }
+            directive => {
+                return Err(CompilerError::LexerError(format!("Unknown directive '#{}'", directive)).at(line, column));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Skips to just past the `#endif` closing the `#if` at `line`/`column`,
+    /// including any `#if` regions nested inside. A `#` inside a comment,
+    /// string or character literal isn't a directive.
+    fn skip_conditional_region(&mut self, line: usize, column: usize) -> Result<(), CompilerError> {
+        let mut depth = 1;
+        while !self.is_at_end() {
+            match self.current_char() {
+                '#' => {}
+                '/' if self.peek() == Some('/') => {
+                    self.skip_line_comment();
+                    continue;
+                }
+                '/' if self.peek() == Some('*') => {
+                    self.skip_block_comment()?;
+                    continue;
+                }
+                '"' => {
+                    self.skip_quoted();
+                    continue;
+                }
+                // A character literal, or the quote starting a loop label.
+                '\'' if self.peek() == Some('\\') || self.input.get(self.position + 2) == Some(&'\'') => {
+                    self.skip_quoted();
+                    continue;
+                }
+                _ => {
+                    self.advance();
+                    continue;
+                }
+            }
+            self.advance();
+            match self.read_word().as_str() {
+                "if" => depth += 1,
+                "endif" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(CompilerError::LexerError("Unterminated #if".to_string()).at(line, column))
+    }
+    
+    /// Skips a string or character literal in a skipped region without
+    /// checking its escapes.
+    fn skip_quoted(&mut self) {
+        let quote = self.current_char();
+        self.advance();
+        while !self.is_at_end() && self.current_char() != quote {
+            if self.current_char() == '\\' {
+                self.advance();
+            }
+            if !self.is_at_end() {
                 self.advance();
             }
         }
-        
-        Err(CompilerError::LexerError("Unterminated block comment".to_string()))
+        if !self.is_at_end() {
+            self.advance();
+        }
     }
     
+    fn read_word(&mut self) -> String {
+        let mut word = String::new();
+        while !self.is_at_end() && (self.current_char().is_alphanumeric() || self.current_char() == '_') {
+            word.push(self.current_char());
+            self.advance();
+        }
+        word
+    }
+    
+    /// The character at the current position, or `'\0'` past the end so that
+    /// lookahead after the last character never reads out of bounds.
     fn current_char(&self) -> char {
-        self.input[self.position]
+        self.input.get(self.position).copied().unwrap_or('\0')
     }
     
     fn peek(&self) -> Option<char> {
@@ -424,12 +815,54 @@ impl Lexer {
         }
     }
     
+    /// Returns `compound` and consumes the `=` if one follows an operator, as
+    /// in `+=`, and `plain` otherwise.
+    fn or_equal(&mut self, plain: TokenType, compound: TokenType) -> TokenType {
+        if self.current_char() == '=' {
+            self.advance();
+            compound
+        } else {
+            plain
+        }
+    }
+    
+    /// Steps past the current character, moving to the next line after `\n`,
+    /// `\r\n` or a lone `\r`.
     fn advance(&mut self) {
+        if self.is_line_break() {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
         self.position += 1;
-        self.column += 1;
+    }
+    
+    /// The `\r` of a `\r\n` pair isn't a break on its own; the `\n` after it is.
+    fn is_line_break(&self) -> bool {
+        match self.input.get(self.position) {
+            Some('\n') => true,
+            Some('\r') => self.peek() != Some('\n'),
+            _ => false,
+        }
     }
     
     fn is_at_end(&self) -> bool {
         self.position >= self.input.len()
     }
+}
+
+/// Streams tokens lazily, ending with `Eof`. Iteration stops after the first error.
+impl Iterator for Lexer {
+    type Item = Result<Token, CompilerError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        
+        let token = self.scan_token();
+        self.finished = !matches!(&token, Ok(t) if t.token_type != TokenType::Eof);
+        Some(token)
+    }
 }
\ No newline at end of file
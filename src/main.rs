@@ -1,18 +1,93 @@
 use std::env;
 use std::fs;
 use std::process;
+use std::time::{Duration, Instant};
 
 mod lexer;
 mod parser;
 mod semantic;
 mod codegen;
+mod cfg;
+mod gas;
+mod ast_json;
+mod optimizer;
+mod const_eval;
+mod prelude;
 mod error;
+mod render;
 
 use lexer::Lexer;
 use parser::Parser;
 use semantic::SemanticAnalyzer;
-use codegen::CodeGenerator;
-use error::CompilerError;
+use codegen::{CodeGenerator, FrameLocal, MapEntry, Target};
+use error::{CompilerError, Diagnostic};
+use render::{ColorChoice, Renderer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MessageFormat {
+    Human,
+    Json,
+}
+
+/// How much progress output to print.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+/// Assembler the output is written for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AsmSyntax {
+    Nasm,
+    Gas,
+}
+
+struct Options {
+    output_file: String,
+    opt_level: u8,
+    emit_ir: bool,
+    emit_cfg: bool,
+    ast_json: bool,
+    emit_deps: bool,
+    dump_symbols: bool,
+    std: bool,
+    run: bool,
+    temp: bool,
+    message_format: MessageFormat,
+    asm_syntax: AsmSyntax,
+    target: Target,
+    color: ColorChoice,
+    max_errors: usize,
+    max_array_size: usize,
+    defines: Vec<String>,
+    warnings_as_errors: bool,
+    print_frame_sizes: bool,
+    stack_protector: bool,
+    time_report: bool,
+    /// Where `--map` writes the symbol layout.
+    map_file: Option<String>,
+    /// Stop after writing the assembly, without assembling or linking it.
+    assembly_only: bool,
+    verbosity: Verbosity,
+}
+
+impl Options {
+    /// Prints a progress line unless `--quiet` was given.
+    fn status(&self, message: &str) {
+        if self.verbosity != Verbosity::Quiet {
+            println!("{}", message);
+        }
+    }
+    
+    /// With `--verbose`, prints how long the phase just finished took.
+    fn phase_time(&self, phases: &[(&str, Duration)]) {
+        if let (Verbosity::Verbose, Some((_, time))) = (self.verbosity, phases.last()) {
+            println!("       Took {:.3} ms", time.as_secs_f64() * 1000.0);
+        }
+    }
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -22,21 +97,78 @@ fn main() {
         eprintln!("Options:");
         eprintln!("  -o <file>    Set output file name");
         eprintln!("  -O<level>    Set optimization level (0-3)");
+        eprintln!("  -D <name>    Define a symbol for #if");
+        eprintln!("  -q, --quiet  Print only diagnostics, without progress output");
+        eprintln!("  -v, --verbose");
+        eprintln!("               Also print the time each phase takes");
+        eprintln!("  -S           Write the assembly without assembling or linking it");
         eprintln!("  --emit-ir    Emit intermediate representation");
+        eprintln!("  --emit-cfg   Write each function's control-flow graph as Graphviz DOT");
+        eprintln!("  --ast-json   Write the syntax tree as JSON");
+        eprintln!("  --emit-deps  Write the source files the output depends on as a Makefile rule");
+        eprintln!("  --dump-symbols");
+        eprintln!("               Print functions and variables after semantic analysis");
+        eprintln!("  --print-frame-sizes");
+        eprintln!("               Print each function's stack frame size after code generation");
+        eprintln!("  --stack-protector");
+        eprintln!("               Check a stack canary before returning from functions with arrays");
+        eprintln!("  --std        Include the standard prelude (default)");
+        eprintln!("  --no-std     Compile without the standard prelude");
+        eprintln!("  --run        Run the executable after building it");
+        eprintln!("  --temp       Delete the executable after --run finishes");
+        eprintln!("  --message-format <human|json>");
+        eprintln!("               Set the diagnostic output format");
+        eprintln!("  --target <win64|elf64>");
+        eprintln!("               Declare sections for a NASM output format (elf64 requires -S)");
+        eprintln!("  --asm-syntax <nasm|gas>");
+        eprintln!("               Write NASM (default) or GNU as assembly");
+        eprintln!("  --color <auto|always|never>");
+        eprintln!("               Control colored diagnostics");
+        eprintln!("  --map <file>  Write each function's and static's section and offset");
+        eprintln!("  --time-report json");
+        eprintln!("               Write the time spent in each phase as JSON");
+        eprintln!("  --max-errors <n> Stop reporting after n errors (0 = no limit)");
+        eprintln!("  --max-array-size <n>");
+        eprintln!("               Reject arrays with more than n elements");
+        eprintln!("  -Werror, --warnings-as-errors");
+        eprintln!("               Fail the build if any warnings are reported");
         process::exit(1);
     }
     
     let source_file = &args[1];
-    let mut output_file = "a.out";
-    let mut opt_level = 0;
-    let mut emit_ir = false;
+    let mut options = Options {
+        output_file: "a.out".to_string(),
+        opt_level: 0,
+        emit_ir: false,
+        emit_cfg: false,
+        ast_json: false,
+        emit_deps: false,
+        dump_symbols: false,
+        std: true,
+        run: false,
+        temp: false,
+        message_format: MessageFormat::Human,
+        asm_syntax: AsmSyntax::Nasm,
+        target: Target::Win64,
+        color: ColorChoice::Auto,
+        max_errors: 20,
+        max_array_size: semantic::DEFAULT_MAX_ARRAY_SIZE,
+        defines: Vec::new(),
+        warnings_as_errors: false,
+        print_frame_sizes: false,
+        stack_protector: false,
+        time_report: false,
+        map_file: None,
+        assembly_only: false,
+        verbosity: Verbosity::Normal,
+    };
     
     let mut i = 2;
     while i < args.len() {
         match args[i].as_str() {
             "-o" => {
                 if i + 1 < args.len() {
-                    output_file = &args[i + 1];
+                    options.output_file = args[i + 1].clone();
                     i += 2;
                 } else {
                     eprintln!("Error: -o requires an argument");
@@ -45,14 +177,164 @@ fn main() {
             }
             arg if arg.starts_with("-O") => {
                 if let Some(level) = arg.chars().nth(2) {
-                    opt_level = level.to_digit(10).unwrap_or(0) as u8;
+                    options.opt_level = level.to_digit(10).unwrap_or(0) as u8;
+                }
+                i += 1;
+            }
+            "--map" => {
+                if i + 1 < args.len() {
+                    options.map_file = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --map requires a file name");
+                    process::exit(1);
                 }
+            }
+            "-D" => {
+                if i + 1 < args.len() {
+                    options.defines.push(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: -D requires a symbol name");
+                    process::exit(1);
+                }
+            }
+            arg if arg.starts_with("-D") => {
+                options.defines.push(arg[2..].to_string());
+                i += 1;
+            }
+            // The last of -q and -v wins.
+            "-q" | "--quiet" => {
+                options.verbosity = Verbosity::Quiet;
+                i += 1;
+            }
+            "-v" | "--verbose" => {
+                options.verbosity = Verbosity::Verbose;
+                i += 1;
+            }
+            "-S" => {
+                options.assembly_only = true;
+                i += 1;
+            }
+            "-Werror" | "--warnings-as-errors" => {
+                options.warnings_as_errors = true;
                 i += 1;
             }
             "--emit-ir" => {
-                emit_ir = true;
+                options.emit_ir = true;
+                i += 1;
+            }
+            "--emit-cfg" => {
+                options.emit_cfg = true;
+                i += 1;
+            }
+            "--ast-json" => {
+                options.ast_json = true;
+                i += 1;
+            }
+            "--emit-deps" => {
+                options.emit_deps = true;
+                i += 1;
+            }
+            "--dump-symbols" => {
+                options.dump_symbols = true;
+                i += 1;
+            }
+            "--print-frame-sizes" => {
+                options.print_frame_sizes = true;
+                i += 1;
+            }
+            "--stack-protector" => {
+                options.stack_protector = true;
+                i += 1;
+            }
+            "--std" => {
+                options.std = true;
                 i += 1;
             }
+            "--no-std" => {
+                options.std = false;
+                i += 1;
+            }
+            "--run" => {
+                options.run = true;
+                i += 1;
+            }
+            "--temp" => {
+                options.temp = true;
+                i += 1;
+            }
+            "--message-format" => {
+                options.message_format = match args.get(i + 1).map(String::as_str) {
+                    Some("human") => MessageFormat::Human,
+                    Some("json") => MessageFormat::Json,
+                    _ => {
+                        eprintln!("Error: --message-format requires 'human' or 'json'");
+                        process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--target" => {
+                options.target = match args.get(i + 1).map(String::as_str) {
+                    Some("win64") => Target::Win64,
+                    Some("elf64") => Target::Elf64,
+                    _ => {
+                        eprintln!("Error: --target requires 'win64' or 'elf64'");
+                        process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--asm-syntax" => {
+                options.asm_syntax = match args.get(i + 1).map(String::as_str) {
+                    Some("nasm") => AsmSyntax::Nasm,
+                    Some("gas") => AsmSyntax::Gas,
+                    _ => {
+                        eprintln!("Error: --asm-syntax requires 'nasm' or 'gas'");
+                        process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--color" => {
+                match args.get(i + 1).and_then(|c| ColorChoice::parse(c)) {
+                    Some(choice) => options.color = choice,
+                    None => {
+                        eprintln!("Error: --color requires 'auto', 'always' or 'never'");
+                        process::exit(1);
+                    }
+                }
+                i += 2;
+            }
+            "--time-report" => {
+                if args.get(i + 1).map(String::as_str) != Some("json") {
+                    eprintln!("Error: --time-report requires 'json'");
+                    process::exit(1);
+                }
+                options.time_report = true;
+                i += 2;
+            }
+            "--max-errors" => {
+                match args.get(i + 1).and_then(|n| n.parse().ok()) {
+                    Some(n) => options.max_errors = n,
+                    None => {
+                        eprintln!("Error: --max-errors requires a number");
+                        process::exit(1);
+                    }
+                }
+                i += 2;
+            }
+            "--max-array-size" => {
+                match args.get(i + 1).and_then(|n| n.parse().ok()) {
+                    Some(n) => options.max_array_size = n,
+                    None => {
+                        eprintln!("Error: --max-array-size requires a number");
+                        process::exit(1);
+                    }
+                }
+                i += 2;
+            }
             _ => {
                 eprintln!("Unknown option: {}", args[i]);
                 process::exit(1);
@@ -60,57 +342,270 @@ fn main() {
         }
     }
     
-    match compile(source_file, output_file, opt_level, emit_ir) {
+    if options.temp && !options.run {
+        eprintln!("Error: --temp requires --run");
+        process::exit(1);
+    }
+    if options.run && options.assembly_only {
+        eprintln!("Error: --run cannot be combined with -S");
+        process::exit(1);
+    }
+    // Only Windows executables can be linked so far.
+    if options.target != Target::Win64 && !options.assembly_only {
+        eprintln!("Error: --target {} requires -S", options.target.nasm_format());
+        process::exit(1);
+    }
+    
+    match compile(source_file, &options) {
+        Ok(_) if options.assembly_only => {}
         Ok(_) => {
-            println!("Compilation successful: {}", output_file);
+            options.status(&format!("Compilation successful: {}", options.output_file));
+            
+            if options.run {
+                let status = run_executable(&options.output_file);
+                if options.temp {
+                    fs::remove_file(&options.output_file).ok();
+                }
+                match status {
+                    Ok(code) => process::exit(code),
+                    Err(e) => {
+                        report(&Diagnostic::from(&e), source_file, None, &options);
+                        process::exit(1);
+                    }
+                }
+            }
         }
         Err(e) => {
-            eprintln!("Compilation failed: {}", e);
+            let source = fs::read_to_string(source_file).ok();
+            let errors = e.into_errors();
+            let limit = if options.max_errors == 0 { errors.len() } else { options.max_errors };
+            
+            for error in errors.iter().take(limit) {
+                report(&Diagnostic::from(error), source_file, source.as_deref(), &options);
+            }
+            if errors.len() > limit && options.message_format == MessageFormat::Human {
+                let remaining = errors.len() - limit;
+                eprintln!("... and {} more error{}", remaining, if remaining == 1 { "" } else { "s" });
+            }
             process::exit(1);
         }
     }
 }
 
-fn compile(source_file: &str, output_file: &str, opt_level: u8, emit_ir: bool) -> Result<(), CompilerError> {
-    println!("Compiling {}...", source_file);
+fn report(diagnostic: &Diagnostic, source_file: &str, source: Option<&str>, options: &Options) {
+    match options.message_format {
+        MessageFormat::Human => {
+            let renderer = Renderer::new(options.color);
+            eprint!("{}", renderer.render(diagnostic, source_file, source));
+        }
+        MessageFormat::Json => eprintln!("{}", diagnostic.to_json()),
+    }
+}
+
+/// Renders function names and frame sizes as two aligned columns.
+fn frame_size_table(frame_sizes: &[(String, i32)]) -> String {
+    let width = frame_sizes.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max("function".len());
+    let mut out = format!("{:<width$}  bytes\n", "function", width = width);
+    for (name, size) in frame_sizes {
+        out.push_str(&format!("{:<width$}  {:>5}\n", name, size, width = width));
+    }
+    out
+}
+
+/// Lists each function's variables with the frame slot code generation gave them.
+fn frame_locals_listing(frame_locals: &[(String, Vec<FrameLocal>)]) -> String {
+    let mut out = String::new();
+    for (function, locals) in frame_locals {
+        out.push_str(&format!("frame {}\n", function));
+        for local in locals {
+            out.push_str(&format!(
+                "    [rbp-{}] {}: {}{}\n",
+                local.offset,
+                local.name,
+                local.var_type,
+                if local.indirect { " (by address)" } else { "" }
+            ));
+        }
+    }
+    out
+}
+
+/// Renders the symbol layout as aligned columns, in output order.
+fn symbol_map_table(entries: &[MapEntry]) -> String {
+    let mut out = format!("{:<8}  {:>8}  {:>8}  symbol\n", "section", "offset", "size");
+    for entry in entries {
+        out.push_str(&format!("{:<8}  {:>8}  {:>8}  {}\n", entry.section, entry.offset, entry.size, entry.symbol));
+    }
+    out
+}
+
+/// A Makefile rule making `target` depend on `sources`, with spaces and `$`
+/// escaped the way Make reads them.
+fn dependency_rule(target: &str, sources: &[&str]) -> String {
+    let escape = |path: &str| path.replace(' ', "\\ ").replace('$', "$$");
+    let sources: Vec<String> = sources.iter().map(|source| escape(source)).collect();
+    format!("{}: {}\n", escape(target), sources.join(" "))
+}
+
+/// Phase timings as a JSON object, with each duration in seconds.
+fn time_report_json(phases: &[(&str, Duration)]) -> String {
+    let total: Duration = phases.iter().map(|(_, time)| *time).sum();
+    let phases: Vec<String> = phases.iter()
+        .map(|(name, time)| format!("{{\"phase\":\"{}\",\"seconds\":{:.6}}}", name, time.as_secs_f64()))
+        .collect();
+    format!("{{\"phases\":[{}],\"total_seconds\":{:.6}}}\n", phases.join(","), total.as_secs_f64())
+}
+
+fn compile(source_file: &str, options: &Options) -> Result<(), CompilerError> {
+    let output_file = options.output_file.as_str();
+    options.status(&format!("Compiling {}...", source_file));
     
     let source = fs::read_to_string(source_file)
         .map_err(|e| CompilerError::IoError(e.to_string()))?;
     
-    println!("  [1/5] Lexical analysis...");
-    let mut lexer = Lexer::new(&source);
+    let mut phases = Vec::new();
+    let mut start = Instant::now();
+    
+    options.status("  [1/5] Lexical analysis...");
+    let mut lexer = Lexer::new(&source).with_defines(&options.defines);
     let tokens = lexer.tokenize()?;
     
-    println!("  [2/5] Parsing...");
+    phases.push(("lexing", start.elapsed()));
+    options.phase_time(&phases);
+    start = Instant::now();
+    
+    options.status("  [2/5] Parsing...");
     let mut parser = Parser::new(tokens);
-    let ast = parser.parse()?;
+    let mut ast = parser.parse()?;
+    let parser_warnings = parser.take_warnings();
+    phases.push(("parsing", start.elapsed()));
+    options.phase_time(&phases);
     
-    println!("  [3/5] Semantic analysis...");
-    let mut semantic = SemanticAnalyzer::new();
+    // Written before the prelude is added, so it holds only the source file's items.
+    if options.ast_json {
+        let json_file = format!("{}.ast.json", output_file);
+        fs::write(&json_file, ast_json::to_json(&ast))
+            .map_err(|e| CompilerError::IoError(e.to_string()))?;
+        options.status(&format!("       Syntax tree written to {}", json_file));
+    }
+    
+    // `import` doesn't read other files yet, so the source file is the only dependency.
+    if options.emit_deps {
+        let deps_file = format!("{}.d", output_file);
+        let target = match (options.assembly_only, options.asm_syntax) {
+            (false, _) => output_file.to_string(),
+            (true, AsmSyntax::Nasm) => format!("{}.asm", output_file),
+            (true, AsmSyntax::Gas) => format!("{}.s", output_file),
+        };
+        fs::write(&deps_file, dependency_rule(&target, &[source_file]))
+            .map_err(|e| CompilerError::IoError(e.to_string()))?;
+        options.status(&format!("       Dependencies written to {}", deps_file));
+    }
+    
+    let mut warnings = 0;
+    for warning in parser_warnings {
+        report(&warning, source_file, Some(&source), options);
+        warnings += 1;
+    }
+    if options.std {
+        for warning in prelude::include(&mut ast)? {
+            report(&warning, source_file, Some(&source), options);
+            warnings += 1;
+        }
+    }
+    
+    options.status("  [3/5] Semantic analysis...");
+    start = Instant::now();
+    const_eval::evaluate(&mut ast)?;
+    let mut semantic = SemanticAnalyzer::new().with_max_array_size(options.max_array_size);
     semantic.analyze(&ast)?;
-    println!("       Semantic analysis completed successfully");
+    phases.push(("semantic", start.elapsed()));
+    options.phase_time(&phases);
+    for warning in semantic.take_warnings() {
+        report(&warning, source_file, Some(&source), options);
+        warnings += 1;
+    }
+    if options.warnings_as_errors && warnings > 0 {
+        return Err(CompilerError::SemanticError(format!(
+            "{} warning{} treated as errors (--warnings-as-errors)",
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        )));
+    }
+    options.status("       Semantic analysis completed successfully");
+    
+    if options.dump_symbols {
+        print!("{}", semantic.dump_symbols());
+    }
     
-    println!("  [4/5] Code generation...");
-    let mut codegen = CodeGenerator::new(opt_level);
+    options.status("  [4/5] Code generation...");
+    start = Instant::now();
+    let mut codegen = CodeGenerator::new(options.opt_level)
+        .with_source_file(source_file)
+        .with_stack_protector(options.stack_protector)
+        .with_target(options.target);
     let ir = codegen.generate(&ast)?;
-    println!("       Generated {} lines of IR", ir.lines().count());
+    phases.push(("codegen", start.elapsed()));
+    options.phase_time(&phases);
+    options.status(&format!("       Generated {} lines of IR", ir.lines().count()));
     
-    if emit_ir {
+    if options.emit_ir {
         let ir_file = format!("{}.ir", output_file);
         fs::write(&ir_file, &ir)
             .map_err(|e| CompilerError::IoError(e.to_string()))?;
-        println!("       IR written to {}", ir_file);
+        options.status(&format!("       IR written to {}", ir_file));
     }
     
-    println!("  [5/5] Assembling and linking...");
+    options.status("  [5/5] Assembling and linking...");
+    start = Instant::now();
     let asm = codegen.to_assembly(&ast)?;
-    println!("       Generated {} lines of assembly", asm.lines().count());
+    options.status(&format!("       Generated {} lines of assembly", asm.lines().count()));
+    
+    if options.dump_symbols {
+        print!("{}", frame_locals_listing(codegen.frame_locals()));
+    }
     
-    let asm_file = format!("{}.asm", output_file);
+    if options.print_frame_sizes {
+        print!("{}", frame_size_table(codegen.frame_sizes()));
+    }
+    
+    if let Some(map_file) = &options.map_file {
+        fs::write(map_file, symbol_map_table(codegen.symbol_map()))
+            .map_err(|e| CompilerError::IoError(e.to_string()))?;
+        options.status(&format!("       Symbol map written to {}", map_file));
+    }
+    
+    if options.emit_cfg {
+        let dot_file = format!("{}.dot", output_file);
+        fs::write(&dot_file, cfg::to_dot(&asm))
+            .map_err(|e| CompilerError::IoError(e.to_string()))?;
+        options.status(&format!("       Control-flow graph written to {}", dot_file));
+    }
+    
+    let (asm_file, asm) = match options.asm_syntax {
+        AsmSyntax::Nasm => (format!("{}.asm", output_file), asm),
+        AsmSyntax::Gas => (format!("{}.s", output_file), gas::to_gas(&asm)),
+    };
     fs::write(&asm_file, &asm)
         .map_err(|e| CompilerError::IoError(e.to_string()))?;
     
-    assemble_and_link(&asm_file, output_file)?;
+    let linked = if options.assembly_only {
+        options.status(&format!("       Assembly written to {}", asm_file));
+        Ok(())
+    } else {
+        assemble_and_link(&asm_file, output_file, options.asm_syntax)
+    };
+    phases.push(("assembly", start.elapsed()));
+    options.phase_time(&phases);
+    
+    // Written even when linking fails, so the compiler's own phases are still measured.
+    if options.time_report {
+        let report_file = format!("{}.time.json", output_file);
+        fs::write(&report_file, time_report_json(&phases))
+            .map_err(|e| CompilerError::IoError(e.to_string()))?;
+        options.status(&format!("       Time report written to {}", report_file));
+    }
+    linked?;
     
     // Keep .asm file for debugging. Because we need it. :P
     // fs::remove_file(&asm_file).ok();
@@ -118,11 +613,41 @@ fn compile(source_file: &str, output_file: &str, opt_level: u8, emit_ir: bool) -
     Ok(())
 }
 
-fn assemble_and_link(asm_file: &str, output_file: &str) -> Result<(), CompilerError> {
+/// Runs the freshly built program with inherited stdio and returns its exit code.
+fn run_executable(output_file: &str) -> Result<i32, CompilerError> {
+    // An absolute path keeps the lookup from wandering onto PATH.
+    let path = env::current_dir()
+        .map_err(|e| CompilerError::IoError(e.to_string()))?
+        .join(output_file);
+    
+    let status = process::Command::new(&path)
+        .status()
+        .map_err(|e| CompilerError::IoError(format!("Failed to run {}: {}", path.display(), e)))?;
+    
+    Ok(status.code().unwrap_or(1))
+}
+
+fn assemble_and_link(asm_file: &str, output_file: &str, syntax: AsmSyntax) -> Result<(), CompilerError> {
     #[cfg(target_os = "windows")]
     {
         use std::process::Command;
         
+        // GCC assembles and links in one step, against the MinGW runtime.
+        if syntax == AsmSyntax::Gas {
+            let gcc_output = Command::new("gcc")
+                .args(&["-o", output_file, asm_file])
+                .output();
+            return match gcc_output {
+                Ok(output) if output.status.success() => Ok(()),
+                Ok(output) => Err(CompilerError::AssemblyError(
+                    String::from_utf8_lossy(&output.stderr).to_string()
+                )),
+                Err(_) => Err(CompilerError::AssemblyError(
+                    "GCC not found. Please install a MinGW-w64 toolchain.".to_string()
+                )),
+            };
+        }
+        
         let obj_file = format!("{}.obj", output_file);
         
         let nasm_output = Command::new("nasm")
@@ -179,6 +704,7 @@ fn assemble_and_link(asm_file: &str, output_file: &str) -> Result<(), CompilerEr
     
     #[cfg(not(target_os = "windows"))]
     {
+        let _ = (asm_file, output_file, syntax);
         return Err(CompilerError::LinkError(
             "Non-Windows platforms not yet supported".to_string()
         ));
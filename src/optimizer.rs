@@ -0,0 +1,394 @@
+use crate::parser::{AstNode, Literal};
+
+/// Evaluates an expression built only from integer and bool literals.
+/// Returns `None` for anything else, or when evaluation would overflow or
+/// divide by zero.
+pub fn fold_constant(node: &AstNode) -> Option<Literal> {
+    match node {
+        AstNode::Literal(lit @ (Literal::Int(_) | Literal::Bool(_))) => Some(lit.clone()),
+        AstNode::UnaryOp { op, operand } => match (op.as_str(), fold_constant(operand)?) {
+            ("-", Literal::Int(n)) => n.checked_neg().map(Literal::Int),
+            ("!", Literal::Bool(b)) => Some(Literal::Bool(!b)),
+            _ => None,
+        },
+        AstNode::BinaryOp { left, op, right, .. } => {
+            match (fold_constant(left)?, fold_constant(right)?) {
+                (Literal::Int(a), Literal::Int(b)) => fold_int(a, op, b),
+                (Literal::Bool(a), Literal::Bool(b)) => match op.as_str() {
+                    "&&" => Some(Literal::Bool(a && b)),
+                    "||" => Some(Literal::Bool(a || b)),
+                    "==" => Some(Literal::Bool(a == b)),
+                    "!=" => Some(Literal::Bool(a != b)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Folds a condition to `true` or `false` if it is constant. A constant
+/// integer is true unless it is zero.
+pub fn constant_condition(node: &AstNode) -> Option<bool> {
+    match fold_constant(node)? {
+        Literal::Bool(b) => Some(b),
+        Literal::Int(n) => Some(n != 0),
+        _ => None,
+    }
+}
+
+/// The statements that replace a `while` whose condition is constant:
+/// `while (true)` becomes a `loop` with the same label and body, and
+/// `while (false)` becomes nothing. Returns `None` for any other condition.
+pub fn constant_while(condition: &AstNode, body: &[AstNode], label: &Option<String>) -> Option<Vec<AstNode>> {
+    if constant_condition(condition)? {
+        Some(vec![AstNode::Loop { body: body.to_vec(), label: label.clone() }])
+    } else {
+        Some(Vec::new())
+    }
+}
+
+/// Most iterations a `for` loop may have to be unrolled.
+const MAX_UNROLL: i64 = 8;
+
+/// The statements that replace a `for` loop over a small constant range: one
+/// block per iteration, each a copy of the body with the iterator replaced by
+/// its value. Returns `None` when the range isn't constant, has more than
+/// `MAX_UNROLL` iterations, or the body could break out, continue, declare a
+/// function or change or shadow the iterator.
+pub fn unroll_for(iterator: &str, range_start: &AstNode, range_end: &AstNode, inclusive: bool, body: &[AstNode]) -> Option<Vec<AstNode>> {
+    let (Literal::Int(start), Literal::Int(end)) = (fold_constant(range_start)?, fold_constant(range_end)?) else {
+        return None;
+    };
+    let end = if inclusive { end.checked_add(1)? } else { end };
+    if end.saturating_sub(start) > MAX_UNROLL {
+        return None;
+    }
+    
+    let blocks = |n: &AstNode| match n {
+        AstNode::Break { .. } | AstNode::Continue { .. } | AstNode::Function { .. } => true,
+        AstNode::Assignment { target: name, .. }
+        | AstNode::VariableDecl { name, .. }
+        | AstNode::ConstDecl { name, .. }
+        | AstNode::For { iterator: name, .. } => name == iterator,
+        AstNode::TupleDestructure { names, .. } => names.iter().any(|(name, _)| name == iterator),
+        _ => false,
+    };
+    if body.iter().any(|stmt| any_node(stmt, &blocks)) {
+        return None;
+    }
+    
+    Some((start..end).map(|value| {
+        let mut body = body.to_vec();
+        for stmt in &mut body {
+            replace_identifier(stmt, iterator, value);
+        }
+        AstNode::Block { body }
+    }).collect())
+}
+
+/// Replaces every read of `name` inside `node` with the literal `value`.
+fn replace_identifier(node: &mut AstNode, name: &str, value: i64) {
+    if let AstNode::Identifier { name: n, .. } = node {
+        if n == name {
+            *node = AstNode::Literal(Literal::Int(value));
+        }
+        return;
+    }
+    let all = |nodes: &mut [AstNode]| nodes.iter_mut().for_each(|n| replace_identifier(n, name, value));
+    match node {
+        AstNode::VariableDecl { value: inner, .. } | AstNode::Return { value: inner } | AstNode::Break { value: inner, .. } => {
+            if let Some(inner) = inner {
+                replace_identifier(inner, name, value);
+            }
+        }
+        AstNode::TupleDestructure { value: inner, .. }
+        | AstNode::ConstDecl { value: inner, .. }
+        | AstNode::Assignment { value: inner, .. }
+        | AstNode::BlockValue { value: inner }
+        | AstNode::ArrayRepeat { value: inner, .. }
+        | AstNode::UnaryOp { operand: inner, .. }
+        | AstNode::FieldAccess { object: inner, .. }
+        | AstNode::Defer { statement: inner } => replace_identifier(inner, name, value),
+        AstNode::MethodCall { receiver, args, .. } => {
+            replace_identifier(receiver, name, value);
+            all(args);
+        }
+        AstNode::BinaryOp { left, right, .. }
+        | AstNode::ArrayIndex { array: left, index: right }
+        | AstNode::PlaceAssignment { target: left, value: right, .. } => {
+            replace_identifier(left, name, value);
+            replace_identifier(right, name, value);
+        }
+        AstNode::FunctionCall { args: nodes, .. }
+        | AstNode::ArrayLiteral { elements: nodes }
+        | AstNode::TupleLiteral { elements: nodes }
+        | AstNode::Loop { body: nodes, .. }
+        | AstNode::Block { body: nodes } => all(nodes),
+        AstNode::StructLiteral { fields, .. } => fields.iter_mut().for_each(|(_, v)| replace_identifier(v, name, value)),
+        AstNode::If { condition, then_branch, else_branch } => {
+            replace_identifier(condition, name, value);
+            all(then_branch);
+            if let Some(else_branch) = else_branch {
+                all(else_branch);
+            }
+        }
+        AstNode::While { condition, body, .. } => {
+            replace_identifier(condition, name, value);
+            all(body);
+        }
+        AstNode::For { range_start, range_end, body, .. } => {
+            replace_identifier(range_start, name, value);
+            replace_identifier(range_end, name, value);
+            all(body);
+        }
+        _ => {}
+    }
+}
+
+/// If the statement at `index` stores to a variable that a later statement in
+/// `body` overwrites before anything can read it, returns the variable's name.
+/// A statement that mentions the variable in any way, or that can leave the
+/// block, ends the search. Statics are skipped, since a call may read them.
+pub fn dead_store(body: &[AstNode], index: usize, is_static: impl Fn(&str) -> bool) -> Option<&str> {
+    let name = match &body[index] {
+        AstNode::VariableDecl { name, value: Some(_), .. } => name,
+        AstNode::Assignment { target, .. } if !is_static(target) => target,
+        _ => return None,
+    };
+    
+    for stmt in &body[index + 1..] {
+        if let AstNode::Assignment { target, value, .. } = stmt
+            && target == name && !mentions(value, name) {
+            return Some(name);
+        }
+        let exits = |n: &AstNode| matches!(n, AstNode::Return { .. } | AstNode::Break { .. } | AstNode::Continue { .. });
+        if mentions(stmt, name) || any_node(stmt, &exits) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Splits the repeated subexpressions out of the value of `stmt`, a `let`,
+/// `const`, assignment or `return`, so each is computed once. Returns the
+/// expressions to compute first, each with the name of the temporary that
+/// holds it, and `stmt` with every occurrence replaced by its temporary. The
+/// largest repeated expression goes first. Returns `None` when nothing repeats
+/// or the value calls a function or holds statements, since those could
+/// write the operands between occurrences.
+pub fn common_subexpressions(stmt: &AstNode, next_temporary: &mut usize) -> Option<(Vec<(String, AstNode)>, AstNode)> {
+    let mut stmt = stmt.clone();
+    let value = match &mut stmt {
+        AstNode::VariableDecl { value: Some(value), .. } | AstNode::Return { value: Some(value) } => value,
+        AstNode::ConstDecl { value, .. } | AstNode::Assignment { value, .. } => value,
+        _ => return None,
+    };
+    let has_statements = |n: &AstNode| matches!(
+        n,
+        AstNode::Loop { .. } | AstNode::Block { .. } | AstNode::BlockValue { .. } | AstNode::If { .. }
+    );
+    if has_call(value) || any_node(value, &has_statements) {
+        return None;
+    }
+    
+    let mut temporaries = Vec::new();
+    while let Some((key, expr)) = largest_repeated(value) {
+        // A name no program can declare, unique within the compilation.
+        let name = format!("cse.{}", next_temporary);
+        *next_temporary += 1;
+        replace_expression(value, &key, &name);
+        temporaries.push((name, expr));
+    }
+    if temporaries.is_empty() {
+        None
+    } else {
+        Some((temporaries, stmt))
+    }
+}
+
+/// Identifies an expression built from operators, variables and literals,
+/// ignoring source positions. `None` for anything else.
+fn expression_key(node: &AstNode) -> Option<String> {
+    match node {
+        AstNode::Literal(Literal::Int(n)) => Some(n.to_string()),
+        AstNode::Literal(Literal::Float(f)) => Some(format!("{:?}", f)),
+        AstNode::Literal(Literal::Bool(b)) => Some(b.to_string()),
+        AstNode::Identifier { name, .. } => Some(name.clone()),
+        AstNode::UnaryOp { op, operand } => Some(format!("({} {})", op, expression_key(operand)?)),
+        AstNode::BinaryOp { left, op, right, .. } => {
+            Some(format!("({} {} {})", op, expression_key(left)?, expression_key(right)?))
+        }
+        _ => None,
+    }
+}
+
+/// The largest operator expression that occurs more than once in `node`, with
+/// its key.
+fn largest_repeated(node: &AstNode) -> Option<(String, AstNode)> {
+    let mut seen: Vec<(String, &AstNode, usize)> = Vec::new();
+    for_each_expression(node, &mut |n| {
+        if !matches!(n, AstNode::BinaryOp { .. } | AstNode::UnaryOp { .. }) {
+            return;
+        }
+        if let Some(key) = expression_key(n) {
+            match seen.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, _, count)) => *count += 1,
+                None => seen.push((key, n, 1)),
+            }
+        }
+    });
+    seen.into_iter()
+        .filter(|(_, _, count)| *count > 1)
+        .max_by_key(|(key, _, _)| key.len())
+        .map(|(key, n, _)| (key, n.clone()))
+}
+
+/// Calls `f` on `node` and each expression inside it.
+fn for_each_expression<'a>(node: &'a AstNode, f: &mut impl FnMut(&'a AstNode)) {
+    f(node);
+    match node {
+        AstNode::BinaryOp { left, right, .. } | AstNode::ArrayIndex { array: left, index: right } => {
+            for_each_expression(left, f);
+            for_each_expression(right, f);
+        }
+        AstNode::UnaryOp { operand: inner, .. }
+        | AstNode::ArrayRepeat { value: inner, .. }
+        | AstNode::FieldAccess { object: inner, .. } => for_each_expression(inner, f),
+        AstNode::ArrayLiteral { elements } | AstNode::TupleLiteral { elements } => {
+            elements.iter().for_each(|e| for_each_expression(e, f));
+        }
+        AstNode::StructLiteral { fields, .. } => fields.iter().for_each(|(_, v)| for_each_expression(v, f)),
+        _ => {}
+    }
+}
+
+/// Replaces each expression in `node` whose key is `key` with a read of `name`.
+fn replace_expression(node: &mut AstNode, key: &str, name: &str) {
+    if expression_key(node).as_deref() == Some(key) {
+        *node = AstNode::Identifier { name: name.to_string(), line: 0, column: 0 };
+        return;
+    }
+    match node {
+        AstNode::BinaryOp { left, right, .. } | AstNode::ArrayIndex { array: left, index: right } => {
+            replace_expression(left, key, name);
+            replace_expression(right, key, name);
+        }
+        AstNode::UnaryOp { operand: inner, .. }
+        | AstNode::ArrayRepeat { value: inner, .. }
+        | AstNode::FieldAccess { object: inner, .. } => replace_expression(inner, key, name),
+        AstNode::ArrayLiteral { elements } | AstNode::TupleLiteral { elements } => {
+            elements.iter_mut().for_each(|e| replace_expression(e, key, name));
+        }
+        AstNode::StructLiteral { fields, .. } => fields.iter_mut().for_each(|(_, v)| replace_expression(v, key, name)),
+        _ => {}
+    }
+}
+
+/// Matches `if (c) { x = a; } else { x = b; }`, returning `x`, `a` and `b`
+/// when both values can be computed before the condition is known: each
+/// is made of variables, literals and operators that cannot fault, so no
+/// calls, indexing or division.
+pub fn conditional_assignment<'a>(then_branch: &'a [AstNode], else_branch: Option<&'a [AstNode]>) -> Option<(&'a str, &'a AstNode, &'a AstNode)> {
+    let (
+        [AstNode::Assignment { target, value: then_value, .. }],
+        Some([AstNode::Assignment { target: else_target, value: else_value, .. }]),
+    ) = (then_branch, else_branch) else {
+        return None;
+    };
+    if target != else_target || !speculatable(then_value) || !speculatable(else_value) {
+        return None;
+    }
+    Some((target, then_value, else_value))
+}
+
+fn speculatable(node: &AstNode) -> bool {
+    match node {
+        AstNode::Literal(_) | AstNode::Identifier { .. } => true,
+        AstNode::UnaryOp { operand, .. } => speculatable(operand),
+        AstNode::BinaryOp { left, op, right, .. } => {
+            !matches!(op.as_str(), "/" | "%") && speculatable(left) && speculatable(right)
+        }
+        _ => false,
+    }
+}
+
+/// Whether evaluating `node` calls a function, and so may have side effects.
+pub fn has_call(node: &AstNode) -> bool {
+    any_node(node, &|n| matches!(n, AstNode::FunctionCall { .. } | AstNode::MethodCall { .. }))
+}
+
+/// Whether `node` reads, assigns or declares `name` anywhere inside it.
+fn mentions(node: &AstNode, name: &str) -> bool {
+    any_node(node, &|n| match n {
+        AstNode::Identifier { name: n, .. }
+        | AstNode::Assignment { target: n, .. }
+        | AstNode::VariableDecl { name: n, .. }
+        | AstNode::ConstDecl { name: n, .. }
+        | AstNode::For { iterator: n, .. } => n == name,
+        AstNode::TupleDestructure { names, .. } => names.iter().any(|(n, _)| n == name),
+        _ => false,
+    })
+}
+
+/// Whether `pred` holds for `node` or any node inside it. Nested function
+/// declarations are not entered.
+fn any_node(node: &AstNode, pred: &dyn Fn(&AstNode) -> bool) -> bool {
+    if pred(node) {
+        return true;
+    }
+    let any = |nodes: &[AstNode]| nodes.iter().any(|n| any_node(n, pred));
+    match node {
+        AstNode::VariableDecl { value, .. } | AstNode::Return { value } | AstNode::Break { value, .. } => {
+            value.as_deref().is_some_and(|v| any_node(v, pred))
+        }
+        AstNode::TupleDestructure { value, .. }
+        | AstNode::ConstDecl { value, .. }
+        | AstNode::Assignment { value, .. }
+        | AstNode::BlockValue { value }
+        | AstNode::ArrayRepeat { value, .. } => any_node(value, pred),
+        AstNode::UnaryOp { operand, .. } => any_node(operand, pred),
+        AstNode::FieldAccess { object, .. } => any_node(object, pred),
+        AstNode::MethodCall { receiver, args, .. } => any_node(receiver, pred) || any(args),
+        AstNode::Defer { statement } => any_node(statement, pred),
+        AstNode::BinaryOp { left, right, .. } => any_node(left, pred) || any_node(right, pred),
+        AstNode::PlaceAssignment { target, value, .. } => any_node(target, pred) || any_node(value, pred),
+        AstNode::ArrayIndex { array, index } => any_node(array, pred) || any_node(index, pred),
+        AstNode::FunctionCall { args: nodes, .. }
+        | AstNode::ArrayLiteral { elements: nodes }
+        | AstNode::TupleLiteral { elements: nodes }
+        | AstNode::Loop { body: nodes, .. }
+        | AstNode::Block { body: nodes } => any(nodes),
+        AstNode::StructLiteral { fields, .. } => fields.iter().any(|(_, v)| any_node(v, pred)),
+        AstNode::If { condition, then_branch, else_branch } => {
+            any_node(condition, pred) || any(then_branch) || else_branch.as_deref().is_some_and(any)
+        }
+        AstNode::While { condition, body, .. } => any_node(condition, pred) || any(body),
+        AstNode::For { range_start, range_end, body, .. } => {
+            any_node(range_start, pred) || any_node(range_end, pred) || any(body)
+        }
+        _ => false,
+    }
+}
+
+fn fold_int(a: i64, op: &str, b: i64) -> Option<Literal> {
+    let value = match op {
+        "+" => a.checked_add(b)?,
+        "-" => a.checked_sub(b)?,
+        "*" => a.checked_mul(b)?,
+        "/" => a.checked_div(b)?,
+        "%" => a.checked_rem(b)?,
+        "&" => a & b,
+        "|" => a | b,
+        "^" => a ^ b,
+        "==" => return Some(Literal::Bool(a == b)),
+        "!=" => return Some(Literal::Bool(a != b)),
+        "<" => return Some(Literal::Bool(a < b)),
+        "<=" => return Some(Literal::Bool(a <= b)),
+        ">" => return Some(Literal::Bool(a > b)),
+        ">=" => return Some(Literal::Bool(a >= b)),
+        _ => return None,
+    };
+    Some(Literal::Int(value))
+}
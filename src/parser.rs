@@ -1,5 +1,7 @@
-use crate::error::CompilerError;
+use crate::error::{CompilerError, Diagnostic};
 use crate::lexer::{Token, TokenType};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub enum AstNode {
@@ -7,11 +9,34 @@ pub enum AstNode {
         name: String,
         items: Vec<AstNode>,
     },
+    /// `type Name = Target;`
+    TypeAlias {
+        name: String,
+        target: Type,
+    },
+    /// `struct Name { field: Type, ... }`
+    StructDecl {
+        name: String,
+        fields: Vec<(String, Type)>,
+    },
+    /// `union Name { field: Type, ... }`
+    UnionDecl {
+        name: String,
+        fields: Vec<(String, Type)>,
+    },
     Function {
         name: String,
         params: Vec<(String, Type)>,
         return_type: Option<Type>,
         body: Vec<AstNode>,
+        /// Attributes written before `fn`, in source order.
+        attributes: Vec<Attribute>,
+        /// Declared `const fn`, so calls with constant arguments are evaluated
+        /// at compile time.
+        constant: bool,
+        /// Position of the function name.
+        line: usize,
+        column: usize,
     },
     VariableDecl {
         name: String,
@@ -19,11 +44,23 @@ pub enum AstNode {
         value: Option<Box<AstNode>>,
         mutable: bool,
     },
+    /// `let (a, mut b) = value;`, each name paired with its mutability.
+    TupleDestructure {
+        names: Vec<(String, bool)>,
+        value: Box<AstNode>,
+    },
     ConstDecl {
         name: String,
         const_type: Type,
         value: Box<AstNode>,
     },
+    /// `static [mut] name: T [= value];` at module level.
+    Static {
+        name: String,
+        static_type: Type,
+        value: Option<Box<AstNode>>,
+        mutable: bool,
+    },
     Return {
         value: Option<Box<AstNode>>,
     },
@@ -31,16 +68,26 @@ pub enum AstNode {
         left: Box<AstNode>,
         op: String,
         right: Box<AstNode>,
+        /// Position of the operator.
+        line: usize,
+        column: usize,
     },
     UnaryOp {
         op: String,
         operand: Box<AstNode>,
     },
     Literal(Literal),
-    Identifier(String),
+    Identifier {
+        name: String,
+        line: usize,
+        column: usize,
+    },
     FunctionCall {
         name: String,
         args: Vec<AstNode>,
+        /// Position of the function name.
+        line: usize,
+        column: usize,
     },
     If {
         condition: Box<AstNode>,
@@ -50,6 +97,7 @@ pub enum AstNode {
     While {
         condition: Box<AstNode>,
         body: Vec<AstNode>,
+        label: Option<String>,
     },
     For {
         iterator: String,
@@ -57,27 +105,120 @@ pub enum AstNode {
         range_end: Box<AstNode>,
         inclusive: bool,
         body: Vec<AstNode>,
+        label: Option<String>,
     },
     Loop {
         body: Vec<AstNode>,
+        label: Option<String>,
+    },
+    /// A bare `{ ... }` statement with its own scope.
+    Block {
+        body: Vec<AstNode>,
+    },
+    /// An expression ending a block without a `;`, giving the block its value.
+    BlockValue {
+        value: Box<AstNode>,
+    },
+    /// `defer stmt;`, run when the enclosing block exits.
+    Defer {
+        statement: Box<AstNode>,
+    },
+    /// `break`, or `break value` to leave a `loop` used as a value.
+    Break {
+        label: Option<String>,
+        value: Option<Box<AstNode>>,
+    },
+    Continue {
+        label: Option<String>,
     },
-    Break,
-    Continue,
     Assignment {
         target: String,
         value: Box<AstNode>,
+        /// Position of the target.
+        line: usize,
+        column: usize,
+    },
+    /// Assignment to an array element or field, such as `a[i] = v` or
+    /// `p.x *= 2`. `op` is the operator of a compound assignment.
+    PlaceAssignment {
+        target: Box<AstNode>,
+        op: Option<String>,
+        value: Box<AstNode>,
+        line: usize,
+        column: usize,
     },
     ArrayLiteral {
         elements: Vec<AstNode>,
     },
     ArrayRepeat {
         value: Box<AstNode>,
-        count: usize,
+        count: i64,
     },
     ArrayIndex {
         array: Box<AstNode>,
         index: Box<AstNode>,
     },
+    TupleLiteral {
+        elements: Vec<AstNode>,
+    },
+    /// `Name { field: value, ... }`, with the fields in source order.
+    StructLiteral {
+        name: String,
+        fields: Vec<(String, AstNode)>,
+        /// Position of the struct name.
+        line: usize,
+        column: usize,
+    },
+    /// `object.field`
+    FieldAccess {
+        object: Box<AstNode>,
+        field: String,
+        /// Position of the field name.
+        line: usize,
+        column: usize,
+    },
+    /// `receiver.method(args)`, calling a method from an `impl` block.
+    MethodCall {
+        receiver: Box<AstNode>,
+        method: String,
+        args: Vec<AstNode>,
+        /// Position of the method name.
+        line: usize,
+        column: usize,
+    },
+}
+
+/// Whether an `if`, or the last `if` of its `else if` chain, has no `else`.
+fn lacks_else(node: &AstNode) -> bool {
+    match node {
+        AstNode::If { else_branch: None, .. } => true,
+        AstNode::If { else_branch: Some(branch), .. } => {
+            matches!(branch.as_slice(), [nested @ AstNode::If { .. }] if lacks_else(nested))
+        }
+        _ => false,
+    }
+}
+
+/// Turns the value a function body ends in into a `return`: a trailing
+/// expression, or the value each arm of a trailing `if`/`else` ends in.
+fn return_trailing_value(body: &mut [AstNode]) {
+    match body.last_mut() {
+        Some(last @ AstNode::BlockValue { .. }) => {
+            if let AstNode::BlockValue { value } = std::mem::replace(last, AstNode::Return { value: None }) {
+                *last = AstNode::Return { value: Some(value) };
+            }
+        }
+        Some(AstNode::If { then_branch, else_branch: Some(else_branch), .. }) => {
+            return_trailing_value(then_branch);
+            return_trailing_value(else_branch);
+        }
+        _ => {}
+    }
+}
+
+/// Name of the function a method of `type_name` is compiled to.
+pub fn method_symbol(type_name: &str, method: &str) -> String {
+    format!("{}__{}", type_name, method)
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -97,35 +238,232 @@ pub enum Type {
     Void,
     Str,
     Array(Box<Type>, usize),
+    /// An array whose length names a constant, replaced with `Array` once
+    /// constants are evaluated.
+    ConstLengthArray(Box<Type>, String),
+    Tuple(Vec<Type>),
+    /// A type alias or struct name, resolved during semantic analysis.
+    Named(String),
+    /// A struct with its fields in declaration order.
+    Struct {
+        name: String,
+        fields: Vec<(String, Type)>,
+    },
+    /// A union, whose fields all share its storage.
+    Union {
+        name: String,
+        fields: Vec<(String, Type)>,
+    },
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::I8 => write!(f, "i8"),
+            Type::I16 => write!(f, "i16"),
+            Type::I32 => write!(f, "i32"),
+            Type::I64 => write!(f, "i64"),
+            Type::U8 => write!(f, "u8"),
+            Type::U16 => write!(f, "u16"),
+            Type::U32 => write!(f, "u32"),
+            Type::U64 => write!(f, "u64"),
+            Type::F32 => write!(f, "f32"),
+            Type::F64 => write!(f, "f64"),
+            Type::Bool => write!(f, "bool"),
+            Type::Char => write!(f, "char"),
+            Type::Void => write!(f, "void"),
+            Type::Str => write!(f, "str"),
+            Type::Array(elem_type, size) => write!(f, "[{}; {}]", elem_type, size),
+            Type::ConstLengthArray(elem_type, name) => write!(f, "[{}; {}]", elem_type, name),
+            Type::Tuple(types) => {
+                let types: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+                write!(f, "({})", types.join(", "))
+            }
+            Type::Named(name) | Type::Struct { name, .. } | Type::Union { name, .. } => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Type {
+    /// Replaces alias, struct and union names with the types they stand for.
+    /// Fails on unknown names, on aliases that refer back to themselves and on
+    /// structs or unions that contain themselves. Structs and unions are
+    /// registered in `aliases` as well.
+    pub fn resolve(&self, aliases: &HashMap<String, Type>) -> Result<Type, String> {
+        self.resolve_within(aliases, 0)
+    }
+    
+    fn resolve_within(&self, aliases: &HashMap<String, Type>, depth: usize) -> Result<Type, String> {
+        match self {
+            Type::Named(name) => {
+                let target = aliases.get(name).ok_or_else(|| format!("Unknown type '{}'", name))?;
+                // A chain longer than the number of aliases must revisit one.
+                if depth >= aliases.len() {
+                    return Err(match target {
+                        Type::Struct { .. } => format!("Struct '{}' contains itself", name),
+                        Type::Union { .. } => format!("Union '{}' contains itself", name),
+                        _ => format!("Type alias '{}' refers to itself", name),
+                    });
+                }
+                target.resolve_within(aliases, depth + 1)
+            }
+            Type::Array(elem_type, size) => {
+                Ok(Type::Array(Box::new(elem_type.resolve_within(aliases, depth)?), *size))
+            }
+            Type::ConstLengthArray(_, name) => Err(format!("'{}' is not a constant with a known value", name)),
+            Type::Tuple(types) => {
+                let types = types.iter()
+                    .map(|t| t.resolve_within(aliases, depth))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Type::Tuple(types))
+            }
+            Type::Struct { name, fields } => {
+                let fields = fields.iter()
+                    .map(|(field, t)| Ok((field.clone(), t.resolve_within(aliases, depth)?)))
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(Type::Struct { name: name.clone(), fields })
+            }
+            Type::Union { name, fields } => {
+                let fields = fields.iter()
+                    .map(|(field, t)| Ok((field.clone(), t.resolve_within(aliases, depth)?)))
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(Type::Union { name: name.clone(), fields })
+            }
+            other => Ok(other.clone()),
+        }
+    }
+    
+    /// Inclusive value range of an integer type.
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        match self {
+            Type::I8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            Type::I16 => Some((i16::MIN as i128, i16::MAX as i128)),
+            Type::I32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            Type::I64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            Type::U8 => Some((0, u8::MAX as i128)),
+            Type::U16 => Some((0, u16::MAX as i128)),
+            Type::U32 => Some((0, u32::MAX as i128)),
+            Type::U64 => Some((0, u64::MAX as i128)),
+            _ => None,
+        }
+    }
+    
+    /// The one of two integer types that holds every value of both, so `u8`
+    /// and `u16` promote to `u16` and `u8` and `i16` to `i16`. `None` when
+    /// neither does, as for `i32` and `u32`, or for non-integers.
+    pub fn promote(&self, other: &Type) -> Option<Type> {
+        let (min, max) = self.int_range()?;
+        let (other_min, other_max) = other.int_range()?;
+        if other_min <= min && max <= other_max {
+            Some(other.clone())
+        } else if min <= other_min && other_max <= max {
+            Some(self.clone())
+        } else {
+            None
+        }
+    }
+    
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Type::I8 | Type::I16 | Type::I32 | Type::I64)
+    }
+    
+    /// Width in bits of an integer type.
+    pub fn bit_width(&self) -> Option<u32> {
+        match self {
+            Type::I8 | Type::U8 => Some(8),
+            Type::I16 | Type::U16 => Some(16),
+            Type::I32 | Type::U32 => Some(32),
+            Type::I64 | Type::U64 => Some(64),
+            _ => None,
+        }
+    }
+    
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::U8 | Type::U16 | Type::U32 | Type::U64
+        )
+    }
+    
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::F32 | Type::F64)
+    }
+    
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+}
+
+/// `@name` or `@name("argument")` before a function.
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub name: String,
+    pub argument: Option<String>,
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Debug, Clone)]
 pub enum Literal {
     Int(i64),
+    /// An integer above `i64::MAX`, which only a `u64` holds.
+    UInt(u64),
     Float(f64),
     String(String),
     Bool(bool),
     Char(char),
 }
 
+impl Literal {
+    /// Type of the literal when nothing constrains it. Integers default to
+    /// `i32`, widening to `i64` when the value doesn't fit and to `u64` above
+    /// `i64::MAX`.
+    pub fn default_type(&self) -> Type {
+        match self {
+            Literal::Int(n) if i32::try_from(*n).is_ok() => Type::I32,
+            Literal::Int(_) => Type::I64,
+            Literal::UInt(_) => Type::U64,
+            Literal::Float(_) => Type::F64,
+            Literal::String(_) => Type::Str,
+            Literal::Bool(_) => Type::Bool,
+            Literal::Char(_) => Type::Char,
+        }
+    }
+    
+    /// The value of an integer literal.
+    pub fn int_value(&self) -> Option<i128> {
+        match self {
+            Literal::Int(n) => Some(*n as i128),
+            Literal::UInt(n) => Some(*n as i128),
+            _ => None,
+        }
+    }
+}
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// Operator positions of binary operations written inside parentheses.
+    grouped: HashSet<(usize, usize)>,
+    warnings: Vec<Diagnostic>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser { tokens, current: 0, grouped: HashSet::new(), warnings: Vec::new() }
+    }
+    
+    pub fn take_warnings(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.warnings)
     }
     
     pub fn parse(&mut self) -> Result<AstNode, CompilerError> {
+        self.check_delimiters()?;
         let mut items = Vec::new();
         let module_name = self.parse_module_declaration()?;
         
         while !self.is_at_end() {
-            if let Some(item) = self.parse_top_level()? {
-                items.push(item);
-            }
+            items.extend(self.parse_top_level()?);
         }
         
         Ok(AstNode::Module {
@@ -134,6 +472,46 @@ impl Parser {
         })
     }
     
+    /// Matches every bracket before parsing, so an unbalanced one is reported
+    /// against its opener rather than wherever the parser gives up.
+    fn check_delimiters(&self) -> Result<(), CompilerError> {
+        let mut open: Vec<&Token> = Vec::new();
+        for token in &self.tokens {
+            let opener = match token.token_type {
+                TokenType::LeftParen | TokenType::LeftBrace | TokenType::LeftBracket => {
+                    open.push(token);
+                    continue;
+                }
+                TokenType::RightParen => TokenType::LeftParen,
+                TokenType::RightBrace => TokenType::LeftBrace,
+                TokenType::RightBracket => TokenType::LeftBracket,
+                _ => continue,
+            };
+            match open.pop() {
+                Some(last) if last.token_type == opener => {}
+                Some(last) => {
+                    return Err(CompilerError::ParseError(format!(
+                        "Unexpected {}: {} opened at line {} is not closed",
+                        token.token_type, last.token_type, last.line
+                    )).at(token.line, token.column));
+                }
+                None => {
+                    return Err(CompilerError::ParseError(format!(
+                        "Unexpected {} with no matching {}",
+                        token.token_type, opener
+                    )).at(token.line, token.column));
+                }
+            }
+        }
+        if let Some(last) = open.pop() {
+            return Err(CompilerError::ParseError(format!(
+                "Unclosed {} opened at line {}",
+                last.token_type, last.line
+            )).at(last.line, last.column));
+        }
+        Ok(())
+    }
+    
     fn parse_module_declaration(&mut self) -> Result<String, CompilerError> {
         if self.match_token(&TokenType::Module) {
             if let TokenType::Identifier(name) = &self.current_token().token_type {
@@ -142,26 +520,63 @@ impl Parser {
                 self.expect_token(&TokenType::Semicolon)?;
                 Ok(name)
             } else {
-                Err(CompilerError::ParseError("Expected module name".to_string()))
+                Err(self.error("Expected module name".to_string()))
             }
         } else {
             Ok("main".to_string())
         }
     }
     
-    fn parse_top_level(&mut self) -> Result<Option<AstNode>, CompilerError> {
+    fn parse_top_level(&mut self) -> Result<Vec<AstNode>, CompilerError> {
         if self.match_token(&TokenType::Import) {
             self.parse_import()?;
-            return Ok(None);
+            return Ok(Vec::new());
+        }
+        
+        if self.check(&TokenType::At) {
+            let attributes = self.parse_attributes()?;
+            let constant = self.match_const_fn();
+            if !constant && !self.match_token(&TokenType::Fn) {
+                return Err(self.error("Expected a function after attributes".to_string()));
+            }
+            return Ok(vec![self.parse_function(attributes, None, constant)?]);
+        }
+        
+        if self.match_const_fn() {
+            return Ok(vec![self.parse_function(Vec::new(), None, true)?]);
         }
         
         if self.match_token(&TokenType::Fn) {
-            return Ok(Some(self.parse_function()?));
+            return Ok(vec![self.parse_function(Vec::new(), None, false)?]);
         }
         
-        Err(CompilerError::ParseError(format!(
-            "Unexpected token at top level: {:?}",
-            self.current_token()
+        if self.match_token(&TokenType::Type) {
+            return Ok(vec![self.parse_type_alias()?]);
+        }
+        
+        if self.match_token(&TokenType::Static) {
+            return Ok(vec![self.parse_static()?]);
+        }
+        
+        if self.match_token(&TokenType::Struct) {
+            return Ok(vec![self.parse_struct()?]);
+        }
+        
+        if self.match_token(&TokenType::Union) {
+            return Ok(vec![self.parse_union()?]);
+        }
+        
+        if self.match_token(&TokenType::Impl) {
+            return self.parse_impl();
+        }
+        
+        if self.check(&TokenType::Module) {
+            return Err(self.error("The module declaration must come before any other item".to_string()));
+        }
+        
+        Err(self.error(format!(
+            "Unexpected token at top level: {}",
+            self.current_token().token_type
         )))
     }
     
@@ -172,23 +587,101 @@ impl Parser {
         Ok(())
     }
     
-    fn parse_function(&mut self) -> Result<AstNode, CompilerError> {
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>, CompilerError> {
+        let mut attributes = Vec::new();
+        while self.check(&TokenType::At) {
+            let (line, column) = (self.current_token().line, self.current_token().column);
+            self.advance();
+            let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
+                n.clone()
+            } else {
+                return Err(self.error("Expected attribute name after '@'".to_string()));
+            };
+            self.advance();
+            
+            let argument = if self.match_token(&TokenType::LeftParen) {
+                let argument = if let TokenType::StringLiteral(s) = &self.current_token().token_type {
+                    s.clone()
+                } else {
+                    return Err(self.error("Expected a string argument".to_string()));
+                };
+                self.advance();
+                self.expect_token(&TokenType::RightParen)?;
+                Some(argument)
+            } else {
+                None
+            };
+            
+            attributes.push(Attribute { name, argument, line, column });
+        }
+        Ok(attributes)
+    }
+    
+    /// `impl Name { fn method(self, ...) { ... } ... }`. Each method becomes a
+    /// function named by `method_symbol` whose first parameter is the receiver.
+    fn parse_impl(&mut self) -> Result<Vec<AstNode>, CompilerError> {
+        let type_name = if let TokenType::Identifier(n) = &self.current_token().token_type {
+            n.clone()
+        } else {
+            return Err(self.error("Expected type name after 'impl'".to_string()));
+        };
+        self.advance();
+        
+        self.expect_token(&TokenType::LeftBrace)?;
+        let mut methods = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let attributes = self.parse_attributes()?;
+            self.expect_token(&TokenType::Fn)?;
+            methods.push(self.parse_function(attributes, Some(&type_name), false)?);
+        }
+        self.expect_token(&TokenType::RightBrace)?;
+        Ok(methods)
+    }
+    
+    /// Consumes `const fn`, but not a `const` that starts a constant declaration.
+    fn match_const_fn(&mut self) -> bool {
+        if self.check(&TokenType::Const) && matches!(self.peek_type(1), Some(TokenType::Fn)) {
+            self.advance();
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+    
+    /// Parses a function after `fn`. Inside `impl Name`, `receiver` is `Name`
+    /// and the function must take `self` first.
+    fn parse_function(&mut self, attributes: Vec<Attribute>, receiver: Option<&str>, constant: bool) -> Result<AstNode, CompilerError> {
+        let (line, column) = (self.current_token().line, self.current_token().column);
         let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
             n.clone()
         } else {
-            return Err(CompilerError::ParseError("Expected function name".to_string()));
+            return Err(self.error("Expected function name".to_string()));
         };
         self.advance();
         
         self.expect_token(&TokenType::LeftParen)?;
         
         let mut params = Vec::new();
+        if let Some(type_name) = receiver {
+            if self.current_token().token_type != TokenType::Identifier("self".to_string()) {
+                return Err(self.error(format!("Method '{}' must take 'self' as its first parameter", name)));
+            }
+            self.advance();
+            params.push(("self".to_string(), Type::Named(type_name.to_string())));
+            if !self.check(&TokenType::RightParen) {
+                self.expect_token(&TokenType::Comma)?;
+            }
+        }
         while !self.check(&TokenType::RightParen) {
             let param_name = if let TokenType::Identifier(n) = &self.current_token().token_type {
                 n.clone()
             } else {
-                return Err(CompilerError::ParseError("Expected parameter name".to_string()));
+                return Err(self.error("Expected parameter name".to_string()));
             };
+            if param_name == "self" {
+                return Err(self.error("Only methods in an 'impl' block can take 'self'".to_string()));
+            }
             self.advance();
             
             self.expect_token(&TokenType::Colon)?;
@@ -209,20 +702,104 @@ impl Parser {
             None
         };
         
-        self.expect_token(&TokenType::LeftBrace)?;
-        
-        let body = self.parse_block()?;
+        // `= expr;` is shorthand for a body holding just that expression.
+        let mut body = if self.match_token(&TokenType::Equal) {
+            let value = self.parse_expression()?;
+            self.expect_token(&TokenType::Semicolon)?;
+            vec![AstNode::BlockValue { value: Box::new(value) }]
+        } else {
+            self.expect_token(&TokenType::LeftBrace)?;
+            let body = self.parse_block()?;
+            self.expect_token(&TokenType::RightBrace)?;
+            body
+        };
         
-        self.expect_token(&TokenType::RightBrace)?;
+        // A trailing expression is the result of a function that returns a value.
+        if return_type.as_ref().is_some_and(|t| *t != Type::Void) {
+            return_trailing_value(&mut body);
+        }
         
+        let name = match receiver {
+            Some(type_name) => method_symbol(type_name, &name),
+            None => name,
+        };
         Ok(AstNode::Function {
             name,
             params,
             return_type,
             body,
+            attributes,
+            constant,
+            line,
+            column,
         })
     }
     
+    fn parse_type_alias(&mut self) -> Result<AstNode, CompilerError> {
+        let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
+            n.clone()
+        } else {
+            return Err(self.error("Expected type alias name".to_string()));
+        };
+        self.advance();
+        
+        self.expect_token(&TokenType::Equal)?;
+        let target = self.parse_type()?;
+        self.expect_token(&TokenType::Semicolon)?;
+        
+        Ok(AstNode::TypeAlias { name, target })
+    }
+    
+    fn parse_struct(&mut self) -> Result<AstNode, CompilerError> {
+        let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
+            n.clone()
+        } else {
+            return Err(self.error("Expected struct name".to_string()));
+        };
+        self.advance();
+        
+        let fields = self.parse_fields()?;
+        Ok(AstNode::StructDecl { name, fields })
+    }
+    
+    fn parse_union(&mut self) -> Result<AstNode, CompilerError> {
+        let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
+            n.clone()
+        } else {
+            return Err(self.error("Expected union name".to_string()));
+        };
+        self.advance();
+        
+        let fields = self.parse_fields()?;
+        if fields.is_empty() {
+            return Err(self.error(format!("Union '{}' must have at least one field", name)));
+        }
+        Ok(AstNode::UnionDecl { name, fields })
+    }
+    
+    /// The `{ field: Type, ... }` of a struct or union.
+    fn parse_fields(&mut self) -> Result<Vec<(String, Type)>, CompilerError> {
+        self.expect_token(&TokenType::LeftBrace)?;
+        let mut fields = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let field = if let TokenType::Identifier(n) = &self.current_token().token_type {
+                n.clone()
+            } else {
+                return Err(self.error("Expected field name".to_string()));
+            };
+            self.advance();
+            
+            self.expect_token(&TokenType::Colon)?;
+            fields.push((field, self.parse_type()?));
+            
+            if !self.match_token(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_token(&TokenType::RightBrace)?;
+        Ok(fields)
+    }
+    
     fn parse_block(&mut self) -> Result<Vec<AstNode>, CompilerError> {
         let mut statements = Vec::new();
         
@@ -234,10 +811,20 @@ impl Parser {
     }
     
     fn parse_statement(&mut self) -> Result<AstNode, CompilerError> {
+        if self.match_token(&TokenType::LeftBrace) {
+            let body = self.parse_block()?;
+            self.expect_token(&TokenType::RightBrace)?;
+            return Ok(AstNode::Block { body });
+        }
+        
         if self.match_token(&TokenType::Let) {
             return self.parse_variable_decl();
         }
         
+        if self.match_token(&TokenType::Fn) {
+            return self.parse_function(Vec::new(), None, false);
+        }
+        
         if self.match_token(&TokenType::Const) {
             return self.parse_const_decl();
         }
@@ -250,60 +837,142 @@ impl Parser {
             return self.parse_if();
         }
         
+        if let TokenType::Label(label) = &self.current_token().token_type {
+            let label = label.clone();
+            self.advance();
+            self.expect_token(&TokenType::Colon)?;
+            
+            if self.match_token(&TokenType::While) {
+                return self.parse_while(Some(label));
+            }
+            if self.match_token(&TokenType::For) {
+                return self.parse_for(Some(label));
+            }
+            if self.match_token(&TokenType::Loop) {
+                return self.parse_loop(Some(label));
+            }
+            return Err(self.error("Expected loop after label".to_string()));
+        }
+        
         if self.match_token(&TokenType::While) {
-            return self.parse_while();
+            return self.parse_while(None);
         }
         
         if self.match_token(&TokenType::For) {
-            return self.parse_for();
+            return self.parse_for(None);
         }
         
         if self.match_token(&TokenType::Loop) {
-            return self.parse_loop();
+            return self.parse_loop(None);
+        }
+        
+        if self.match_token(&TokenType::Defer) {
+            let statement = self.parse_statement()?;
+            return Ok(AstNode::Defer { statement: Box::new(statement) });
         }
         
         if self.match_token(&TokenType::Break) {
+            let label = self.parse_optional_label();
+            let value = if self.check(&TokenType::Semicolon) {
+                None
+            } else {
+                Some(Box::new(self.parse_expression()?))
+            };
             self.expect_token(&TokenType::Semicolon)?;
-            return Ok(AstNode::Break);
+            return Ok(AstNode::Break { label, value });
         }
         
         if self.match_token(&TokenType::Continue) {
+            let label = self.parse_optional_label();
             self.expect_token(&TokenType::Semicolon)?;
-            return Ok(AstNode::Continue);
+            return Ok(AstNode::Continue { label });
         }
         
+        let (line, column) = (self.current_token().line, self.current_token().column);
         let expr = self.parse_expression()?;
         
-        if self.match_token(&TokenType::Equal) {
-            if let AstNode::Identifier(name) = expr {
-                let value = self.parse_expression()?;
-                self.expect_token(&TokenType::Semicolon)?;
-                return Ok(AstNode::Assignment {
-                    target: name,
+        let op = self.compound_operator();
+        if op.is_some() || self.check(&TokenType::Equal) {
+            self.advance();
+            let value = self.parse_expression()?;
+            self.expect_token(&TokenType::Semicolon)?;
+            return match expr {
+                // `x op= v` reads `x` again, which has no side effects.
+                AstNode::Identifier { name, .. } => {
+                    let value = match op {
+                        Some(op) => AstNode::BinaryOp {
+                            left: Box::new(AstNode::Identifier { name: name.clone(), line, column }),
+                            op,
+                            right: Box::new(value),
+                            line,
+                            column,
+                        },
+                        None => value,
+                    };
+                    Ok(AstNode::Assignment { target: name, value: Box::new(value), line, column })
+                }
+                AstNode::ArrayIndex { .. } | AstNode::FieldAccess { .. } => Ok(AstNode::PlaceAssignment {
+                    target: Box::new(expr),
+                    op,
                     value: Box::new(value),
-                });
-            }
+                    line,
+                    column,
+                }),
+                _ => Err(CompilerError::ParseError(
+                    "Invalid assignment target".to_string()
+                ).at(line, column)),
+            };
+        }
+        
+        if self.check(&TokenType::RightBrace) {
+            return Ok(AstNode::BlockValue { value: Box::new(expr) });
         }
         
         self.expect_token(&TokenType::Semicolon)?;
         Ok(expr)
     }
     
+    /// The binary operator of the compound assignment token at the cursor, such as `+` for `+=`.
+    fn compound_operator(&self) -> Option<String> {
+        let op = match self.current_token().token_type {
+            TokenType::PlusEqual => "+",
+            TokenType::MinusEqual => "-",
+            TokenType::StarEqual => "*",
+            TokenType::SlashEqual => "/",
+            TokenType::PercentEqual => "%",
+            TokenType::AmpEqual => "&",
+            TokenType::PipeEqual => "|",
+            TokenType::CaretEqual => "^",
+            TokenType::LessLessEqual => "<<",
+            TokenType::GreaterGreaterEqual => ">>",
+            _ => return None,
+        };
+        Some(op.to_string())
+    }
+    
     fn parse_variable_decl(&mut self) -> Result<AstNode, CompilerError> {
+        if self.match_token(&TokenType::LeftParen) {
+            return self.parse_tuple_destructure();
+        }
+        
         let mutable = self.match_token(&TokenType::Mut);
         
         let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
             n.clone()
         } else {
-            return Err(CompilerError::ParseError("Expected variable name".to_string()));
+            return Err(self.error("Expected variable name".to_string()));
         };
         self.advance();
         
-        let var_type = if self.match_token(&TokenType::Colon) {
-            Some(self.parse_type()?)
-        } else {
-            None
-        };
+        let (line, column) = (self.current_token().line, self.current_token().column);
+        let mut inferred_element = None;
+        let mut var_type = None;
+        if self.match_token(&TokenType::Colon) {
+            inferred_element = self.parse_inferred_length_array()?;
+            if inferred_element.is_none() {
+                var_type = Some(self.parse_type()?);
+            }
+        }
         
         let value = if self.match_token(&TokenType::Equal) {
             Some(Box::new(self.parse_expression()?))
@@ -311,6 +980,20 @@ impl Parser {
             None
         };
         
+        // `[T; _]` takes its length from the array literal it is initialized with.
+        if let Some(element_type) = inferred_element {
+            let length = match value.as_deref() {
+                Some(AstNode::ArrayLiteral { elements }) => elements.len(),
+                Some(AstNode::ArrayRepeat { count, .. }) => *count as usize,
+                _ => {
+                    return Err(CompilerError::ParseError(
+                        "An array length of '_' can only be inferred from an array literal".to_string()
+                    ).at(line, column));
+                }
+            };
+            var_type = Some(Type::Array(Box::new(element_type), length));
+        }
+        
         self.expect_token(&TokenType::Semicolon)?;
         
         Ok(AstNode::VariableDecl {
@@ -321,6 +1004,62 @@ impl Parser {
         })
     }
     
+    /// Parses `[T; _]` and returns `T`. Leaves any other type unparsed.
+    fn parse_inferred_length_array(&mut self) -> Result<Option<Type>, CompilerError> {
+        let start = self.current;
+        if !self.match_token(&TokenType::LeftBracket) {
+            return Ok(None);
+        }
+        let element_type = self.parse_type()?;
+        self.expect_token(&TokenType::Semicolon)?;
+        if self.current_token().token_type == TokenType::Identifier("_".to_string()) {
+            self.advance();
+            self.expect_token(&TokenType::RightBracket)?;
+            return Ok(Some(element_type));
+        }
+        self.current = start;
+        Ok(None)
+    }
+    
+    /// Parses call arguments after the opening `(`, through the closing `)`.
+    fn parse_arguments(&mut self) -> Result<Vec<AstNode>, CompilerError> {
+        let mut args = Vec::new();
+        
+        while !self.check(&TokenType::RightParen) {
+            args.push(self.parse_expression()?);
+            if !self.match_token(&TokenType::Comma) {
+                break;
+            }
+        }
+        
+        self.expect_token(&TokenType::RightParen)?;
+        Ok(args)
+    }
+    
+    fn parse_tuple_destructure(&mut self) -> Result<AstNode, CompilerError> {
+        let mut names = Vec::new();
+        while !self.check(&TokenType::RightParen) {
+            let mutable = self.match_token(&TokenType::Mut);
+            if let TokenType::Identifier(n) = &self.current_token().token_type {
+                names.push((n.clone(), mutable));
+            } else {
+                return Err(self.error("Expected variable name".to_string()));
+            }
+            self.advance();
+            
+            if !self.match_token(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_token(&TokenType::RightParen)?;
+        
+        self.expect_token(&TokenType::Equal)?;
+        let value = Box::new(self.parse_expression()?);
+        self.expect_token(&TokenType::Semicolon)?;
+        
+        Ok(AstNode::TupleDestructure { names, value })
+    }
+    
     fn parse_return(&mut self) -> Result<AstNode, CompilerError> {
         let value = if !self.check(&TokenType::Semicolon) {
             Some(Box::new(self.parse_expression()?))
@@ -343,6 +1082,15 @@ impl Parser {
         self.expect_token(&TokenType::RightBrace)?;
         
         let else_branch = if self.match_token(&TokenType::Else) {
+            // `else if` nests the rest of the chain as the whole else branch,
+            // so each arm is a block of its own with its own labels.
+            if self.match_token(&TokenType::If) {
+                return Ok(AstNode::If {
+                    condition,
+                    then_branch,
+                    else_branch: Some(vec![self.parse_if()?]),
+                });
+            }
             self.expect_token(&TokenType::LeftBrace)?;
             let else_body = self.parse_block()?;
             self.expect_token(&TokenType::RightBrace)?;
@@ -358,7 +1106,17 @@ impl Parser {
         })
     }
     
-    fn parse_while(&mut self) -> Result<AstNode, CompilerError> {
+    fn parse_optional_label(&mut self) -> Option<String> {
+        if let TokenType::Label(label) = &self.current_token().token_type {
+            let label = label.clone();
+            self.advance();
+            Some(label)
+        } else {
+            None
+        }
+    }
+    
+    fn parse_while(&mut self, label: Option<String>) -> Result<AstNode, CompilerError> {
         self.expect_token(&TokenType::LeftParen)?;
         let condition = Box::new(self.parse_expression()?);
         self.expect_token(&TokenType::RightParen)?;
@@ -367,7 +1125,7 @@ impl Parser {
         let body = self.parse_block()?;
         self.expect_token(&TokenType::RightBrace)?;
         
-        Ok(AstNode::While { condition, body })
+        Ok(AstNode::While { condition, body, label })
     }
     
     fn parse_expression(&mut self) -> Result<AstNode, CompilerError> {
@@ -378,11 +1136,14 @@ impl Parser {
         let mut left = self.parse_logical_and()?;
         
         while self.match_token(&TokenType::PipePipe) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
             let right = self.parse_logical_and()?;
             left = AstNode::BinaryOp {
                 left: Box::new(left),
                 op: "||".to_string(),
                 right: Box::new(right),
+                line,
+                column,
             };
         }
         
@@ -390,24 +1151,87 @@ impl Parser {
     }
     
     fn parse_logical_and(&mut self) -> Result<AstNode, CompilerError> {
-        let mut left = self.parse_equality()?;
+        let mut left = self.parse_bitwise_or()?;
         
         while self.match_token(&TokenType::AmpAmp) {
-            let right = self.parse_equality()?;
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
+            let right = self.parse_bitwise_or()?;
             left = AstNode::BinaryOp {
                 left: Box::new(left),
                 op: "&&".to_string(),
                 right: Box::new(right),
+                line,
+                column,
             };
         }
         
         Ok(left)
     }
     
+    fn parse_bitwise_or(&mut self) -> Result<AstNode, CompilerError> {
+        let mut left = self.parse_bitwise_xor()?;
+        
+        while self.match_token(&TokenType::Pipe) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
+            let right = self.parse_bitwise_xor()?;
+            left = self.bitwise_op(left, "|", right, line, column);
+        }
+        
+        Ok(left)
+    }
+    
+    fn parse_bitwise_xor(&mut self) -> Result<AstNode, CompilerError> {
+        let mut left = self.parse_bitwise_and()?;
+        
+        while self.match_token(&TokenType::Caret) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
+            let right = self.parse_bitwise_and()?;
+            left = self.bitwise_op(left, "^", right, line, column);
+        }
+        
+        Ok(left)
+    }
+    
+    fn parse_bitwise_and(&mut self) -> Result<AstNode, CompilerError> {
+        let mut left = self.parse_equality()?;
+        
+        while self.match_token(&TokenType::Amp) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
+            let right = self.parse_equality()?;
+            left = self.bitwise_op(left, "&", right, line, column);
+        }
+        
+        Ok(left)
+    }
+    
+    /// Builds a bitwise operation, warning when an operand is an unparenthesized
+    /// comparison: as in C, `a & b == c` groups as `a & (b == c)`.
+    fn bitwise_op(&mut self, left: AstNode, op: &str, right: AstNode, line: usize, column: usize) -> AstNode {
+        for operand in [&left, &right] {
+            if let AstNode::BinaryOp { op: inner, line: inner_line, column: inner_column, .. } = operand {
+                let comparison = matches!(inner.as_str(), "==" | "!=" | "<" | "<=" | ">" | ">=");
+                if comparison && !self.grouped.contains(&(*inner_line, *inner_column)) {
+                    self.warnings.push(Diagnostic::warning(
+                        "parser",
+                        format!("'{}' binds more tightly than '{}'; add parentheses to make the grouping explicit", inner, op),
+                    ).at(line, column));
+                }
+            }
+        }
+        AstNode::BinaryOp {
+            left: Box::new(left),
+            op: op.to_string(),
+            right: Box::new(right),
+            line,
+            column,
+        }
+    }
+    
     fn parse_equality(&mut self) -> Result<AstNode, CompilerError> {
         let mut left = self.parse_comparison()?;
         
         while self.match_any(&[TokenType::EqualEqual, TokenType::NotEqual]) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
             let op = match &self.previous_token().token_type {
                 TokenType::EqualEqual => "==",
                 TokenType::NotEqual => "!=",
@@ -418,6 +1242,8 @@ impl Parser {
                 left: Box::new(left),
                 op: op.to_string(),
                 right: Box::new(right),
+                line,
+                column,
             };
         }
         
@@ -425,9 +1251,10 @@ impl Parser {
     }
     
     fn parse_comparison(&mut self) -> Result<AstNode, CompilerError> {
-        let mut left = self.parse_term()?;
+        let mut left = self.parse_shift()?;
         
         while self.match_any(&[TokenType::Less, TokenType::LessEqual, TokenType::Greater, TokenType::GreaterEqual]) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
             let op = match &self.previous_token().token_type {
                 TokenType::Less => "<",
                 TokenType::LessEqual => "<=",
@@ -435,11 +1262,36 @@ impl Parser {
                 TokenType::GreaterEqual => ">=",
                 _ => unreachable!(),
             };
+            let right = self.parse_shift()?;
+            left = AstNode::BinaryOp {
+                left: Box::new(left),
+                op: op.to_string(),
+                right: Box::new(right),
+                line,
+                column,
+            };
+        }
+        
+        Ok(left)
+    }
+    
+    fn parse_shift(&mut self) -> Result<AstNode, CompilerError> {
+        let mut left = self.parse_term()?;
+        
+        while self.match_any(&[TokenType::LessLess, TokenType::GreaterGreater]) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
+            let op = match &self.previous_token().token_type {
+                TokenType::LessLess => "<<",
+                TokenType::GreaterGreater => ">>",
+                _ => unreachable!(),
+            };
             let right = self.parse_term()?;
             left = AstNode::BinaryOp {
                 left: Box::new(left),
                 op: op.to_string(),
                 right: Box::new(right),
+                line,
+                column,
             };
         }
         
@@ -450,6 +1302,7 @@ impl Parser {
         let mut left = self.parse_factor()?;
         
         while self.match_any(&[TokenType::Plus, TokenType::Minus]) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
             let op = match &self.previous_token().token_type {
                 TokenType::Plus => "+",
                 TokenType::Minus => "-",
@@ -460,6 +1313,8 @@ impl Parser {
                 left: Box::new(left),
                 op: op.to_string(),
                 right: Box::new(right),
+                line,
+                column,
             };
         }
         
@@ -470,6 +1325,7 @@ impl Parser {
         let mut left = self.parse_unary()?;
         
         while self.match_any(&[TokenType::Star, TokenType::Slash, TokenType::Percent]) {
+            let (line, column) = (self.previous_token().line, self.previous_token().column);
             let op = match &self.previous_token().token_type {
                 TokenType::Star => "*",
                 TokenType::Slash => "/",
@@ -481,6 +1337,8 @@ impl Parser {
                 left: Box::new(left),
                 op: op.to_string(),
                 right: Box::new(right),
+                line,
+                column,
             };
         }
         
@@ -495,6 +1353,24 @@ impl Parser {
                 _ => unreachable!(),
             };
             let operand = self.parse_unary()?;
+            
+            // Fold `-<literal>` so the negative value is range-checked as a whole
+            // (`-128` fits an i8 even though `128` alone does not).
+            if op == "-" {
+                match operand {
+                    AstNode::Literal(Literal::Int(n)) => {
+                        return Ok(AstNode::Literal(Literal::Int(-n)));
+                    }
+                    AstNode::Literal(Literal::Float(f)) => {
+                        return Ok(AstNode::Literal(Literal::Float(-f)));
+                    }
+                    AstNode::Literal(Literal::UInt(n)) => {
+                        return Err(self.error(format!("Integer literal -{} is out of range for every integer type", n)));
+                    }
+                    _ => {}
+                }
+            }
+            
             return Ok(AstNode::UnaryOp {
                 op: op.to_string(),
                 operand: Box::new(operand),
@@ -505,12 +1381,75 @@ impl Parser {
     }
     
     fn parse_primary(&mut self) -> Result<AstNode, CompilerError> {
+        let expr = self.parse_operand()?;
+        self.parse_postfix(expr)
+    }
+    
+    /// Applies any `[index]`, `.field` and `.method(args)` suffixes to `expr`,
+    /// left to right, so `a[i].m()[j]` indexes the result of the call.
+    fn parse_postfix(&mut self, mut expr: AstNode) -> Result<AstNode, CompilerError> {
+        loop {
+            if self.match_token(&TokenType::LeftBracket) {
+                let index = self.parse_expression()?;
+                self.expect_token(&TokenType::RightBracket)?;
+                expr = AstNode::ArrayIndex { array: Box::new(expr), index: Box::new(index) };
+            } else if self.check(&TokenType::Dot) && matches!(self.peek_type(1), Some(TokenType::Identifier(_))) {
+                self.advance();
+                let (line, column) = (self.current_token().line, self.current_token().column);
+                let TokenType::Identifier(field) = self.current_token().token_type.clone() else {
+                    unreachable!()
+                };
+                self.advance();
+                expr = if self.match_token(&TokenType::LeftParen) {
+                    let args = self.parse_arguments()?;
+                    AstNode::MethodCall { receiver: Box::new(expr), method: field, args, line, column }
+                } else {
+                    AstNode::FieldAccess { object: Box::new(expr), field, line, column }
+                };
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+    
+    /// A primary expression without its postfix suffixes.
+    fn parse_operand(&mut self) -> Result<AstNode, CompilerError> {
         match &self.current_token().token_type {
+            TokenType::Loop => {
+                self.advance();
+                self.parse_loop(None)
+            }
+            // Only a statement `if` may leave out `else`, since a value needs both branches.
+            TokenType::If => {
+                let (line, column) = (self.current_token().line, self.current_token().column);
+                self.advance();
+                let node = self.parse_if()?;
+                if lacks_else(&node) {
+                    return Err(CompilerError::ParseError(
+                        "An 'if' used as a value must have an 'else' branch".to_string()
+                    ).at(line, column));
+                }
+                Ok(node)
+            }
+            TokenType::Label(label) => {
+                let label = label.clone();
+                self.advance();
+                self.expect_token(&TokenType::Colon)?;
+                if !self.match_token(&TokenType::Loop) {
+                    return Err(self.error("Expected loop after label".to_string()));
+                }
+                self.parse_loop(Some(label))
+            }
             TokenType::IntLiteral(n) => {
                 let val = *n;
                 self.advance();
                 Ok(AstNode::Literal(Literal::Int(val)))
             }
+            TokenType::UIntLiteral(n) => {
+                let val = *n;
+                self.advance();
+                Ok(AstNode::Literal(Literal::UInt(val)))
+            }
             TokenType::FloatLiteral(f) => {
                 let val = *f;
                 self.advance();
@@ -533,39 +1472,41 @@ impl Parser {
             }
             TokenType::Identifier(name) => {
                 let name = name.clone();
+                let (line, column) = (self.current_token().line, self.current_token().column);
                 self.advance();
                 
-                if self.check(&TokenType::LeftBracket) {
-                    self.advance();
-                    let index = self.parse_expression()?;
-                    self.expect_token(&TokenType::RightBracket)?;
-                    return Ok(AstNode::ArrayIndex {
-                        array: Box::new(AstNode::Identifier(name)),
-                        index: Box::new(index),
-                    });
+                if self.is_struct_literal_start() {
+                    return self.parse_struct_literal(name, line, column);
                 }
                 
                 if self.match_token(&TokenType::LeftParen) {
-                    let mut args = Vec::new();
-                    
-                    while !self.check(&TokenType::RightParen) {
-                        args.push(self.parse_expression()?);
-                        if !self.match_token(&TokenType::Comma) {
-                            break;
-                        }
-                    }
-                    
-                    self.expect_token(&TokenType::RightParen)?;
-                    
-                    Ok(AstNode::FunctionCall { name, args })
+                    let args = self.parse_arguments()?;
+                    Ok(AstNode::FunctionCall { name, args, line, column })
                 } else {
-                    Ok(AstNode::Identifier(name))
+                    Ok(AstNode::Identifier { name, line, column })
                 }
             }
             TokenType::LeftParen => {
                 self.advance();
                 let expr = self.parse_expression()?;
+                
+                // A comma turns a parenthesized expression into a tuple.
+                if self.check(&TokenType::Comma) {
+                    let mut elements = vec![expr];
+                    while self.match_token(&TokenType::Comma) {
+                        if self.check(&TokenType::RightParen) {
+                            break;
+                        }
+                        elements.push(self.parse_expression()?);
+                    }
+                    self.expect_token(&TokenType::RightParen)?;
+                    return Ok(AstNode::TupleLiteral { elements });
+                }
+                
                 self.expect_token(&TokenType::RightParen)?;
+                if let AstNode::BinaryOp { line, column, .. } = &expr {
+                    self.grouped.insert((*line, *column));
+                }
                 Ok(expr)
             }
             TokenType::LeftBracket => {
@@ -580,15 +1521,15 @@ impl Parser {
                 let first_expr = self.parse_expression()?;
                 
                 if self.match_token(&TokenType::Semicolon) {
-                    if let TokenType::IntLiteral(count) = self.current_token().token_type {
-                        self.advance();
+                    // Counts are validated in semantic analysis, so a folded `-N` is kept as-is.
+                    if let AstNode::Literal(Literal::Int(count)) = self.parse_unary()? {
                         self.expect_token(&TokenType::RightBracket)?;
                         return Ok(AstNode::ArrayRepeat {
                             value: Box::new(first_expr),
-                            count: count as usize,
+                            count,
                         });
                     } else {
-                        return Err(CompilerError::ParseError("Expected array size".to_string()));
+                        return Err(self.error("Expected array size".to_string()));
                     }
                 }
                 
@@ -604,13 +1545,47 @@ impl Parser {
                 self.expect_token(&TokenType::RightBracket)?;
                 Ok(AstNode::ArrayLiteral { elements })
             }
-            _ => Err(CompilerError::ParseError(format!(
-                "Unexpected token: {:?}",
-                self.current_token()
+            _ => Err(self.error(format!(
+                "Unexpected token: {}",
+                self.current_token().token_type
             ))),
         }
     }
     
+    /// Whether the `{` after a name opens a struct literal: it must be empty
+    /// or start with `field:`. Conditions are parenthesized, so a name is never
+    /// directly followed by a block.
+    fn is_struct_literal_start(&self) -> bool {
+        self.check(&TokenType::LeftBrace) && match self.peek_type(1) {
+            Some(TokenType::RightBrace) => true,
+            Some(TokenType::Identifier(_)) => matches!(self.peek_type(2), Some(TokenType::Colon)),
+            _ => false,
+        }
+    }
+    
+    fn parse_struct_literal(&mut self, name: String, line: usize, column: usize) -> Result<AstNode, CompilerError> {
+        self.expect_token(&TokenType::LeftBrace)?;
+        let mut fields = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let field = if let TokenType::Identifier(n) = &self.current_token().token_type {
+                n.clone()
+            } else {
+                return Err(self.error("Expected field name".to_string()));
+            };
+            self.advance();
+            
+            self.expect_token(&TokenType::Colon)?;
+            fields.push((field, self.parse_expression()?));
+            
+            if !self.match_token(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_token(&TokenType::RightBrace)?;
+        
+        Ok(AstNode::StructLiteral { name, fields, line, column })
+    }
+    
     fn parse_type(&mut self) -> Result<Type, CompilerError> {
         let ty = match &self.current_token().token_type {
             TokenType::I8 => Type::I8,
@@ -627,6 +1602,19 @@ impl Parser {
             TokenType::Char => Type::Char,
             TokenType::Void => Type::Void,
             TokenType::Str => Type::Str,
+            TokenType::Identifier(name) => Type::Named(name.clone()),
+            TokenType::LeftParen => {
+                self.advance();
+                let mut types = Vec::new();
+                while !self.check(&TokenType::RightParen) {
+                    types.push(self.parse_type()?);
+                    if !self.match_token(&TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.expect_token(&TokenType::RightParen)?;
+                return Ok(Type::Tuple(types));
+            }
             TokenType::LeftBracket => {
                 self.advance();
                 let element_type = self.parse_type()?;
@@ -636,11 +1624,18 @@ impl Parser {
                     self.advance();
                     self.expect_token(&TokenType::RightBracket)?;
                     return Ok(Type::Array(Box::new(element_type), size as usize));
+                } else if let TokenType::Identifier(name) = self.current_token().token_type.clone() {
+                    if name == "_" {
+                        return Err(self.error("An array length of '_' can only be inferred from an array literal".to_string()));
+                    }
+                    self.advance();
+                    self.expect_token(&TokenType::RightBracket)?;
+                    return Ok(Type::ConstLengthArray(Box::new(element_type), name));
                 } else {
-                    return Err(CompilerError::ParseError("Expected array size".to_string()));
+                    return Err(self.error("Expected array size".to_string()));
                 }
             }
-            _ => return Err(CompilerError::ParseError("Expected type".to_string())),
+            _ => return Err(self.error("Expected type".to_string())),
         };
         self.advance();
         Ok(ty)
@@ -677,18 +1672,28 @@ impl Parser {
             self.advance();
             Ok(())
         } else {
-            Err(CompilerError::ParseError(format!(
-                "Expected {:?}, got {:?}",
+            Err(self.error(format!(
+                "Expected {}, got {}",
                 token_type,
-                self.current_token()
+                self.current_token().token_type
             )))
         }
     }
     
+    fn error(&self, message: String) -> CompilerError {
+        let token = self.current_token();
+        CompilerError::ParseError(message).at(token.line, token.column)
+    }
+    
     fn current_token(&self) -> &Token {
         &self.tokens[self.current]
     }
     
+    /// Type of the token `offset` places after the current one.
+    fn peek_type(&self, offset: usize) -> Option<&TokenType> {
+        self.tokens.get(self.current + offset).map(|t| &t.token_type)
+    }
+    
     fn previous_token(&self) -> &Token {
         &self.tokens[self.current - 1]
     }
@@ -707,7 +1712,7 @@ impl Parser {
         let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
             n.clone()
         } else {
-            return Err(CompilerError::ParseError("Expected constant name".to_string()));
+            return Err(self.error("Expected constant name".to_string()));
         };
         self.advance();
         
@@ -726,39 +1731,62 @@ impl Parser {
         })
     }
     
-    fn parse_for(&mut self) -> Result<AstNode, CompilerError> {
+    fn parse_static(&mut self) -> Result<AstNode, CompilerError> {
+        let mutable = self.match_token(&TokenType::Mut);
+        
+        let name = if let TokenType::Identifier(n) = &self.current_token().token_type {
+            n.clone()
+        } else {
+            return Err(self.error("Expected static name".to_string()));
+        };
+        self.advance();
+        
+        self.expect_token(&TokenType::Colon)?;
+        let static_type = self.parse_type()?;
+        
+        let value = if self.match_token(&TokenType::Equal) {
+            Some(Box::new(self.parse_expression()?))
+        } else {
+            None
+        };
+        
+        self.expect_token(&TokenType::Semicolon)?;
+        
+        Ok(AstNode::Static {
+            name,
+            static_type,
+            value,
+            mutable,
+        })
+    }
+    
+    fn parse_for(&mut self, label: Option<String>) -> Result<AstNode, CompilerError> {
         self.expect_token(&TokenType::LeftParen)?;
         
         let iterator = if let TokenType::Identifier(n) = &self.current_token().token_type {
             n.clone()
         } else {
-            return Err(CompilerError::ParseError("Expected iterator variable".to_string()));
+            return Err(self.error("Expected iterator variable".to_string()));
         };
         self.advance();
         
         if let TokenType::Identifier(kw) = &self.current_token().token_type {
             if kw != "in" {
-                return Err(CompilerError::ParseError("Expected 'in' keyword".to_string()));
+                return Err(self.error("Expected 'in' keyword".to_string()));
             }
         } else {
-            return Err(CompilerError::ParseError("Expected 'in' keyword".to_string()));
+            return Err(self.error("Expected 'in' keyword".to_string()));
         }
         self.advance();
         
         let range_start = Box::new(self.parse_expression()?);
         
-        let inclusive = if self.match_token(&TokenType::Dot) {
-            if self.match_token(&TokenType::Dot) {
-                if self.match_token(&TokenType::Dot) {
-                    true  // is ...
-                } else {
-                    false  // is ..
-                }
-            } else {
-                return Err(CompilerError::ParseError("Expected range operator".to_string()));
-            }
+        let inclusive = if self.match_token(&TokenType::DotDot) {
+            false
+        } else if self.match_token(&TokenType::DotDotDot) || self.match_token(&TokenType::DotDotEq) {
+            true
         } else {
-            return Err(CompilerError::ParseError("Expected range operator".to_string()));
+            return Err(self.error("Expected range operator".to_string()));
         };
         
         let range_end = Box::new(self.parse_expression()?);
@@ -776,14 +1804,15 @@ impl Parser {
             range_end,
             inclusive,
             body,
+            label,
         })
     }
     
-    fn parse_loop(&mut self) -> Result<AstNode, CompilerError> {
+    fn parse_loop(&mut self, label: Option<String>) -> Result<AstNode, CompilerError> {
         self.expect_token(&TokenType::LeftBrace)?;
         let body = self.parse_block()?;
         self.expect_token(&TokenType::RightBrace)?;
         
-        Ok(AstNode::Loop { body })
+        Ok(AstNode::Loop { body, label })
     }
 }
\ No newline at end of file
@@ -0,0 +1,38 @@
+use crate::error::{CompilerError, Diagnostic};
+use crate::lexer::Lexer;
+use crate::parser::{AstNode, Parser};
+
+const SOURCE: &str = include_str!("prelude.ss");
+
+/// Prepends the prelude's functions to `module`. A function the program
+/// defines itself replaces the prelude one, with a warning.
+pub fn include(module: &mut AstNode) -> Result<Vec<Diagnostic>, CompilerError> {
+    let AstNode::Module { items, .. } = module else {
+        return Ok(Vec::new());
+    };
+    
+    let tokens = Lexer::new(SOURCE).tokenize()?;
+    let AstNode::Module { items: prelude, .. } = Parser::new(tokens).parse()? else {
+        return Ok(Vec::new());
+    };
+    
+    let mut warnings = Vec::new();
+    let mut included = Vec::new();
+    for item in prelude {
+        let AstNode::Function { name, .. } = &item else {
+            continue;
+        };
+        let overridden = items.iter().any(|i| matches!(i, AstNode::Function { name: n, .. } if n == name));
+        if overridden {
+            warnings.push(Diagnostic::warning(
+                "semantic",
+                format!("Function '{}' replaces the prelude definition", name),
+            ));
+        } else {
+            included.push(item);
+        }
+    }
+    
+    items.splice(0..0, included);
+    Ok(warnings)
+}
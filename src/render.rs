@@ -0,0 +1,98 @@
+use crate::error::{Diagnostic, Severity};
+use std::env;
+use std::io::IsTerminal;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    /// `Auto` colors only when stderr is a terminal and `NO_COLOR` is unset.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stderr().is_terminal()
+            }
+        }
+    }
+}
+
+pub struct Renderer {
+    color: bool,
+}
+
+impl Renderer {
+    pub fn new(choice: ColorChoice) -> Self {
+        Renderer { color: choice.enabled() }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic, path: &str, source: Option<&str>) -> String {
+        let (label_color, label) = match diagnostic.severity {
+            Severity::Error => (RED, "error"),
+            Severity::Warning => (YELLOW, "warning"),
+        };
+
+        let mut out = format!(
+            "{}: {}\n",
+            self.paint(label_color, label),
+            self.paint(BOLD, &diagnostic.message)
+        );
+
+        let Some(line) = diagnostic.line else {
+            return out;
+        };
+        let column = diagnostic.column.unwrap_or(1);
+
+        let gutter = " ".repeat(line.to_string().len());
+        out.push_str(&format!("{}{} {}:{}:{}\n", gutter, self.paint(BLUE, "-->"), path, line, column));
+
+        if let Some(text) = source.and_then(|s| source_line(s, line)) {
+            let bar = self.paint(BLUE, "|");
+            let caret_pad: String = text
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+
+            out.push_str(&format!("{} {}\n", gutter, bar));
+            out.push_str(&format!("{} {} {}\n", self.paint(BLUE, &line.to_string()), bar, text));
+            out.push_str(&format!("{} {} {}{}\n", gutter, bar, caret_pad, self.paint(label_color, "^")));
+        }
+
+        out
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// The 1-based `line` of `source`, counting `\n`, `\r\n` and lone `\r` as breaks
+/// the same way the lexer does.
+fn source_line(source: &str, line: usize) -> Option<String> {
+    source.replace("\r\n", "\n").split(['\n', '\r']).nth(line - 1).map(str::to_string)
+}
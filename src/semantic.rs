@@ -1,31 +1,385 @@
-use crate::error::CompilerError;
-use crate::parser::{AstNode, Type, Literal};
-use std::collections::HashMap;
+use crate::error::{CompilerError, Diagnostic};
+use crate::optimizer;
+use crate::parser::{method_symbol, AstNode, Attribute, Type, Literal};
+use std::collections::{HashMap, HashSet};
+
+/// Largest array, in elements, accepted unless overridden with `with_max_array_size`.
+pub const DEFAULT_MAX_ARRAY_SIZE: usize = 1 << 20;
 
 #[derive(Debug, Clone)]
 struct SymbolInfo {
     symbol_type: Type,
     mutable: bool,
+    /// False for a `let` without a value until it is definitely assigned.
+    initialized: bool,
+}
+
+/// A declaration remembered for `--dump-symbols` after its scope is gone.
+#[derive(Debug, Clone)]
+struct SymbolRecord {
+    function: Option<String>,
+    depth: usize,
+    name: String,
+    info: SymbolInfo,
+}
+
+/// How often a function is called, and how many of those calls drop the result.
+#[derive(Debug, Default)]
+struct CallCounts {
+    calls: usize,
+    discarded: usize,
+}
+
+#[derive(Debug, Clone)]
+struct FunctionSignature {
+    params: Vec<Type>,
+    return_type: Option<Type>,
+    line: usize,
 }
 
+/// A loop enclosing the statement being visited.
+#[derive(Debug, Clone)]
+struct LoopScope {
+    label: Option<String>,
+    /// Only `loop` can give a value to `break`.
+    accepts_value: bool,
+    /// Type expected of the loop's value, which literals given to `break` adopt.
+    expected: Option<Type>,
+    /// Type of the first value given to `break`.
+    value_type: Option<Type>,
+    /// Whether some `break` leaves without a value.
+    plain_break: bool,
+}
+
+/// The variables an `if` arm definitely assigns, and the type of the value it ends in.
+type BranchResult = Result<(Vec<(usize, String)>, Option<Type>), CompilerError>;
+
 pub struct SemanticAnalyzer {
     symbol_table: Vec<HashMap<String, SymbolInfo>>,
+    functions: HashMap<String, FunctionSignature>,
+    type_aliases: HashMap<String, Type>,
+    function_order: Vec<String>,
+    call_counts: HashMap<String, CallCounts>,
+    symbol_log: Vec<SymbolRecord>,
+    local_functions: Vec<String>,
+    enclosing_scopes: Vec<Vec<HashMap<String, SymbolInfo>>>,
+    current_function: Option<String>,
     current_function_return: Option<Type>,
+    loops: Vec<LoopScope>,
+    in_defer: bool,
+    max_array_size: usize,
+    /// Functions marked `@deprecated`, with the attribute's message if it has one.
+    deprecated: HashMap<String, Option<String>>,
+    /// Functions marked `@noreturn`.
+    noreturn: HashSet<String>,
+    warnings: Vec<Diagnostic>,
 }
 
 impl SemanticAnalyzer {
     pub fn new() -> Self {
         SemanticAnalyzer {
             symbol_table: vec![HashMap::new()],
+            functions: HashMap::new(),
+            type_aliases: HashMap::new(),
+            function_order: Vec::new(),
+            call_counts: HashMap::new(),
+            symbol_log: Vec::new(),
+            local_functions: Vec::new(),
+            enclosing_scopes: Vec::new(),
+            current_function: None,
             current_function_return: None,
+            loops: Vec::new(),
+            in_defer: false,
+            max_array_size: DEFAULT_MAX_ARRAY_SIZE,
+            deprecated: HashMap::new(),
+            noreturn: HashSet::new(),
+            warnings: Vec::new(),
         }
     }
     
+    pub fn with_max_array_size(mut self, max_array_size: usize) -> Self {
+        self.max_array_size = max_array_size;
+        self
+    }
+    
+    /// Analyzes each top-level item independently so one broken function
+    /// doesn't hide errors in the others.
     pub fn analyze(&mut self, ast: &AstNode) -> Result<(), CompilerError> {
-        self.visit(ast)?;
+        let AstNode::Module { items, .. } = ast else {
+            self.visit(ast)?;
+            return Ok(());
+        };
+        
+        let mut errors = self.collect_type_aliases(items);
+        errors.extend(self.collect_statics(items));
+        errors.extend(self.collect_signatures(items));
+        for item in items {
+            let depth = self.symbol_table.len();
+            if let Err(e) = self.visit(item) {
+                errors.push(e);
+                self.symbol_table.truncate(depth);
+                self.enclosing_scopes.clear();
+                for name in self.local_functions.drain(..) {
+                    self.functions.remove(&name);
+                }
+                self.current_function = None;
+                self.current_function_return = None;
+                self.loops.clear();
+                self.in_defer = false;
+            }
+        }
+        
+        self.warn_unused_results();
+        
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(CompilerError::Multiple(errors)),
+        }
+    }
+    
+    /// Warns about values stored in a variable and overwritten before any read,
+    /// in `body` and the blocks nested in it.
+    fn warn_dead_stores(&mut self, body: &[AstNode]) {
+        for (i, stmt) in body.iter().enumerate() {
+            if let Some(name) = optimizer::dead_store(body, i, |n| self.symbol_table[0].contains_key(n)) {
+                self.warnings.push(Diagnostic::warning(
+                    "semantic",
+                    format!("value assigned to '{}' is never read", name),
+                ));
+            }
+            match stmt {
+                AstNode::If { then_branch, else_branch, .. } => {
+                    self.warn_dead_stores(then_branch);
+                    if let Some(else_body) = else_branch {
+                        self.warn_dead_stores(else_body);
+                    }
+                }
+                AstNode::While { body, .. }
+                | AstNode::For { body, .. }
+                | AstNode::Loop { body, .. }
+                | AstNode::Block { body } => self.warn_dead_stores(body),
+                _ => {}
+            }
+        }
+    }
+    
+    /// Warns about value-returning functions whose every call drops the result.
+    fn warn_unused_results(&mut self) {
+        for name in &self.function_order {
+            let Some(counts) = self.call_counts.get(name) else {
+                continue;
+            };
+            let returns_value = !matches!(self.functions[name].return_type, None | Some(Type::Void));
+            if returns_value && counts.discarded == counts.calls {
+                self.warnings.push(Diagnostic::warning(
+                    "semantic",
+                    format!("Result of function '{}' is never used", name),
+                ));
+            }
+        }
+    }
+    
+    /// Registers every `type` alias, struct and union before anything uses one, then
+    /// checks that each resolves to a real type.
+    fn collect_type_aliases(&mut self, items: &[AstNode]) -> Vec<CompilerError> {
+        let mut errors = Vec::new();
+        for item in items {
+            match item {
+                AstNode::TypeAlias { name, target } => {
+                    if self.type_aliases.contains_key(name) {
+                        errors.push(CompilerError::SemanticError(
+                            format!("Type alias '{}' is already defined", name)
+                        ));
+                        continue;
+                    }
+                    self.type_aliases.insert(name.clone(), target.clone());
+                }
+                AstNode::StructDecl { name, fields } | AstNode::UnionDecl { name, fields } => {
+                    if self.type_aliases.contains_key(name) {
+                        errors.push(CompilerError::SemanticError(
+                            format!("Type '{}' is already defined", name)
+                        ));
+                        continue;
+                    }
+                    let (kind, declared) = if matches!(item, AstNode::UnionDecl { .. }) {
+                        ("union", Type::Union { name: name.clone(), fields: fields.clone() })
+                    } else {
+                        ("struct", Type::Struct { name: name.clone(), fields: fields.clone() })
+                    };
+                    if let Some(field) = fields.iter().enumerate()
+                        .find(|(i, (f, _))| fields[..*i].iter().any(|(g, _)| g == f))
+                        .map(|(_, (f, _))| f)
+                    {
+                        errors.push(CompilerError::SemanticError(
+                            format!("Field '{}' is already defined in {} '{}'", field, kind, name)
+                        ));
+                    }
+                    self.type_aliases.insert(name.clone(), declared);
+                }
+                _ => {}
+            }
+        }
+        
+        for item in items {
+            let result = match item {
+                AstNode::TypeAlias { target, .. } => self.resolve_type(target),
+                AstNode::StructDecl { name, .. } | AstNode::UnionDecl { name, .. } => {
+                    self.resolve_type(&Type::Named(name.clone()))
+                }
+                _ => continue,
+            };
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        errors
+    }
+    
+    /// Declares every `static` in the global scope so any function can use it.
+    /// Initializers must be constants, since they are emitted as data.
+    fn collect_statics(&mut self, items: &[AstNode]) -> Vec<CompilerError> {
+        let mut errors = Vec::new();
+        for item in items {
+            if let AstNode::Static { name, static_type, value, mutable } = item
+                && let Err(e) = self.check_static(name, static_type, value.as_deref(), *mutable) {
+                errors.push(e);
+            }
+        }
+        errors
+    }
+    
+    fn check_static(&mut self, name: &str, static_type: &Type, value: Option<&AstNode>, mutable: bool) -> Result<(), CompilerError> {
+        let static_type = self.resolve_type(static_type)?;
+        let is_scalar = |t: &Type| !matches!(t, Type::Array(..) | Type::Tuple(_) | Type::Struct { .. } | Type::Union { .. } | Type::Void);
+        if let Type::Array(elem_type, size) = &static_type {
+            if !is_scalar(elem_type) {
+                return Err(CompilerError::SemanticError(
+                    format!("Static '{}' must be an array of scalars, got {}", name, static_type)
+                ));
+            }
+            self.check_array_size(*size)?;
+            
+            // Each element is checked on its own, so integer literals adopt the element type.
+            let elements = match value {
+                Some(AstNode::ArrayLiteral { elements }) if elements.len() == *size => elements.iter().collect(),
+                Some(AstNode::ArrayRepeat { value, count }) if *count as usize == *size => vec![&**value],
+                Some(_) => {
+                    return Err(CompilerError::SemanticError(
+                        format!("Static '{}' must be initialized with an array of {} constants", name, size)
+                    ));
+                }
+                None => Vec::new(),
+            };
+            for element in elements {
+                self.check_static_value(name, elem_type, element)?;
+            }
+            return self.declare_variable(name.to_string(), static_type, mutable);
+        }
+        if !is_scalar(&static_type) {
+            return Err(CompilerError::SemanticError(
+                format!("Static '{}' must have a scalar type, got {}", name, static_type)
+            ));
+        }
+        
+        if let Some(value) = value {
+            self.check_static_value(name, &static_type, value)?;
+        }
+        
+        self.declare_variable(name.to_string(), static_type, mutable)
+    }
+    
+    /// Checks that `value` is a constant of type `expected`, since a static's
+    /// initializer is emitted as data.
+    fn check_static_value(&mut self, name: &str, expected: &Type, value: &AstNode) -> Result<(), CompilerError> {
+        if !matches!(value, AstNode::Literal(_)) && optimizer::fold_constant(value).is_none() {
+            return Err(CompilerError::SemanticError(
+                format!("Static '{}' must be initialized with a constant", name)
+            ));
+        }
+        if let Some(value_type) = self.visit_expecting(value, Some(expected))?
+            && !self.types_compatible(expected, &value_type) {
+            return Err(CompilerError::SemanticError(
+                format!("Type mismatch: expected {}, got {}", expected, value_type)
+            ));
+        }
         Ok(())
     }
     
+    /// Records every function signature up front so calls can precede definitions.
+    /// A second definition of a name is reported and the first one is kept.
+    fn collect_signatures(&mut self, items: &[AstNode]) -> Vec<CompilerError> {
+        let mut errors = Vec::new();
+        for item in items {
+            if let AstNode::Function { name, params, return_type, attributes, line, column, .. } = item {
+                if let Some(existing) = self.functions.get(name) {
+                    errors.push(CompilerError::SemanticError(format!(
+                        "Function '{}' is already defined at line {}",
+                        name, existing.line
+                    )).at(*line, *column));
+                    continue;
+                }
+                self.function_order.push(name.clone());
+                // Unknown types are reported when the function itself is visited.
+                let resolve = |t: &Type| t.resolve(&self.type_aliases).unwrap_or_else(|_| t.clone());
+                let signature = FunctionSignature {
+                    params: params.iter().map(|(_, t)| resolve(t)).collect(),
+                    return_type: return_type.as_ref().map(resolve),
+                    line: *line,
+                };
+                self.functions.insert(name.clone(), signature);
+                if let Some(attribute) = attributes.iter().find(|a| a.name == "deprecated") {
+                    self.deprecated.insert(name.clone(), attribute.argument.clone());
+                }
+                if attributes.iter().any(|a| a.name == "noreturn") {
+                    self.noreturn.insert(name.clone());
+                }
+            }
+        }
+        errors
+    }
+    
+    /// Warns at a call of `name` if the function is `@deprecated`.
+    fn warn_if_deprecated(&mut self, name: &str, line: usize, column: usize) {
+        if let Some(message) = self.deprecated.get(name) {
+            let warning = match message {
+                Some(message) => format!("Call to deprecated function '{}': {}", name, message),
+                None => format!("Call to deprecated function '{}'", name),
+            };
+            self.warnings.push(Diagnostic::warning("semantic", warning).at(line, column));
+        }
+    }
+    
+    /// Renders every function signature followed by the variables declared in
+    /// it, tagged with their scope depth.
+    pub fn dump_symbols(&self) -> String {
+        let mut out = String::new();
+        for name in &self.function_order {
+            let signature = &self.functions[name];
+            let params: Vec<String> = signature.params.iter().map(|t| t.to_string()).collect();
+            out.push_str(&format!("fn {}({})", name, params.join(", ")));
+            if let Some(return_type) = &signature.return_type {
+                out.push_str(&format!(" -> {}", return_type));
+            }
+            out.push('\n');
+            
+            for record in self.symbol_log.iter().filter(|r| r.function.as_deref() == Some(name.as_str())) {
+                out.push_str(&format!(
+                    "    [{}] {}{}: {}\n",
+                    record.depth,
+                    if record.info.mutable { "mut " } else { "" },
+                    record.name,
+                    record.info.symbol_type
+                ));
+            }
+        }
+        out
+    }
+    
+    pub fn take_warnings(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.warnings)
+    }
+    
     fn enter_scope(&mut self) {
         self.symbol_table.push(HashMap::new());
     }
@@ -41,11 +395,215 @@ impl SemanticAnalyzer {
                     format!("Variable '{}' already declared in this scope", name)
                 ));
             }
-            scope.insert(name, SymbolInfo { symbol_type: var_type, mutable });
+            let info = SymbolInfo { symbol_type: var_type, mutable, initialized: true };
+            self.symbol_log.push(SymbolRecord {
+                function: self.current_function.clone(),
+                depth: self.symbol_table.len() - 1,
+                name: name.clone(),
+                info: info.clone(),
+            });
+            self.symbol_table.last_mut().unwrap().insert(name, info);
+        }
+        Ok(())
+    }
+    
+    /// Registers a function declared inside another one. It is callable from
+    /// the rest of the enclosing function, including from itself.
+    fn declare_local_function(&mut self, name: &str, params: &[(String, Type)], return_type: &Option<Type>, line: usize, column: usize) -> Result<(), CompilerError> {
+        if let Some(existing) = self.functions.get(name) {
+            return Err(CompilerError::SemanticError(format!(
+                "Function '{}' is already defined at line {}",
+                name, existing.line
+            )).at(line, column));
+        }
+        let signature = FunctionSignature {
+            params: params.iter().map(|(_, t)| self.resolve_type(t)).collect::<Result<_, _>>()?,
+            return_type: return_type.as_ref().map(|t| self.resolve_type(t)).transpose()?,
+            line,
+        };
+        self.functions.insert(name.to_string(), signature);
+        self.local_functions.push(name.to_string());
+        Ok(())
+    }
+    
+    /// Validates the attributes of function `name`. Unknown attributes are
+    /// ignored with a warning.
+    fn check_attributes(&mut self, name: &str, return_type: &Option<Type>, attributes: &[Attribute]) -> Result<(), CompilerError> {
+        for attribute in attributes {
+            let at = |e: CompilerError| e.at(attribute.line, attribute.column);
+            match attribute.name.as_str() {
+                "inline" | "noreturn" => {
+                    if attribute.argument.is_some() {
+                        return Err(at(CompilerError::SemanticError(format!(
+                            "Attribute '@{}' takes no arguments",
+                            attribute.name
+                        ))));
+                    }
+                    if attribute.name == "noreturn" && return_type.as_ref().is_some_and(|t| *t != Type::Void) {
+                        return Err(at(CompilerError::SemanticError(format!(
+                            "Function '{}' is marked @noreturn but has a return type",
+                            name
+                        ))));
+                    }
+                }
+                // The message, if any, is repeated at each call site.
+                "deprecated" => {}
+                "export" => {
+                    let Some(symbol) = &attribute.argument else {
+                        return Err(at(CompilerError::SemanticError(
+                            "Attribute '@export' expects a symbol name, as in @export(\"name\")".to_string()
+                        )));
+                    };
+                    let valid = symbol.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                        && symbol.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                    if !valid {
+                        return Err(at(CompilerError::SemanticError(format!(
+                            "Invalid export name '{}'",
+                            symbol
+                        ))));
+                    }
+                    if name == "main" {
+                        return Err(at(CompilerError::SemanticError(
+                            "'main' cannot be exported under another name".to_string()
+                        )));
+                    }
+                    if symbol != name && (self.functions.contains_key(symbol) || symbol == "main") {
+                        return Err(at(CompilerError::SemanticError(format!(
+                            "Export name '{}' is already used by a function",
+                            symbol
+                        ))));
+                    }
+                }
+                _ => self.warnings.push(Diagnostic::warning(
+                    "semantic",
+                    format!("Unknown attribute '@{}' on function '{}' is ignored", attribute.name, name),
+                )),
+            }
         }
         Ok(())
     }
     
+    /// Reports an unknown variable, calling out locals of an enclosing function
+    /// that a local function tried to capture.
+    fn undefined_variable(&self, name: &str) -> CompilerError {
+        let captured = self.enclosing_scopes.iter().flatten().any(|scope| scope.contains_key(name));
+        match &self.current_function {
+            Some(function) if captured => CompilerError::SemanticError(format!(
+                "Local function '{}' cannot capture '{}' from the enclosing function",
+                function, name
+            )),
+            _ => match self.similar_variable(name) {
+                Some(similar) => CompilerError::SemanticError(format!(
+                    "Undefined variable '{}'; did you mean '{}'?",
+                    name, similar
+                )),
+                None => CompilerError::SemanticError(format!("Undefined variable '{}'", name)),
+            },
+        }
+    }
+    
+    /// The variable in scope whose name is closest to `name`, if it is within
+    /// a third of the name's length in edits (at least one).
+    fn similar_variable(&self, name: &str) -> Option<&str> {
+        let limit = (name.chars().count() / 3).max(1);
+        self.symbol_table.iter()
+            .flat_map(|scope| scope.keys())
+            .map(|candidate| (edit_distance(name, candidate), candidate.as_str()))
+            .filter(|&(distance, _)| distance <= limit)
+            .min()
+            .map(|(_, candidate)| candidate)
+    }
+    
+    /// Variables declared without a value and not yet definitely assigned,
+    /// identified by scope index and name.
+    fn unassigned_variables(&self) -> Vec<(usize, String)> {
+        let mut unassigned = Vec::new();
+        for (depth, scope) in self.symbol_table.iter().enumerate() {
+            for (name, info) in scope {
+                if !info.initialized {
+                    unassigned.push((depth, name.clone()));
+                }
+            }
+        }
+        unassigned
+    }
+    
+    fn set_initialized(&mut self, variables: &[(usize, String)], initialized: bool) {
+        for (depth, name) in variables {
+            if let Some(info) = self.symbol_table.get_mut(*depth).and_then(|scope| scope.get_mut(name)) {
+                info.initialized = initialized;
+            }
+        }
+    }
+    
+    /// Whether running `body` always ends in a `return`, a call that doesn't
+    /// return, or a loop that is never left, rather than reaching its end.
+    fn always_exits(&self, body: &[AstNode]) -> bool {
+        body.iter().any(|stmt| match stmt {
+            AstNode::Return { .. } => true,
+            AstNode::FunctionCall { name, .. } => name == "panic" || self.noreturn.contains(name),
+            AstNode::If { then_branch, else_branch: Some(else_branch), .. } => {
+                self.always_exits(then_branch) && self.always_exits(else_branch)
+            }
+            AstNode::Block { body } => self.always_exits(body),
+            AstNode::Loop { body, label } => !breaks_out(body, label, false),
+            AstNode::While { condition, body, label } => {
+                optimizer::constant_condition(condition) == Some(true) && !breaks_out(body, label, false)
+            }
+            _ => false,
+        })
+    }
+    
+    /// Visits one arm of an `if` in its own scope. Returns the variables from
+    /// `unassigned` that it definitely assigns, and when `as_value` the type of
+    /// the value it ends in, then marks those variables unassigned again so the
+    /// other arm starts from the same state.
+    fn visit_branch(&mut self, body: &[AstNode], unassigned: &[(usize, String)], as_value: bool, expected: Option<&Type>) -> BranchResult {
+        // An arm that ends in return, break or continue never reaches the code after the `if`.
+        let diverges = matches!(body.last(), Some(AstNode::Return { .. } | AstNode::Break { .. } | AstNode::Continue { .. }));
+        
+        self.enter_scope();
+        let mut value_type = None;
+        for (i, stmt) in body.iter().enumerate() {
+            match stmt {
+                AstNode::BlockValue { value } if as_value && i == body.len() - 1 => {
+                    value_type = self.visit_expecting(value, expected)?;
+                }
+                // A nested `if` with an `else` that ends the arm gives its value.
+                AstNode::If { condition, then_branch, else_branch: Some(else_branch) } if as_value && i == body.len() - 1 => {
+                    value_type = self.visit_if(condition, then_branch, Some(else_branch), true, expected)?;
+                }
+                _ => {
+                    self.visit_statement(stmt)?;
+                }
+            }
+        }
+        self.exit_scope();
+        
+        if as_value {
+            match &value_type {
+                Some(t) if matches!(t, Type::Array(..) | Type::Tuple(_) | Type::Struct { .. } | Type::Union { .. } | Type::Void) => {
+                    return Err(CompilerError::SemanticError(
+                        format!("An 'if' used as a value must give a scalar value, got {}", t)
+                    ));
+                }
+                None if !diverges && !matches!(body.last(), Some(AstNode::BlockValue { .. } | AstNode::If { else_branch: Some(_), .. })) => {
+                    return Err(CompilerError::SemanticError(
+                        "Each branch of an 'if' used as a value must end in a value".to_string()
+                    ));
+                }
+                _ => {}
+            }
+        }
+        
+        let assigned: Vec<_> = unassigned.iter()
+            .filter(|(depth, name)| diverges || self.symbol_table[*depth][name].initialized)
+            .cloned()
+            .collect();
+        self.set_initialized(unassigned, false);
+        Ok((assigned, value_type))
+    }
+    
     fn lookup_variable(&self, name: &str) -> Option<&SymbolInfo> {
         for scope in self.symbol_table.iter().rev() {
             if let Some(info) = scope.get(name) {
@@ -63,40 +621,77 @@ impl SemanticAnalyzer {
                 }
                 Ok(None)
             }
-            AstNode::Function { name: _, params, return_type, body } => {
+            AstNode::TypeAlias { .. } | AstNode::StructDecl { .. } | AstNode::UnionDecl { .. } | AstNode::Static { .. } => Ok(None),
+            AstNode::Function { name, params, return_type, body, attributes, line, column, .. } => {
+                self.check_attributes(name, return_type, attributes)?;
+                
+                // A function declared inside another sees only the global scope.
+                let enclosing_loops = if self.current_function.is_some() {
+                    self.declare_local_function(name, params, return_type, *line, *column)?;
+                    let globals = vec![self.symbol_table[0].clone()];
+                    self.enclosing_scopes.push(std::mem::replace(&mut self.symbol_table, globals));
+                    Some(std::mem::take(&mut self.loops))
+                } else {
+                    None
+                };
+                let local_mark = self.local_functions.len();
                 self.enter_scope();
                 
+                let old_function = self.current_function.replace(name.clone());
                 let old_return = self.current_function_return.clone();
-                self.current_function_return = return_type.clone();
+                self.current_function_return = return_type.as_ref().map(|t| self.resolve_type(t)).transpose()?;
                 
                 for (param_name, param_type) in params {
-                    self.declare_variable(param_name.clone(), param_type.clone(), false)?;
+                    let param_type = self.resolve_type(param_type)?;
+                    self.declare_variable(param_name.clone(), param_type, false)?;
                 }
                 
                 for stmt in body {
-                    self.visit(stmt)?;
+                    self.visit_statement(stmt)?;
                 }
+                self.warn_dead_stores(body);
                 
+                // Each `return`, including a trailing expression, was checked against the
+                // return type, so what remains is falling off the end without a value.
+                if let Some(expected) = self.current_function_return.clone().filter(|t| *t != Type::Void)
+                    && !self.always_exits(body) {
+                    return Err(CompilerError::SemanticError(format!(
+                        "Function '{}' must return a value of type {} on every path",
+                        name, expected
+                    )).at(*line, *column));
+                }
+                
+                self.current_function = old_function;
                 self.current_function_return = old_return;
                 self.exit_scope();
+                
+                for local in self.local_functions.split_off(local_mark) {
+                    self.functions.remove(&local);
+                }
+                if let Some(loops) = enclosing_loops {
+                    self.symbol_table = self.enclosing_scopes.pop().unwrap();
+                    self.loops = loops;
+                }
                 Ok(None)
             }
             AstNode::VariableDecl { name, var_type, value, mutable } => {
+                let var_type = var_type.as_ref().map(|t| self.resolve_type(t)).transpose()?;
+                
                 let inferred_type = if let Some(val) = value {
-                    self.visit(val)?
+                    self.visit_expecting(val, var_type.as_ref())?
                 } else {
                     None
                 };
                 
                 let final_type = if let Some(explicit_type) = var_type {
                     if let Some(inf_type) = inferred_type {
-                        if !self.types_compatible(explicit_type, &inf_type) {
+                        if !self.types_compatible(&explicit_type, &inf_type) {
                             return Err(CompilerError::SemanticError(
                                 format!("Type mismatch: expected {:?}, got {:?}", explicit_type, inf_type)
                             ));
                         }
                     }
-                    explicit_type.clone()
+                    explicit_type
                 } else if let Some(inf_type) = inferred_type {
                     inf_type
                 } else {
@@ -106,23 +701,47 @@ impl SemanticAnalyzer {
                 };
                 
                 self.declare_variable(name.clone(), final_type, *mutable)?;
+                if value.is_none() {
+                    self.symbol_table.last_mut().unwrap().get_mut(name).unwrap().initialized = false;
+                }
                 Ok(None)
             }
             AstNode::ConstDecl { name, const_type, value } => {
-                let value_type = self.visit(value)?;
+                let const_type = self.resolve_type(const_type)?;
+                let value_type = self.visit_expecting(value, Some(&const_type))?;
                 if let Some(val_type) = value_type {
-                    if !self.types_compatible(const_type, &val_type) {
+                    if !self.types_compatible(&const_type, &val_type) {
                         return Err(CompilerError::SemanticError(
                             format!("Constant type mismatch: expected {:?}, got {:?}", const_type, val_type)
                         ));
                     }
                 }
-                self.declare_variable(name.clone(), const_type.clone(), false)?;
+                self.declare_variable(name.clone(), const_type, false)?;
                 Ok(None)
             }
             AstNode::Return { value } => {
+                if self.in_defer {
+                    return Err(CompilerError::SemanticError(
+                        "Cannot return from a deferred statement".to_string()
+                    ));
+                }
+                match (value, &self.current_function_return) {
+                    (Some(_), Some(Type::Void)) => {
+                        return Err(CompilerError::SemanticError(
+                            "Cannot return a value from a function returning void".to_string()
+                        ));
+                    }
+                    (None, Some(expected)) if *expected != Type::Void => {
+                        return Err(CompilerError::SemanticError(
+                            format!("Missing return value: expected {}", expected)
+                        ));
+                    }
+                    _ => {}
+                }
+                
                 if let Some(val) = value {
-                    let return_type = self.visit(val)?;
+                    let expected_return = self.current_function_return.clone();
+                    let return_type = self.visit_expecting(val, expected_return.as_ref())?;
                     if let Some(expected) = &self.current_function_return {
                         if let Some(actual) = return_type {
                             if !self.types_compatible(expected, &actual) {
@@ -135,21 +754,73 @@ impl SemanticAnalyzer {
                 }
                 Ok(None)
             }
-            AstNode::BinaryOp { left, op, right } => {
+            AstNode::BinaryOp { left, op, right, line, column } if op == "<<" || op == ">>" => {
                 let left_type = self.visit(left)?;
                 let right_type = self.visit(right)?;
                 
+                for t in [&left_type, &right_type].into_iter().flatten() {
+                    if !t.is_integer() {
+                        return Err(CompilerError::SemanticError(
+                            format!("Shift operands must be integers, got {}", t)
+                        ).at(*line, *column));
+                    }
+                }
+                
+                Ok(left_type)
+            }
+            AstNode::BinaryOp { left, op, right, line, column } => {
+                // A bare integer literal adopts the other operand's integer type.
+                let (left_type, right_type) = if literal_int_value(left).is_some() {
+                    let right_type = self.visit(right)?;
+                    (self.visit_expecting(left, right_type.as_ref())?, right_type)
+                } else {
+                    let left_type = self.visit(left)?;
+                    (left_type.clone(), self.visit_expecting(right, left_type.as_ref())?)
+                };
+                
                 if let (Some(lt), Some(rt)) = (left_type, right_type) {
+                    // Integers of different widths are promoted to the type that holds both.
+                    let (lt, rt) = match lt.promote(&rt) {
+                        Some(promoted) => (promoted.clone(), promoted),
+                        None => (lt, rt),
+                    };
+                    if matches!(lt, Type::Array(..)) && matches!(rt, Type::Array(..)) && !self.types_compatible(&lt, &rt) {
+                        return Err(CompilerError::SemanticError(
+                            format!("Cannot compare arrays of different types: {} {} {}", lt, op, rt)
+                        ).at(*line, *column));
+                    }
                     if !self.types_compatible(&lt, &rt) {
                         return Err(CompilerError::SemanticError(
                             format!("Type mismatch in binary operation: {:?} {} {:?}", lt, op, rt)
-                        ));
+                        ).at(*line, *column));
                     }
                     
                     match op.as_str() {
-                        "==" | "!=" | "<" | "<=" | ">" | ">=" | "&&" | "||" => {
+                        "&&" | "||" => {
+                            if lt != Type::Bool {
+                                return Err(CompilerError::SemanticError(
+                                    format!("Operator '{}' requires bool operands, got {}", op, lt)
+                                ).at(*line, *column));
+                            }
                             Ok(Some(Type::Bool))
                         }
+                        "<" | "<=" | ">" | ">=" => {
+                            if !lt.is_numeric() {
+                                return Err(CompilerError::SemanticError(
+                                    format!("Operator '{}' requires numeric operands, got {}", op, lt)
+                                ).at(*line, *column));
+                            }
+                            Ok(Some(Type::Bool))
+                        }
+                        "==" | "!=" => Ok(Some(Type::Bool)),
+                        "&" | "|" | "^" => {
+                            if !lt.is_integer() && lt != Type::Bool {
+                                return Err(CompilerError::SemanticError(
+                                    format!("Operator '{}' requires integer or bool operands, got {}", op, lt)
+                                ).at(*line, *column));
+                            }
+                            Ok(Some(lt))
+                        }
                         _ => Ok(Some(lt))
                     }
                 } else {
@@ -160,127 +831,429 @@ impl SemanticAnalyzer {
                 self.visit(operand)
             }
             AstNode::Literal(lit) => {
-                Ok(Some(match lit {
-                    Literal::Int(_) => Type::I32,
-                    Literal::Float(_) => Type::F64,
-                    Literal::String(_) => Type::Str,
-                    Literal::Bool(_) => Type::Bool,
-                    Literal::Char(_) => Type::Char,
-                }))
-            }
-            AstNode::Identifier(name) => {
+                Ok(Some(lit.default_type()))
+            }
+            AstNode::Identifier { name, line, column } => {
                 if let Some(info) = self.lookup_variable(name) {
+                    if !info.initialized {
+                        return Err(CompilerError::SemanticError(
+                            format!("Variable '{}' is used before being assigned", name)
+                        ).at(*line, *column));
+                    }
                     Ok(Some(info.symbol_type.clone()))
                 } else {
-                    Err(CompilerError::SemanticError(
-                        format!("Undefined variable '{}'", name)
-                    ))
+                    Err(self.undefined_variable(name).at(*line, *column))
                 }
             }
-            AstNode::FunctionCall { name: _, args } => {
+            AstNode::FunctionCall { name, args, .. } if name == "print" => {
                 for arg in args {
                     self.visit(arg)?;
                 }
-                Ok(Some(Type::I32))
+                Ok(Some(Type::Void))
             }
-            AstNode::If { condition, then_branch, else_branch } => {
-                let cond_type = self.visit(condition)?;
-                if let Some(t) = cond_type {
-                    if t != Type::Bool {
+            AstNode::FunctionCall { name, args, line, column } if name == "print_hex" || name == "print_char" => {
+                // print_hex(x: any integer) and print_char(c: char or u8)
+                if args.len() != 1 {
+                    return Err(CompilerError::SemanticError(
+                        format!("Function '{}' expects 1 argument, got {}", name, args.len())
+                    ).at(*line, *column));
+                }
+                if let Some(arg_type) = self.visit(&args[0])? {
+                    let arg_type = self.resolve_type(&arg_type)?;
+                    let accepted = if name == "print_hex" {
+                        arg_type.is_integer()
+                    } else {
+                        matches!(arg_type, Type::Char | Type::U8)
+                    };
+                    if !accepted {
+                        let expected = if name == "print_hex" { "an integer" } else { "a char or u8" };
                         return Err(CompilerError::SemanticError(
-                            "Condition must be boolean".to_string()
-                        ));
+                            format!("Function '{}' expects {}, got {}", name, expected, arg_type)
+                        ).at(*line, *column));
+                    }
+                }
+                Ok(Some(Type::Void))
+            }
+            AstNode::FunctionCall { name, args, line, column } if name == "printf" => {
+                // printf(format: str, args...) passes everything straight to C.
+                let Some(format) = args.first() else {
+                    return Err(CompilerError::SemanticError(
+                        "Function 'printf' expects a format string".to_string()
+                    ).at(*line, *column));
+                };
+                if let Some(format_type) = self.visit(format)?
+                    && format_type != Type::Str {
+                    return Err(CompilerError::SemanticError(
+                        format!("printf format must be a str, got {}", format_type)
+                    ).at(*line, *column));
+                }
+                for arg in &args[1..] {
+                    if let Some(arg_type) = self.visit(arg)?
+                        && matches!(self.resolve_type(&arg_type)?, Type::Array(..) | Type::Tuple(_) | Type::Struct { .. } | Type::Union { .. }) {
+                        return Err(CompilerError::SemanticError(
+                            format!("printf arguments must be scalars, got {}", arg_type)
+                        ).at(*line, *column));
                     }
                 }
                 
-                self.enter_scope();
-                for stmt in then_branch {
-                    self.visit(stmt)?;
+                if let AstNode::Literal(Literal::String(format)) = format {
+                    let expected = printf_argument_count(format);
+                    if expected != args.len() - 1 {
+                        self.warnings.push(Diagnostic::warning(
+                            "semantic",
+                            format!(
+                                "printf format expects {} argument{}, but {} {} given",
+                                expected,
+                                if expected == 1 { "" } else { "s" },
+                                args.len() - 1,
+                                if args.len() == 2 { "was" } else { "were" },
+                            ),
+                        ).at(*line, *column));
+                    }
+                }
+                Ok(Some(Type::Void))
+            }
+            AstNode::FunctionCall { name, args, line, column } if name == "assert" || name == "panic" => {
+                // assert(cond: bool, message: str) and panic(message: str)
+                let expected: &[Type] = if name == "assert" { &[Type::Bool, Type::Str] } else { &[Type::Str] };
+                if args.len() != expected.len() {
+                    return Err(CompilerError::SemanticError(
+                        format!("Function '{}' expects {} arguments, got {}", name, expected.len(), args.len())
+                    ).at(*line, *column));
+                }
+                
+                for (arg, param_type) in args.iter().zip(expected) {
+                    if let Some(arg_type) = self.visit(arg)?
+                        && !self.types_compatible(param_type, &arg_type) {
+                        return Err(CompilerError::SemanticError(
+                            format!("Argument type mismatch in call to '{}': expected {}, got {}", name, param_type, arg_type)
+                        ).at(*line, *column));
+                    }
+                }
+                Ok(Some(Type::Void))
+            }
+            AstNode::FunctionCall { name, args, line, column }
+                if matches!(name.as_str(), "min" | "max" | "abs") && !self.functions.contains_key(name) =>
+            {
+                // min(a, b), max(a, b) and abs(x) on any numeric type, compiled inline.
+                let expected = if name == "abs" { 1 } else { 2 };
+                if args.len() != expected {
+                    return Err(CompilerError::SemanticError(
+                        format!("Function '{}' expects {} arguments, got {}", name, expected, args.len())
+                    ).at(*line, *column));
                 }
-                self.exit_scope();
                 
-                if let Some(else_body) = else_branch {
-                    self.enter_scope();
-                    for stmt in else_body {
-                        self.visit(stmt)?;
+                let mut arg_type = self.visit(&args[0])?;
+                if let Some(second) = args.get(1) {
+                    let second_type = self.visit_expecting(second, arg_type.as_ref())?;
+                    // A literal first argument takes the type of the second.
+                    if matches!(args[0], AstNode::Literal(_)) {
+                        arg_type = self.visit_expecting(&args[0], second_type.as_ref())?;
+                    }
+                    if let (Some(a), Some(b)) = (&arg_type, &second_type)
+                        && a != b {
+                        return Err(CompilerError::SemanticError(
+                            format!("Arguments to '{}' must have the same type, got {} and {}", name, a, b)
+                        ).at(*line, *column));
                     }
-                    self.exit_scope();
                 }
                 
-                Ok(None)
+                if let Some(t) = &arg_type
+                    && !t.is_numeric() {
+                    return Err(CompilerError::SemanticError(
+                        format!("Function '{}' expects numeric arguments, got {}", name, t)
+                    ).at(*line, *column));
+                }
+                Ok(arg_type)
             }
-            AstNode::While { condition, body } => {
-                let cond_type = self.visit(condition)?;
-                if let Some(t) = cond_type {
-                    if t != Type::Bool {
+            AstNode::FunctionCall { name, args, line, column } => {
+                self.call_counts.entry(name.clone()).or_default().calls += 1;
+                self.warn_if_deprecated(name, *line, *column);
+                let signature = match self.functions.get(name) {
+                    Some(signature) => signature.clone(),
+                    None => {
                         return Err(CompilerError::SemanticError(
-                            "Condition must be boolean".to_string()
-                        ));
+                            format!("Undefined function '{}'", name)
+                        ).at(*line, *column));
                     }
+                };
+                
+                if args.len() != signature.params.len() {
+                    return Err(CompilerError::SemanticError(
+                        format!("Function '{}' expects {} arguments, got {}", name, signature.params.len(), args.len())
+                    ).at(*line, *column));
                 }
                 
+                for (arg, param_type) in args.iter().zip(&signature.params) {
+                    if let Some(arg_type) = self.visit_expecting(arg, Some(param_type)).map_err(|e| e.at(*line, *column))?
+                        && !self.types_compatible(param_type, &arg_type) {
+                        return Err(CompilerError::SemanticError(
+                            format!("Argument type mismatch in call to '{}': expected {}, got {}", name, param_type, arg_type)
+                        ).at(*line, *column));
+                    }
+                }
+                
+                Ok(Some(signature.return_type.unwrap_or(Type::Void)))
+            }
+            AstNode::MethodCall { receiver, method, args, line, column } => {
+                let struct_name = match self.visit(receiver)? {
+                    Some(Type::Struct { name, .. }) => name,
+                    Some(other) => {
+                        return Err(CompilerError::SemanticError(
+                            format!("Cannot call method '{}' on non-struct type {}", method, other)
+                        ).at(*line, *column));
+                    }
+                    None => return Ok(None),
+                };
+                let symbol = method_symbol(&struct_name, method);
+                self.call_counts.entry(symbol.clone()).or_default().calls += 1;
+                self.warn_if_deprecated(&symbol, *line, *column);
+                let Some(signature) = self.functions.get(&symbol).cloned() else {
+                    return Err(CompilerError::SemanticError(
+                        format!("Struct '{}' has no method '{}'", struct_name, method)
+                    ).at(*line, *column));
+                };
+                
+                // The receiver is the hidden first parameter.
+                let params = &signature.params[1..];
+                if args.len() != params.len() {
+                    return Err(CompilerError::SemanticError(
+                        format!("Method '{}' expects {} arguments, got {}", method, params.len(), args.len())
+                    ).at(*line, *column));
+                }
+                
+                for (arg, param_type) in args.iter().zip(params) {
+                    if let Some(arg_type) = self.visit_expecting(arg, Some(param_type)).map_err(|e| e.at(*line, *column))?
+                        && !self.types_compatible(param_type, &arg_type) {
+                        return Err(CompilerError::SemanticError(
+                            format!("Argument type mismatch in call to method '{}': expected {}, got {}", method, param_type, arg_type)
+                        ).at(*line, *column));
+                    }
+                }
+                
+                Ok(Some(signature.return_type.unwrap_or(Type::Void)))
+            }
+            // Statements go through `visit_statement`, so this `if` is used as a value.
+            AstNode::If { condition, then_branch, else_branch } => {
+                self.visit_if(condition, then_branch, else_branch.as_deref(), true, None)
+            }
+            AstNode::Block { body } => {
                 self.enter_scope();
                 for stmt in body {
-                    self.visit(stmt)?;
+                    self.visit_statement(stmt)?;
                 }
                 self.exit_scope();
+                Ok(None)
+            }
+            AstNode::While { condition, body, label } => {
+                self.visit_condition(condition)?;
+                // `while (true)` is the usual infinite loop, so only a constant false is suspicious.
+                if optimizer::constant_condition(condition) == Some(false) {
+                    self.warnings.push(Diagnostic::warning(
+                        "semantic",
+                        "`while` condition is always false".to_string(),
+                    ));
+                }
+                
+                // The body may not run, so assignments in it don't count afterwards.
+                let unassigned = self.unassigned_variables();
+                self.push_loop(label, false);
+                self.enter_scope();
+                for stmt in body {
+                    self.visit_statement(stmt)?;
+                }
+                self.exit_scope();
+                self.loops.pop();
+                self.set_initialized(&unassigned, false);
                 
                 Ok(None)
             }
-            AstNode::For { iterator, range_start, range_end, body, .. } => {
+            AstNode::For { iterator, range_start, range_end, body, label, .. } => {
                 self.visit(range_start)?;
                 self.visit(range_end)?;
                 
+                let unassigned = self.unassigned_variables();
+                self.push_loop(label, false);
                 self.enter_scope();
                 self.declare_variable(iterator.clone(), Type::I32, false)?;
                 
                 for stmt in body {
-                    self.visit(stmt)?;
+                    self.visit_statement(stmt)?;
                 }
                 self.exit_scope();
+                self.loops.pop();
+                self.set_initialized(&unassigned, false);
                 
                 Ok(None)
             }
-            AstNode::Loop { body } => {
-                self.enter_scope();
-                for stmt in body {
-                    self.visit(stmt)?;
+            // Reached only for a loop used as a value; statements go through `visit_statement`.
+            AstNode::Loop { body, label } => self.visit_loop_value(body, label, None),
+            AstNode::Break { label, value } => {
+                self.check_loop_exit(label)?;
+                let index = match label {
+                    Some(label) => self.loops.iter().rposition(|l| l.label.as_ref() == Some(label)),
+                    None => self.loops.len().checked_sub(1),
+                };
+                let (Some(index), Some(value)) = (index, value) else {
+                    if let Some(index) = index {
+                        self.loops[index].plain_break = true;
+                    }
+                    return Ok(None);
+                };
+                if !self.loops[index].accepts_value {
+                    return Err(CompilerError::SemanticError(
+                        "Only 'loop' can be left with a 'break' that gives a value".to_string()
+                    ));
                 }
-                self.exit_scope();
+                
+                let expected = self.loops[index].value_type.clone();
+                let adopted = expected.clone().or_else(|| self.loops[index].expected.clone());
+                let Some(value_type) = self.visit_expecting(value, adopted.as_ref())? else {
+                    return Err(CompilerError::SemanticError(
+                        "A 'break' value must have a type".to_string()
+                    ));
+                };
+                if matches!(value_type, Type::Array(..) | Type::Tuple(_) | Type::Struct { .. } | Type::Union { .. }) {
+                    return Err(CompilerError::SemanticError(format!(
+                        "A loop cannot produce a value of type {}; only scalar values can be given to 'break'",
+                        value_type
+                    )));
+                }
+                match expected {
+                    Some(expected) if !self.types_compatible(&expected, &value_type) => {
+                        Err(CompilerError::SemanticError(format!(
+                            "Mismatched 'break' values: expected {}, got {}",
+                            expected, value_type
+                        )))
+                    }
+                    Some(_) => Ok(None),
+                    None => {
+                        self.loops[index].value_type = Some(value_type);
+                        Ok(None)
+                    }
+                }
+            }
+            AstNode::Continue { label } => {
+                self.check_loop_exit(label)?;
+                Ok(None)
+            }
+            AstNode::BlockValue { value } => {
+                // Outside a function's own body the value is unused.
+                self.visit_statement(value)?;
                 Ok(None)
             }
-            AstNode::Break | AstNode::Continue => {
+            AstNode::Defer { statement } => {
+                if self.in_defer {
+                    return Err(CompilerError::SemanticError(
+                        "Cannot defer inside a deferred statement".to_string()
+                    ));
+                }
+                let loops = std::mem::take(&mut self.loops);
+                self.in_defer = true;
+                let result = self.visit_statement(statement);
+                self.in_defer = false;
+                self.loops = loops;
+                result?;
                 Ok(None)
             }
-            AstNode::Assignment { target, value } => {
+            AstNode::Assignment { target, value, line, column } => {
                 let symbol_info = if let Some(info) = self.lookup_variable(target) {
                     info.clone()
                 } else {
-                    return Err(CompilerError::SemanticError(
-                        format!("Undefined variable '{}'", target)
-                    ));
+                    return Err(self.undefined_variable(target).at(*line, *column));
                 };
                 
                 if !symbol_info.mutable {
                     return Err(CompilerError::SemanticError(
                         format!("Cannot assign to immutable variable '{}'", target)
-                    ));
+                    ).at(*line, *column));
                 }
                 
-                let value_type = self.visit(value)?;
+                let value_type = self.visit_expecting(value, Some(&symbol_info.symbol_type))?;
                 if let Some(val_type) = value_type {
                     if !self.types_compatible(&symbol_info.symbol_type, &val_type) {
                         return Err(CompilerError::SemanticError(
                             format!("Type mismatch in assignment to '{}'", target)
-                        ));
+                        ).at(*line, *column));
                     }
                 }
                 
+                if let Some(info) = self.symbol_table.iter_mut().rev().find_map(|scope| scope.get_mut(target)) {
+                    info.initialized = true;
+                }
+                
+                Ok(None)
+            }
+            AstNode::PlaceAssignment { target, op, value, line, column } => {
+                // The element or field is updated in place, so it must belong to a variable that may change.
+                let mut root = &**target;
+                while let AstNode::ArrayIndex { array: inner, .. } | AstNode::FieldAccess { object: inner, .. } = root {
+                    root = inner;
+                }
+                let AstNode::Identifier { name, .. } = root else {
+                    return Err(CompilerError::SemanticError(
+                        "Invalid assignment target".to_string()
+                    ).at(*line, *column));
+                };
+                if self.lookup_variable(name).is_some_and(|info| !info.mutable) {
+                    return Err(CompilerError::SemanticError(
+                        format!("Cannot assign to immutable variable '{}'", name)
+                    ).at(*line, *column));
+                }
+                if let AstNode::ArrayIndex { array, .. } = &**target
+                    && self.visit(array)? == Some(Type::Str) {
+                    return Err(CompilerError::SemanticError(
+                        "Cannot assign to a character of a string".to_string()
+                    ).at(*line, *column));
+                }
+                
+                let Some(target_type) = self.visit(target)? else {
+                    return Ok(None);
+                };
+                let Some(value_type) = self.visit_expecting(value, Some(&target_type))? else {
+                    return Ok(None);
+                };
+                
+                // `target op= value` stores `target op value`, which must keep the target's type.
+                let result_type = match op.as_deref() {
+                    None => value_type.clone(),
+                    Some("<<" | ">>") => {
+                        if let Some(t) = [&target_type, &value_type].into_iter().find(|t| !t.is_integer()) {
+                            return Err(CompilerError::SemanticError(
+                                format!("Shift operands must be integers, got {}", t)
+                            ).at(*line, *column));
+                        }
+                        target_type.clone()
+                    }
+                    Some(op) => {
+                        let bitwise = matches!(op, "&" | "|" | "^");
+                        if bitwise && !target_type.is_integer() && target_type != Type::Bool {
+                            return Err(CompilerError::SemanticError(
+                                format!("Operator '{}=' requires integer or bool operands, got {}", op, target_type)
+                            ).at(*line, *column));
+                        }
+                        if !bitwise && !target_type.is_numeric() {
+                            return Err(CompilerError::SemanticError(
+                                format!("Operator '{}=' requires numeric operands, got {}", op, target_type)
+                            ).at(*line, *column));
+                        }
+                        target_type.promote(&value_type).unwrap_or(value_type.clone())
+                    }
+                };
+                if !self.types_compatible(&target_type, &result_type) {
+                    return Err(CompilerError::SemanticError(
+                        format!("Type mismatch in assignment: expected {}, got {}", target_type, value_type)
+                    ).at(*line, *column));
+                }
+                
                 Ok(None)
             }
             AstNode::ArrayLiteral { elements } => {
+                // With an expected type, `visit_expecting` types `[]` before getting here.
                 if elements.is_empty() {
-                    return Ok(Some(Type::Array(Box::new(Type::I32), 0)));
+                    return Err(CompilerError::SemanticError(
+                        "Cannot infer the element type of an empty array literal; add a type annotation".to_string()
+                    ));
                 }
                 
                 let first_type = self.visit(&elements[0])?;
@@ -300,30 +1273,431 @@ impl SemanticAnalyzer {
                     Ok(None)
                 }
             }
+            AstNode::TupleLiteral { elements } => {
+                let mut types = Vec::new();
+                for elem in elements {
+                    match self.visit(elem)? {
+                        Some(t) => types.push(t),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(Type::Tuple(types)))
+            }
+            AstNode::StructLiteral { name, fields, line, column } => {
+                let struct_type = match self.resolve_type(&Type::Named(name.clone())) {
+                    Ok(t @ (Type::Struct { .. } | Type::Union { .. })) => t,
+                    _ => {
+                        return Err(CompilerError::SemanticError(
+                            format!("Unknown struct '{}'", name)
+                        ).at(*line, *column));
+                    }
+                };
+                let (kind, declared) = match &struct_type {
+                    Type::Struct { fields, .. } => ("Struct", fields),
+                    Type::Union { fields, .. } => ("Union", fields),
+                    _ => unreachable!(),
+                };
+                
+                // A union holds one field at a time, so its literal sets exactly one.
+                let is_union = matches!(struct_type, Type::Union { .. });
+                if is_union && fields.len() != 1 {
+                    return Err(CompilerError::SemanticError(
+                        format!("A '{}' literal must initialize exactly one field, got {}", name, fields.len())
+                    ).at(*line, *column));
+                }
+                
+                for (i, (field, value)) in fields.iter().enumerate() {
+                    let Some((_, field_type)) = declared.iter().find(|(f, _)| f == field) else {
+                        return Err(CompilerError::SemanticError(
+                            format!("{} '{}' has no field '{}'", kind, name, field)
+                        ).at(*line, *column));
+                    };
+                    if fields[..i].iter().any(|(f, _)| f == field) {
+                        return Err(CompilerError::SemanticError(
+                            format!("Field '{}' is initialized more than once", field)
+                        ).at(*line, *column));
+                    }
+                    if let Some(value_type) = self.visit_expecting(value, Some(field_type))?
+                        && !self.types_compatible(field_type, &value_type) {
+                        return Err(CompilerError::SemanticError(
+                            format!("Type mismatch for field '{}': expected {}, got {}", field, field_type, value_type)
+                        ).at(*line, *column));
+                    }
+                }
+                
+                if let Some((missing, _)) = declared.iter()
+                    .filter(|_| !is_union)
+                    .find(|(f, _)| !fields.iter().any(|(g, _)| g == f))
+                {
+                    return Err(CompilerError::SemanticError(
+                        format!("Missing field '{}' in '{}' literal", missing, name)
+                    ).at(*line, *column));
+                }
+                Ok(Some(struct_type))
+            }
+            AstNode::FieldAccess { object, field, line, column } => {
+                match self.visit(object)? {
+                    Some(Type::Struct { name, fields }) => {
+                        match fields.into_iter().find(|(f, _)| f == field) {
+                            Some((_, field_type)) => Ok(Some(field_type)),
+                            None => Err(CompilerError::SemanticError(
+                                format!("Struct '{}' has no field '{}'", name, field)
+                            ).at(*line, *column)),
+                        }
+                    }
+                    // Any field of a union may be read, whichever was last written.
+                    Some(Type::Union { name, fields }) => {
+                        match fields.into_iter().find(|(f, _)| f == field) {
+                            Some((_, field_type)) => Ok(Some(field_type)),
+                            None => Err(CompilerError::SemanticError(
+                                format!("Union '{}' has no field '{}'", name, field)
+                            ).at(*line, *column)),
+                        }
+                    }
+                    Some(other) => Err(CompilerError::SemanticError(
+                        format!("Cannot access field '{}' on non-struct type {}", field, other)
+                    ).at(*line, *column)),
+                    None => Ok(None),
+                }
+            }
+            AstNode::TupleDestructure { names, value } => {
+                let types = match self.visit(value)? {
+                    Some(Type::Tuple(types)) => types,
+                    Some(other) => {
+                        return Err(CompilerError::SemanticError(
+                            format!("Cannot destructure non-tuple type {}", other)
+                        ));
+                    }
+                    None => {
+                        return Err(CompilerError::SemanticError(
+                            "Cannot infer type for tuple destructuring".to_string()
+                        ));
+                    }
+                };
+                
+                if types.len() != names.len() {
+                    return Err(CompilerError::SemanticError(
+                        format!("Tuple has {} elements, but {} names were given", types.len(), names.len())
+                    ));
+                }
+                
+                for ((name, mutable), elem_type) in names.iter().zip(types) {
+                    self.declare_variable(name.clone(), elem_type, *mutable)?;
+                }
+                Ok(None)
+            }
             AstNode::ArrayRepeat { value, count } => {
+                if *count < 0 {
+                    return Err(CompilerError::SemanticError(
+                        format!("Array repeat count must not be negative, got {}", count)
+                    ));
+                }
+                let count = *count as usize;
+                self.check_array_size(count)?;
+                
                 let elem_type = self.visit(value)?;
                 if let Some(t) = elem_type {
-                    Ok(Some(Type::Array(Box::new(t), *count)))
+                    Ok(Some(Type::Array(Box::new(t), count)))
                 } else {
                     Ok(None)
                 }
             }
             AstNode::ArrayIndex { array, index } => {
                 let array_type = self.visit(array)?;
-                self.visit(index)?;
+                let index_type = self.visit(index)?;
                 
-                if let Some(Type::Array(elem_type, _)) = array_type {
-                    Ok(Some(*elem_type))
-                } else {
-                    Err(CompilerError::SemanticError(
-                        "Can only index arrays".to_string()
-                    ))
+                if let Some(t) = index_type
+                    && !t.is_integer() {
+                    return Err(CompilerError::SemanticError(
+                        format!("Index must be an integer, got {}", t)
+                    ));
+                }
+                
+                match array_type {
+                    Some(Type::Array(elem_type, _)) => Ok(Some(*elem_type)),
+                    Some(Type::Str) => Ok(Some(Type::U8)),
+                    _ => Err(CompilerError::SemanticError(
+                        "Can only index arrays and strings".to_string()
+                    )),
                 }
             }
         }
     }
     
+    fn check_array_size(&self, size: usize) -> Result<(), CompilerError> {
+        if size > self.max_array_size {
+            return Err(CompilerError::SemanticError(
+                format!("array too large: {} elements (maximum is {})", size, self.max_array_size)
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Visits a statement in a block; a bare call there discards its result.
+    fn visit_statement(&mut self, stmt: &AstNode) -> Result<Option<Type>, CompilerError> {
+        if let AstNode::FunctionCall { name, .. } = stmt {
+            self.call_counts.entry(name.clone()).or_default().discarded += 1;
+        }
+        // A loop statement's `break` values are discarded.
+        if let AstNode::Loop { body, label } = stmt {
+            self.visit_loop(body, label, None)?;
+            return Ok(None);
+        }
+        if let AstNode::If { condition, then_branch, else_branch } = stmt {
+            self.visit_if(condition, then_branch, else_branch.as_deref(), false, None)?;
+            return Ok(None);
+        }
+        self.visit(stmt)
+    }
+    
+    /// Visits the condition of an `if` or `while`. An integer condition is
+    /// true when it isn't zero, as in `while (n)`; floats and strings must be
+    /// compared explicitly.
+    fn visit_condition(&mut self, condition: &AstNode) -> Result<(), CompilerError> {
+        if let Some(t) = self.visit(condition)?
+            && t != Type::Bool && !t.is_integer() {
+            return Err(CompilerError::SemanticError(format!(
+                "Condition must be boolean or an integer, found {}", t
+            )));
+        }
+        Ok(())
+    }
+    
+    /// Visits an `if`. As a statement it may leave out `else`; used as a value,
+    /// each branch must end in a scalar value of the same type, or never finish.
+    fn visit_if(&mut self, condition: &AstNode, then_branch: &[AstNode], else_branch: Option<&[AstNode]>, as_value: bool, expected: Option<&Type>) -> Result<Option<Type>, CompilerError> {
+        self.visit_condition(condition)?;
+        if let Some(value) = optimizer::constant_condition(condition) {
+            self.warnings.push(Diagnostic::warning(
+                "semantic",
+                format!("`if` condition is always {}", value),
+            ));
+        }
+        
+        // A variable is assigned after the `if` only if both arms assign it.
+        let unassigned = self.unassigned_variables();
+        let (then_assigned, then_type) = self.visit_branch(then_branch, &unassigned, as_value, expected)?;
+        // The parser only accepts an `if` without `else` as a statement.
+        let Some(else_body) = else_branch else {
+            return Ok(None);
+        };
+        let (else_assigned, else_type) = self.visit_branch(else_body, &unassigned, as_value, expected.or(then_type.as_ref()))?;
+        let both: Vec<_> = then_assigned.into_iter().filter(|v| else_assigned.contains(v)).collect();
+        self.set_initialized(&both, true);
+        
+        if let (Some(then_type), Some(else_type)) = (&then_type, &else_type)
+            && !self.types_compatible(then_type, else_type) {
+            return Err(CompilerError::SemanticError(
+                format!("The branches of an 'if' have different types: {} and {}", then_type, else_type)
+            ));
+        }
+        Ok(then_type.or(else_type))
+    }
+    
+    /// Visits a `loop` used as a value, which must give a value to every `break`.
+    fn visit_loop_value(&mut self, body: &[AstNode], label: &Option<String>, expected: Option<&Type>) -> Result<Option<Type>, CompilerError> {
+        let scope = self.visit_loop(body, label, expected)?;
+        match scope.value_type {
+            None => Err(CompilerError::SemanticError(
+                "A loop used as a value must leave through a 'break' with a value".to_string()
+            )),
+            Some(_) if scope.plain_break => Err(CompilerError::SemanticError(
+                "Every 'break' from a loop used as a value must give a value".to_string()
+            )),
+            Some(value_type) => Ok(Some(value_type)),
+        }
+    }
+    
+    fn push_loop(&mut self, label: &Option<String>, accepts_value: bool) {
+        self.loops.push(LoopScope {
+            label: label.clone(),
+            accepts_value,
+            expected: None,
+            value_type: None,
+            plain_break: false,
+        });
+    }
+    
+    /// Visits the body of a `loop` and returns what its `break`s gave.
+    fn visit_loop(&mut self, body: &[AstNode], label: &Option<String>, expected: Option<&Type>) -> Result<LoopScope, CompilerError> {
+        // A `break` may come before the assignment, so treat the body like `while`'s.
+        let unassigned = self.unassigned_variables();
+        self.push_loop(label, true);
+        self.loops.last_mut().unwrap().expected = expected.cloned();
+        self.enter_scope();
+        let result = body.iter().try_for_each(|stmt| self.visit_statement(stmt).map(|_| ()));
+        self.exit_scope();
+        let scope = self.loops.pop().unwrap();
+        result?;
+        self.set_initialized(&unassigned, false);
+        Ok(scope)
+    }
+    
+    /// Checks that a `break` or `continue` has a loop to leave.
+    fn check_loop_exit(&self, label: &Option<String>) -> Result<(), CompilerError> {
+        // Loops outside a deferred statement are hidden while visiting it.
+        if self.in_defer && self.loops.is_empty() {
+            return Err(CompilerError::SemanticError(
+                "Cannot leave a deferred statement with break or continue".to_string()
+            ));
+        }
+        if let Some(label) = label
+            && !self.loops.iter().any(|l| l.label.as_ref() == Some(label)) {
+            return Err(CompilerError::SemanticError(
+                format!("Undefined loop label '{}'", label)
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Resolves aliases in a declared type and rejects arrays over the size limit.
+    fn resolve_type(&self, t: &Type) -> Result<Type, CompilerError> {
+        let resolved = t.resolve(&self.type_aliases).map_err(CompilerError::SemanticError)?;
+        self.check_array_size_of(&resolved)?;
+        Ok(resolved)
+    }
+    
+    fn check_array_size_of(&self, t: &Type) -> Result<(), CompilerError> {
+        match t {
+            Type::Array(elem_type, size) => {
+                self.check_array_size(*size)?;
+                self.check_array_size_of(elem_type)?;
+            }
+            Type::Tuple(types) => {
+                for elem_type in types {
+                    self.check_array_size_of(elem_type)?;
+                }
+            }
+            Type::Struct { fields, .. } | Type::Union { fields, .. } => {
+                for (_, field_type) in fields {
+                    self.check_array_size_of(field_type)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Visits `node`, letting an integer literal adopt `expected` when it fits its range.
+    fn visit_expecting(&mut self, node: &AstNode, expected: Option<&Type>) -> Result<Option<Type>, CompilerError> {
+        if let (Some(value), Some(expected)) = (literal_int_value(node), expected)
+            && let Some((min, max)) = expected.int_range() {
+            if value < min || value > max {
+                return Err(CompilerError::SemanticError(
+                    format!("Integer literal {} out of range for {} ({}..={})", value, expected, min, max)
+                ));
+            }
+            return Ok(Some(expected.clone()));
+        }
+        
+        // A float literal takes either float type.
+        if let (AstNode::Literal(Literal::Float(_)), Some(expected)) = (node, expected)
+            && expected.is_float() {
+            return Ok(Some(expected.clone()));
+        }
+        
+        // An empty array literal takes its element type from the annotation.
+        if let (AstNode::ArrayLiteral { elements }, Some(Type::Array(elem, _))) = (node, expected)
+            && elements.is_empty() {
+            return Ok(Some(Type::Array(elem.clone(), 0)));
+        }
+        
+        if let (AstNode::Loop { body, label }, Some(_)) = (node, expected) {
+            return self.visit_loop_value(body, label, expected);
+        }
+        
+        if let (AstNode::If { condition, then_branch, else_branch }, Some(_)) = (node, expected) {
+            return self.visit_if(condition, then_branch, else_branch.as_deref(), true, expected);
+        }
+        
+        // Tuple elements each adopt their position's expected type.
+        if let (AstNode::TupleLiteral { elements }, Some(Type::Tuple(types))) = (node, expected)
+            && elements.len() == types.len() {
+            let mut actual = Vec::new();
+            for (elem, elem_type) in elements.iter().zip(types) {
+                match self.visit_expecting(elem, Some(elem_type))? {
+                    Some(t) => actual.push(t),
+                    None => return Ok(None),
+                }
+            }
+            return Ok(Some(Type::Tuple(actual)));
+        }
+        
+        self.visit(node)
+    }
+    
     fn types_compatible(&self, t1: &Type, t2: &Type) -> bool {
         t1 == t2
     }
+}
+
+/// Number of arguments a printf format consumes: one per conversion, plus one
+/// for each `*` width or precision. `%%` consumes none.
+fn printf_argument_count(format: &str) -> usize {
+    let mut count = 0;
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        for spec in chars.by_ref() {
+            match spec {
+                '%' => break,
+                '*' => count += 1,
+                _ if spec.is_ascii_alphabetic() && !"hlLqjztI".contains(spec) => {
+                    count += 1;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    count
+}
+
+/// The value of `node` if it is a bare integer literal.
+fn literal_int_value(node: &AstNode) -> Option<i128> {
+    match node {
+        AstNode::Literal(lit) => lit.int_value(),
+        _ => None,
+    }
+}
+
+/// Number of single-character insertions, deletions, substitutions and
+/// swaps of adjacent characters that turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Whether `body` holds a `break` that leaves the loop labeled `label` whose
+/// body it is. `nested` is set inside inner loops, which an unlabeled `break` leaves instead.
+fn breaks_out(body: &[AstNode], label: &Option<String>, nested: bool) -> bool {
+    body.iter().any(|stmt| match stmt {
+        AstNode::Break { label: None, .. } => !nested,
+        AstNode::Break { label: Some(target), .. } => label.as_ref() == Some(target),
+        AstNode::If { then_branch, else_branch, .. } => {
+            breaks_out(then_branch, label, nested)
+                || else_branch.as_ref().is_some_and(|b| breaks_out(b, label, nested))
+        }
+        AstNode::Block { body } => breaks_out(body, label, nested),
+        AstNode::Loop { body, .. } | AstNode::While { body, .. } | AstNode::For { body, .. } => {
+            breaks_out(body, label, true)
+        }
+        _ => false,
+    })
 }
\ No newline at end of file
@@ -0,0 +1,1297 @@
+//! End-to-end tests: every `tests/programs/<name>.ss` is compiled with `-S`
+//! and its assembly compared with `<name>.asm`. On Windows with NASM on the
+//! path, each program is also built and run, and its output compared with
+//! `<name>.out` when that file exists.
+//!
+//! Set `SSC_BLESS=1` to rewrite the `.asm` files from the current compiler.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn programs_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("programs")
+}
+
+/// A scratch directory for one test's output files.
+fn output_dir(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("ssc-{}-{}", test, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn sources() -> Vec<PathBuf> {
+    let mut sources: Vec<PathBuf> = fs::read_dir(programs_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ss"))
+        .collect();
+    sources.sort();
+    sources
+}
+
+/// Runs the compiler from the programs directory, so source paths embedded in
+/// the output don't depend on where the repository is checked out.
+fn ssc(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_ssc"))
+        .args(args)
+        .current_dir(programs_dir())
+        .output()
+        .unwrap()
+}
+
+/// What compiling a snippet produced.
+struct Snippet {
+    result: std::process::Output,
+    asm: Option<String>,
+    /// The syntax tree, when compiled with `--ast-json`.
+    ast_json: Option<String>,
+    /// The dependency rule, when compiled with `--emit-deps`.
+    deps: Option<String>,
+    /// The control-flow graphs, when compiled with `--emit-cfg`.
+    cfg: Option<String>,
+    /// The phase timings, when compiled with `--time-report json`.
+    time_report: Option<String>,
+}
+
+/// Compiles `source` with `-S` from a scratch file, for tests of a single
+/// diagnostic or option rather than a whole program.
+fn compile_snippet(test: &str, source: &str, args: &[&str]) -> Snippet {
+    let out_dir = output_dir(test);
+    let file = out_dir.join("main.ss");
+    fs::write(&file, source).unwrap();
+    let output = out_dir.join("main");
+    let mut all_args = vec![file.to_str().unwrap(), "-S", "-o", output.to_str().unwrap()];
+    all_args.extend_from_slice(args);
+    let snippet = Snippet {
+        result: ssc(&all_args),
+        asm: fs::read_to_string(output.with_extension("asm")).ok(),
+        ast_json: fs::read_to_string(output.with_extension("ast.json")).ok(),
+        deps: fs::read_to_string(output.with_extension("d")).ok(),
+        cfg: fs::read_to_string(output.with_extension("dot")).ok(),
+        time_report: fs::read_to_string(output.with_extension("time.json")).ok(),
+    };
+    fs::remove_dir_all(&out_dir).ok();
+    snippet
+}
+
+fn name_of(source: &Path) -> String {
+    source.file_stem().unwrap().to_string_lossy().into_owned()
+}
+
+#[test]
+fn assembly_matches_golden_files() {
+    let out_dir = output_dir("assembly");
+    let bless = env::var_os("SSC_BLESS").is_some();
+    let mut failures = Vec::new();
+    
+    for source in sources() {
+        let name = name_of(&source);
+        let output = out_dir.join(&name);
+        let file = format!("{}.ss", name);
+        let result = ssc(&[&file, "-S", "-o", output.to_str().unwrap()]);
+        if !result.status.success() {
+            failures.push(format!("{}: compilation failed\n{}", name, String::from_utf8_lossy(&result.stderr)));
+            continue;
+        }
+        
+        let asm = fs::read_to_string(output.with_extension("asm")).unwrap();
+        let golden = source.with_extension("asm");
+        if bless {
+            fs::write(&golden, &asm).unwrap();
+        } else if fs::read_to_string(&golden).ok().as_deref() != Some(asm.as_str()) {
+            failures.push(format!("{}: assembly differs from {}", name, golden.display()));
+        }
+    }
+    
+    fs::remove_dir_all(&out_dir).ok();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn programs_print_expected_output() {
+    let nasm = Command::new("nasm").arg("-v").output().is_ok_and(|o| o.status.success());
+    if !cfg!(windows) || !nasm {
+        eprintln!("skipping: running programs needs Windows and NASM");
+        return;
+    }
+    
+    let out_dir = output_dir("run");
+    let mut failures = Vec::new();
+    for source in sources() {
+        let name = name_of(&source);
+        let Ok(expected) = fs::read_to_string(source.with_extension("out")) else {
+            continue;
+        };
+        let exe = out_dir.join(format!("{}.exe", name));
+        let file = format!("{}.ss", name);
+        let result = ssc(&[&file, "-o", exe.to_str().unwrap()]);
+        if !result.status.success() {
+            failures.push(format!("{}: build failed\n{}", name, String::from_utf8_lossy(&result.stderr)));
+            continue;
+        }
+        
+        let run = Command::new(&exe).output().unwrap();
+        let stdout = String::from_utf8_lossy(&run.stdout).replace("\r\n", "\n");
+        if stdout != expected {
+            failures.push(format!("{}: expected output\n{}\ngot\n{}", name, expected, stdout));
+        }
+    }
+    
+    fs::remove_dir_all(&out_dir).ok();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn quiet_prints_no_progress() {
+    let out_dir = output_dir("quiet");
+    let output = out_dir.join("fib");
+    let result = ssc(&["fib.ss", "-q", "-S", "-o", output.to_str().unwrap()]);
+    fs::remove_dir_all(&out_dir).ok();
+    
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(!stdout.contains("[1/5]"), "unexpected progress output:\n{}", stdout);
+}
+
+#[test]
+fn integer_literal_arguments_adopt_the_parameter_type() {
+    let source = "fn take(x: u8) -> u8 {\n    return x;\n}\n\nfn main() {\n    print(take(200));\n}\n";
+    let result = compile_snippet("literal-arg", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let source = source.replace("200", "300");
+    let result = compile_snippet("literal-arg-range", &source, &[]).result;
+    assert!(!result.status.success());
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Integer literal 300 out of range"), "{}", stderr);
+}
+
+#[test]
+fn map_lists_functions_in_emission_order() {
+    let map_dir = output_dir("map");
+    let map = map_dir.join("main.map");
+    let source = "fn second() -> i32 {\n    return 2;\n}\n\nfn first() -> i32 {\n    return second();\n}\n\nfn main() {\n    print(first());\n}\n";
+    let result = compile_snippet("map-source", source, &["--no-std", "--map", map.to_str().unwrap()]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let contents = fs::read_to_string(&map).unwrap();
+    fs::remove_dir_all(&map_dir).ok();
+    let functions: Vec<&str> = contents.lines()
+        .filter(|line| line.starts_with(".text"))
+        .filter_map(|line| line.split_whitespace().last())
+        .collect();
+    assert_eq!(functions, ["second", "first", "main"], "{}", contents);
+}
+
+#[test]
+fn small_constant_for_loops_are_unrolled_at_o3() {
+    let source = "fn main() {\n    for (i in 0..4) {\n        print(i * 7);\n    }\n}\n";
+    let snippet = compile_snippet("unroll", source, &["-O3", "--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    let main = &asm[asm.find("\nmain:").unwrap()..];
+    assert_eq!(main.matches("call printf").count(), 4, "{}", main);
+    assert!(!main.lines().any(|line| line.trim_start().starts_with('j')), "{}", main);
+}
+
+#[test]
+fn dots_after_integers_lex_as_ranges_floats_or_fields() {
+    let source = "fn main() {
+    let mut total = 0;
+    for (i in 1..5) {
+        total = total + i;
+    }
+    for (j in 1..=5) {
+        total = total + j;
+    }
+    let a = 1.5;
+    let b = 1.;
+    print(total);
+}
+";
+    let snippet = compile_snippet("dots", source, &["--ast-json"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let ast = snippet.ast_json.unwrap();
+    assert_eq!(ast.matches("\"inclusive\":false").count(), 1, "{}", ast);
+    assert_eq!(ast.matches("\"inclusive\":true").count(), 1, "{}", ast);
+    assert!(ast.contains("\"type\":\"f64\",\"value\":1.5"), "{}", ast);
+    assert!(ast.contains("\"type\":\"f64\",\"value\":1.0"), "{}", ast);
+    
+    // `1.foo()` is a method call on the integer 1.
+    let result = compile_snippet("dot-method", "fn main() {\n    let a = 1.foo();\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("method 'foo' on non-struct type i32"), "{}", stderr);
+}
+
+#[test]
+fn unknown_variables_suggest_a_similar_name() {
+    let source = "fn main() {\n    let count = 3;\n    print(conut);\n}\n";
+    let result = compile_snippet("did-you-mean", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Undefined variable 'conut'; did you mean 'count'?"), "{}", stderr);
+}
+
+#[test]
+fn if_without_else_is_only_a_statement() {
+    let source = "fn main() {\n    let c = true;\n    if (c) {\n        print(1);\n    }\n}\n";
+    let result = compile_snippet("if-statement", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let source = "fn main() {\n    let c = true;\n    let x = if (c) { 1 };\n}\n";
+    let result = compile_snippet("if-value", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("An 'if' used as a value must have an 'else' branch"), "{}", stderr);
+}
+
+#[test]
+fn conditions_must_be_boolean_or_integer() {
+    let source = "fn main() {\n    let s = \"text\";\n    while (s) {\n        print(1);\n    }\n}\n";
+    let result = compile_snippet("string-condition", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Condition must be boolean or an integer, found str"), "{}", stderr);
+    
+    let source = "fn main() {\n    if (1.5) {\n        print(1);\n    }\n}\n";
+    let result = compile_snippet("float-condition", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Condition must be boolean or an integer, found f64"), "{}", stderr);
+}
+
+#[test]
+fn dump_symbols_lists_frame_slots_of_locals() {
+    let source = "fn sum(values: [i32; 3], scale: i64) -> i64 {\n    let mut total: i64 = 0;\n    for (i in 0..3) {\n        let value: i32 = values[i];\n        total = total + value;\n    }\n    return total * scale;\n}\n\nfn main() {\n    let pair = (1, true);\n    print(sum([1, 2, 3], 2));\n}\n";
+    let snippet = compile_snippet("frame-locals", source, &["--no-std", "--dump-symbols"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let stdout = String::from_utf8_lossy(&snippet.result.stdout);
+    let frames = &stdout[stdout.find("frame sum").unwrap()..];
+    assert!(frames.starts_with("frame sum
+    [rbp-8] values: [i32; 3] (by address)
+    [rbp-16] scale: i64
+    [rbp-24] total: i64
+    [rbp-32] i: i32
+    [rbp-48] value: i32
+frame main
+    [rbp-16] pair: (i32, bool)
+"), "{}", frames);
+}
+
+#[test]
+fn u64_literals_above_i64_max_are_hex_immediates() {
+    let source = "fn main() {\n    let max: u64 = 0xFFFFFFFFFFFFFFFF;\n    let high: u64 = 9223372036854775808;\n    print(max - high);\n}\n";
+    let snippet = compile_snippet("u64-literal", source, &[]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    mov rax, 0xFFFFFFFFFFFFFFFF\n"), "{}", asm);
+    assert!(asm.contains("    mov rax, 0x8000000000000000\n"), "{}", asm);
+    
+    let source = "fn main() {\n    let x: i64 = 0xFFFFFFFFFFFFFFFF;\n}\n";
+    let result = compile_snippet("u64-literal-range", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Integer literal 18446744073709551615 out of range for i64"), "{}", stderr);
+}
+
+#[test]
+fn unsigned_operands_compare_and_divide_unsigned() {
+    let source = "fn main() {\n    let max: u64 = 0xFFFFFFFFFFFFFFFF;\n    print(max > 1);\n    print(max / 10);\n    let n: i64 = -7;\n    print(n / 2);\n}\n";
+    let snippet = compile_snippet("unsigned-ops", source, &[]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    seta al\n"), "{}", asm);
+    assert!(asm.contains("    xor rdx, rdx\n    div rcx\n"), "{}", asm);
+    // Signed division sign-extends the dividend into rdx.
+    assert!(asm.contains("    cqo\n    idiv rcx\n"), "{}", asm);
+}
+
+#[test]
+fn skipped_if_regions_ignore_endif_in_comments_and_strings() {
+    let source = "fn main() {\n#if DEBUG\n    // #endif\n    /* #endif */\n    let s: str = \"#endif\";\n    let c: char = '#';\n    print(missing);\n#endif\n    print(1);\n}\n";
+    let result = compile_snippet("if-skip", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let result = compile_snippet("if-skip-defined", source, &["-D", "DEBUG"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("missing"), "{}", stderr);
+}
+
+#[test]
+fn unknown_escapes_are_errors() {
+    let result = compile_snippet("unknown-escape", "fn main() {\n    let s: str = \"a\\qb\";\n}\n", &[]).result;
+    assert!(!result.status.success());
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Unknown escape '\\q'"), "{}", stderr);
+}
+
+#[test]
+fn max_errors_truncates_and_summarizes() {
+    let source = "fn f() {\n    print(a);\n}\n\nfn g() {\n    print(b);\n}\n\nfn main() {\n    print(c);\n}\n";
+    let result = compile_snippet("max-errors", source, &["--max-errors", "1"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Undefined variable 'a'"), "{}", stderr);
+    assert!(!stderr.contains("Undefined variable 'b'"), "{}", stderr);
+    assert!(stderr.contains("... and 2 more errors"), "{}", stderr);
+    
+    // JSON output stops at the limit without a summary line.
+    let result = compile_snippet("max-errors-json", source, &["--max-errors", "1", "--message-format", "json", "-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert_eq!(stderr.lines().count(), 1, "{}", stderr);
+    assert!(stderr.starts_with("{\"severity\":\"error\""), "{}", stderr);
+}
+
+#[test]
+fn json_diagnostics_report_severity_message_position_and_phase() {
+    let source = "fn main() {\n    let x = 1 $ 2;\n}\n";
+    let result = compile_snippet("json-lexer", source, &["--message-format", "json", "-q"]).result;
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr).trim_end(),
+        r#"{"severity":"error","message":"Unexpected character '$'","line":2,"column":15,"phase":"lexer"}"#
+    );
+    
+    let source = "fn main() {\n    print(a);\n}\n";
+    let result = compile_snippet("json-semantic", source, &["--message-format", "json", "-q"]).result;
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr).trim_end(),
+        r#"{"severity":"error","message":"Undefined variable 'a'","line":2,"column":11,"phase":"semantic"}"#
+    );
+}
+
+#[test]
+fn diagnostics_show_the_source_line_under_a_gutter_with_a_caret() {
+    let source = "fn main() {\n\tprint(a);\n}\n";
+    let result = compile_snippet("render-plain", source, &["-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    // Piped stderr isn't a terminal, so the output is plain.
+    assert!(!stderr.contains('\x1b'), "{}", stderr);
+    assert!(stderr.starts_with("error: Undefined variable 'a'\n --> "), "{}", stderr);
+    assert!(stderr.ends_with("main.ss:2:8\n  |\n2 | \tprint(a);\n  | \t      ^\n"), "{}", stderr);
+    
+    let result = compile_snippet("render-color", source, &["-q", "--color", "always"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.starts_with("\x1b[1;31merror\x1b[0m: "), "{}", stderr);
+}
+
+#[test]
+fn no_color_disables_colors_on_a_terminal() {
+    // `script` runs the compiler on a pseudo-terminal.
+    let script = Command::new("script").arg("-V").output().is_ok_and(|o| o.status.success());
+    if !cfg!(target_os = "linux") || !script {
+        eprintln!("skipping: needs Linux and script(1)");
+        return;
+    }
+    
+    let out_dir = output_dir("no-color");
+    let file = out_dir.join("main.ss");
+    fs::write(&file, "fn main() {\n    print(a);\n}\n").unwrap();
+    let command = format!("{} {} -q -S -o {}", env!("CARGO_BIN_EXE_ssc"), file.display(), out_dir.join("main").display());
+    let on_terminal = |no_color: bool| {
+        let mut script = Command::new("script");
+        script.args(["-qec", &command, "/dev/null"]).env_remove("NO_COLOR");
+        if no_color {
+            script.env("NO_COLOR", "1");
+        }
+        String::from_utf8_lossy(&script.output().unwrap().stdout).into_owned()
+    };
+    let colored = on_terminal(false);
+    let plain = on_terminal(true);
+    fs::remove_dir_all(&out_dir).ok();
+    
+    assert!(colored.contains("\x1b[1;31merror"), "{}", colored);
+    assert!(plain.starts_with("error: Undefined variable 'a'"), "{}", plain);
+    assert!(!plain.contains('\x1b'), "{}", plain);
+}
+
+#[test]
+fn temp_requires_run() {
+    let result = ssc(&["fib.ss", "--temp"]);
+    assert!(!result.status.success());
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("--temp requires --run"), "{}", stderr);
+}
+
+#[test]
+fn run_exits_with_the_program_exit_code() {
+    let nasm = Command::new("nasm").arg("-v").output().is_ok_and(|o| o.status.success());
+    if !cfg!(windows) || !nasm {
+        eprintln!("skipping: running programs needs Windows and NASM");
+        return;
+    }
+    
+    let out_dir = output_dir("run-exit");
+    let file = out_dir.join("main.ss");
+    fs::write(&file, "fn main() -> i32 {\n    return 7;\n}\n").unwrap();
+    let exe = out_dir.join("main.exe");
+    let result = ssc(&[file.to_str().unwrap(), "-o", exe.to_str().unwrap(), "--run", "--temp", "-q"]);
+    let removed = !exe.exists();
+    fs::remove_dir_all(&out_dir).ok();
+    
+    assert_eq!(result.status.code(), Some(7), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(removed, "--temp left the executable behind");
+}
+
+#[test]
+fn werror_fails_the_build_on_warnings() {
+    let source = "fn main() {\n    while (false) {\n        print(1);\n    }\n}\n";
+    let snippet = compile_snippet("warnings", source, &["-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    
+    let snippet = compile_snippet("werror", source, &["-q", "-Werror"]);
+    assert!(!snippet.result.status.success());
+    let stderr = String::from_utf8_lossy(&snippet.result.stderr);
+    assert!(stderr.contains("warning: `while` condition is always false"), "{}", stderr);
+    assert!(stderr.contains("1 warning treated as errors"), "{}", stderr);
+}
+
+#[test]
+fn emit_deps_lists_the_source_file() {
+    let snippet = compile_snippet("deps", "import std;\n\nfn main() {\n    print(1);\n}\n", &["--emit-deps"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let deps = snippet.deps.unwrap();
+    let (target, sources) = deps.trim_end().split_once(": ").unwrap();
+    assert!(target.ends_with("main.asm"), "{}", deps);
+    assert!(sources.ends_with("main.ss"), "{}", deps);
+    assert!(!sources.contains(' '), "{}", deps);
+}
+
+#[test]
+fn bool_locals_are_stored_as_bytes() {
+    let source = "fn main() {\n    let a: i32 = 3;\n    let b: bool = a < 5;\n    let c: bool = b;\n    print(c);\n}\n";
+    let snippet = compile_snippet("bool-byte", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    // `b` and `c` share the slot after `a`.
+    assert!(asm.contains("    mov [rbp-9], al\n    movzx eax, byte [rbp-9]\n    mov [rbp-10], al\n"), "{}", asm);
+}
+
+#[test]
+fn else_if_arms_have_their_own_scopes() {
+    let source = "fn main() {\n    let n = 3;\n    if (n < 0) {\n        let seen = 1;\n    } else if (n < 5) {\n        print(seen);\n    }\n}\n";
+    let result = compile_snippet("else-if-scope", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Undefined variable 'seen'"), "{}", stderr);
+    
+    let source = "fn main() {\n    let n = 3;\n    let x = if (n < 0) { 1 } else if (n < 5) { 2 };\n}\n";
+    let result = compile_snippet("else-if-value", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("An 'if' used as a value must have an 'else' branch"), "{}", stderr);
+}
+
+#[test]
+fn frames_past_the_size_limit_are_errors() {
+    // 300 arrays of 8 MiB add up to more than an i32 holds.
+    let mut source = String::from("fn main() {\n");
+    for i in 0..300 {
+        source.push_str(&format!("    let a{}: [i64; 1048576];\n", i));
+    }
+    source.push_str("    print(1);\n}\n");
+    let result = compile_snippet("frame-limit", &source, &["--no-std"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Stack frame of function 'main' exceeds the 1073741824 byte limit"), "{}", stderr);
+    
+    let source = "fn main() {\n    let a: [[[[i64; 1048576]; 1048576]; 1048576]; 1048576];\n    print(1);\n}\n";
+    let result = compile_snippet("frame-limit-nested", source, &["--no-std"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Stack frame of function 'main' exceeds the 1073741824 byte limit"), "{}", stderr);
+}
+
+#[test]
+fn elf64_target_declares_section_attributes() {
+    let source = "static mut count: i32 = 1;\nstatic mut buffer: [i64; 4];\n\nfn main() {\n    print(count);\n}\n";
+    let snippet = compile_snippet("elf64", source, &["--target", "elf64"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("section .data progbits alloc noexec write align=8\n"), "{}", asm);
+    assert!(asm.contains("section .bss nobits alloc noexec write align=8\n"), "{}", asm);
+    assert!(asm.contains("section .text progbits alloc exec nowrite align=16\n"), "{}", asm);
+}
+
+#[test]
+fn negative_literals_reach_the_bottom_of_the_range() {
+    let source = "fn main() {\n    let x: i8 = -128;\n    print(x);\n}\n";
+    let snippet = compile_snippet("i8-min", source, &[]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    assert!(snippet.asm.unwrap().contains("    mov rax, -128\n"));
+    
+    let source = "fn main() {\n    let x: i8 = -129;\n}\n";
+    let result = compile_snippet("i8-below-min", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Integer literal -129 out of range for i8 (-128..=127)"), "{}", stderr);
+}
+
+#[test]
+fn undefined_loop_labels_are_errors() {
+    let source = "fn main() {\n    'inner: loop {\n        break 'outer;\n    }\n}\n";
+    let result = compile_snippet("loop-label", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Undefined loop label 'outer'"), "{}", stderr);
+}
+
+#[test]
+fn array_repeat_counts_are_range_checked() {
+    let result = compile_snippet("repeat-ok", "fn main() {\n    let a = [0; 1000];\n    print(a[999]);\n}\n", &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let result = compile_snippet("repeat-large", "fn main() {\n    let a = [0; 100000000000];\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("array too large: 100000000000 elements (maximum is 1048576)"), "{}", stderr);
+    
+    let result = compile_snippet("repeat-negative", "fn main() {\n    let a = [0; -1];\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Array repeat count must not be negative, got -1"), "{}", stderr);
+}
+
+#[test]
+fn literals_past_i32_default_to_i64() {
+    let source = "fn main() {\n    let x = 3000000000;\n    print(x);\n}\n";
+    let snippet = compile_snippet("wide-literal", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [1] x: i64\n"), "{}", symbols);
+    assert!(snippet.asm.unwrap().contains("    mov rax, 3000000000\n"));
+}
+
+#[test]
+fn array_arguments_must_match_the_parameter_length() {
+    let source = "fn sum(values: [i32; 3]) -> i32 {\n    return values[0];\n}\n\nfn main() {\n    let a = [1, 2];\n    print(sum(a));\n}\n";
+    let result = compile_snippet("array-argument", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("expected [i32; 3], got [i32; 2]"), "{}", stderr);
+}
+
+#[test]
+fn continue_in_a_for_loop_jumps_to_the_increment() {
+    let source = "fn main() {\n    for (i in 0..10) {\n        if (i % 2 == 0) {\n            continue;\n        }\n        print(i);\n    }\n}\n";
+    let snippet = compile_snippet("for-continue", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    let increment = asm.find("    inc rax\n").unwrap();
+    let label = asm[..increment].rsplit_once("\nL").unwrap().1.split(':').next().unwrap();
+    assert!(asm.contains(&format!("    jmp L{}\n", label)), "{}", asm);
+}
+
+#[test]
+fn dump_symbols_lists_signatures_and_variable_types() {
+    let source = "fn scale(x: i32, factor: i64) -> i64 {\n    let wide: i64 = factor;\n    return wide;\n}\n\nfn main() {\n    let flag = true;\n    if (flag) {\n        let ratio = 1.5;\n        print(ratio);\n    }\n}\n";
+    let snippet = compile_snippet("dump-symbols", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("fn scale(i32, i64) -> i64\n    [1] x: i32\n    [1] factor: i64\n    [1] wide: i64\n"), "{}", symbols);
+    assert!(symbols.contains("fn main()\n    [1] flag: bool\n    [2] ratio: f64\n"), "{}", symbols);
+}
+
+#[test]
+fn duplicate_functions_name_the_first_definition() {
+    let source = "fn foo() {\n}\n\nfn foo() {\n}\n\nfn main() {\n}\n";
+    let result = compile_snippet("duplicate-fn", source, &["--message-format", "json", "-q"]).result;
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr).trim_end(),
+        r#"{"severity":"error","message":"Function 'foo' is already defined at line 1","line":4,"column":4,"phase":"semantic"}"#
+    );
+}
+
+#[test]
+fn powers_of_two_become_shifts_at_o1() {
+    let source = "fn main() {\n    let x: i64 = 7;\n    let u: u64 = 40;\n    print(x * 8);\n    print(u / 4);\n}\n";
+    let snippet = compile_snippet("power-of-two", source, &["--no-std", "-O1"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    shl rax, 3\n"), "{}", asm);
+    assert!(asm.contains("    shr rax, 2\n"), "{}", asm);
+    assert!(!asm.contains("imul") && !asm.contains("div rcx"), "{}", asm);
+    
+    let snippet = compile_snippet("power-of-two-o0", source, &["--no-std", "-O0"]);
+    assert!(snippet.asm.unwrap().contains("    imul rax, rcx\n"));
+}
+
+#[test]
+fn tokens_after_a_multiline_block_comment_keep_their_column() {
+    let source = "fn main() {\n    /* one\n       two */ let x = $;\n}\n";
+    let result = compile_snippet("comment-column", source, &["--message-format", "json", "-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains(r#""line":3,"column":23"#), "{}", stderr);
+}
+
+#[test]
+fn type_aliases_are_interchangeable_with_their_target() {
+    let source = "type Int = i32;\n\nfn twice(n: Int) -> Int {\n    return n * 2;\n}\n\nfn main() {\n    let x: Int = 4;\n    let y: i32 = twice(x);\n    print(y);\n}\n";
+    let result = compile_snippet("type-alias", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let source = "type Int = i32;\n\nfn main() {\n    let x: Int = true;\n}\n";
+    let result = compile_snippet("type-alias-mismatch", source, &[]).result;
+    assert!(!result.status.success());
+}
+
+#[test]
+fn functions_whose_result_is_always_dropped_are_warned_about() {
+    let source = "fn f() -> i32 {\n    return 1;\n}\n\nfn main() {\n    f();\n    f();\n}\n";
+    let result = compile_snippet("unused-result", source, &["-q"]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("warning: Result of function 'f' is never used"), "{}", stderr);
+    
+    let source = "fn f() -> i32 {\n    return 1;\n}\n\nfn main() {\n    f();\n    print(f());\n}\n";
+    let result = compile_snippet("used-result", source, &["-q"]).result;
+    assert!(!String::from_utf8_lossy(&result.stderr).contains("never used"));
+}
+
+#[test]
+fn logical_operators_need_bool_operands() {
+    let result = compile_snippet("logical-ints", "fn main() {\n    let x = 1 && 2;\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Operator '&&' requires bool operands, got i32"), "{}", stderr);
+    
+    let source = "fn main() {\n    let a = 1;\n    let b = 2;\n    let x = (a < b) && (b < a);\n    print(x);\n}\n";
+    let result = compile_snippet("logical-bools", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+}
+
+#[test]
+fn based_literals_are_checked_against_the_target_range() {
+    let source = "fn main() {\n    let m: u8 = 0xFF;\n    let b: u8 = 0b1000_0001;\n    let o: i32 = 0o17;\n    print(m + b);\n    print(o);\n}\n";
+    let result = compile_snippet("based-literals", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let result = compile_snippet("based-literal-range", "fn main() {\n    let n: i8 = 0xFF;\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Integer literal 255 out of range for i8 (-128..=127)"), "{}", stderr);
+}
+
+#[test]
+fn lexing_stops_at_the_first_error() {
+    let source = "fn main() {\n    let a = $;\n    let b = @;\n}\n";
+    let result = compile_snippet("lexer-stops", source, &["--message-format", "json", "--max-errors", "0", "-q"]).result;
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr).trim_end(),
+        r#"{"severity":"error","message":"Unexpected character '$'","line":2,"column":13,"phase":"lexer"}"#
+    );
+}
+
+#[test]
+fn crlf_and_lone_cr_each_end_one_line() {
+    for (name, newline) in [("crlf", "\r\n"), ("cr", "\r")] {
+        let source = ["fn main() {", "    let a = 1;", "    let b = $;", "}", ""].join(newline);
+        let result = compile_snippet(name, &source, &["--message-format", "json", "-q"]).result;
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        assert!(stderr.contains(r#""line":3,"column":13"#), "{}: {}", name, stderr);
+    }
+}
+
+#[test]
+fn assert_branches_to_an_abort_and_panic_always_aborts() {
+    let source = "fn main() {\n    let ok = 1 < 2;\n    assert(ok, \"x\");\n    panic(\"boom\");\n}\n";
+    let snippet = compile_snippet("assert-panic", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains(":3: assertion failed: %s\\n`"), "{}", asm);
+    assert!(asm.contains(":4: panic: %s\\n`"), "{}", asm);
+    // The assert jumps over its abort; the panic's abort is unconditional.
+    assert_eq!(asm.matches("    call ExitProcess\n").count(), 2, "{}", asm);
+    assert_eq!(asm.matches("    jnz L").count(), 1, "{}", asm);
+    
+    let result = compile_snippet("assert-message", "fn main() {\n    assert(true, 3);\n}\n", &[]).result;
+    assert!(!result.status.success());
+}
+
+#[test]
+fn constant_conditions_warn_and_lose_the_dead_branch_at_o2() {
+    let source = "fn main() {\n    if (true) {\n        print(1);\n    } else {\n        print(2);\n    }\n}\n";
+    let snippet = compile_snippet("constant-if", source, &["--no-std", "-O2", "-q"]);
+    let stderr = String::from_utf8_lossy(&snippet.result.stderr);
+    assert!(stderr.contains("warning: `if` condition is always true"), "{}", stderr);
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    mov rax, 1\n") && !asm.contains("    mov rax, 2\n"), "{}", asm);
+    
+    let snippet = compile_snippet("constant-if-o0", source, &["--no-std", "-q"]);
+    assert!(snippet.asm.unwrap().contains("    mov rax, 2\n"));
+}
+
+#[test]
+fn leaf_functions_omit_the_frame_pointer_at_o2() {
+    let source = "fn add(a: i64, b: i64) -> i64 {\n    let c = a + b;\n    return c;\n}\n\nfn main() {\n    print(add(1, 2));\n}\n";
+    let snippet = compile_snippet("leaf", source, &["--no-std", "-O2"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    let add = &asm[asm.find("add:\n").unwrap()..asm.find("main:\n").unwrap()];
+    assert!(!add.contains("rbp"), "{}", add);
+    assert!(add.contains("    sub rsp, 32\n") && add.contains("    add rsp, 32\n    ret\n"), "{}", add);
+    // main calls printf, so it keeps its frame pointer.
+    assert!(asm[asm.find("main:\n").unwrap()..].starts_with("main:\n    push rbp\n"), "{}", asm);
+}
+
+#[test]
+fn bare_blocks_scope_their_variables() {
+    let source = "fn main() {\n    {\n        let inner = 1;\n        print(inner);\n    }\n    print(inner);\n}\n";
+    let result = compile_snippet("bare-block", source, &["--message-format", "json", "-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains(r#""message":"Undefined variable 'inner'","line":6"#), "{}", stderr);
+}
+
+#[test]
+fn non_variable_assignment_targets_are_parse_errors() {
+    let source = "fn main() {\n    let a = 1;\n    let b = 2;\n    (a + b) = 1;\n}\n";
+    let result = compile_snippet("assign-sum", source, &["--message-format", "json", "-q"]).result;
+    assert_eq!(
+        String::from_utf8_lossy(&result.stderr).trim_end(),
+        r#"{"severity":"error","message":"Invalid assignment target","line":4,"column":5,"phase":"parser"}"#
+    );
+    
+    let source = "fn f() -> i32 {\n    return 1;\n}\n\nfn main() {\n    f() = 2;\n}\n";
+    let result = compile_snippet("assign-call", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Invalid assignment target"), "{}", stderr);
+}
+
+#[test]
+fn the_prelude_provides_pow_unless_disabled_or_replaced() {
+    let source = "fn main() {\n    print(pow(2, 10));\n}\n";
+    let snippet = compile_snippet("prelude", source, &["-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    assert!(snippet.asm.unwrap().contains("\npow:\n"));
+    
+    let result = compile_snippet("no-std", source, &["--no-std", "-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Undefined function 'pow'"), "{}", stderr);
+    
+    let source = "fn pow(a: i32, b: i32) -> i32 {\n    return a;\n}\n\nfn main() {\n    print(pow(2, 10));\n}\n";
+    let snippet = compile_snippet("prelude-replaced", source, &["-q"]);
+    let stderr = String::from_utf8_lossy(&snippet.result.stderr);
+    assert!(stderr.contains("warning: Function 'pow' replaces the prelude definition"), "{}", stderr);
+    assert_eq!(snippet.asm.unwrap().matches("\npow:\n").count(), 1);
+}
+
+#[test]
+fn empty_array_literals_take_their_type_from_the_annotation() {
+    let result = compile_snippet("empty-array", "fn main() {\n    let a: [f64; 0] = [];\n}\n", &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let result = compile_snippet("empty-array-untyped", "fn main() {\n    let a = [];\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Cannot infer the element type of an empty array literal; add a type annotation"), "{}", stderr);
+}
+
+#[test]
+fn arrays_of_different_lengths_cannot_be_compared() {
+    let result = compile_snippet("array-compare", "fn main() {\n    let a = [1, 2] == [1, 2, 3];\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Cannot compare arrays of different types: [i32; 2] == [i32; 3]"), "{}", stderr);
+}
+
+#[test]
+fn local_functions_get_their_own_label_and_cannot_capture() {
+    let source = "fn main() {\n    fn helper(n: i32) -> i32 {\n        return n * 2;\n    }\n    print(helper(4));\n}\n";
+    let snippet = compile_snippet("local-fn", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("\nmain.helper:\n"), "{}", asm);
+    assert!(asm.contains("    call main.helper\n"), "{}", asm);
+    
+    let source = "fn main() {\n    let x = 1;\n    fn helper() -> i32 {\n        return x;\n    }\n}\n";
+    let result = compile_snippet("local-fn-capture", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Local function 'helper' cannot capture 'x' from the enclosing function"), "{}", stderr);
+}
+
+#[test]
+fn block_locals_give_their_slot_back_when_the_block_ends() {
+    let source = "fn main() {\n    let n = 3;\n    if (n > 1) {\n        let inner: i64 = 5;\n        print(inner);\n    }\n    let after: i64 = 7;\n    print(after);\n}\n";
+    let snippet = compile_snippet("scope-slots", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [rbp-24] inner: i64\n    [rbp-24] after: i64\n"), "{}", symbols);
+}
+
+#[test]
+fn parse_errors_describe_tokens_by_their_text() {
+    let source = "fn main() {\n    let x = 1\n}\n";
+    let result = compile_snippet("token-text", source, &["-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Expected ';', got '}'"), "{}", stderr);
+    assert!(!stderr.contains("Semicolon"), "{}", stderr);
+}
+
+#[test]
+fn emit_cfg_gives_a_condition_block_two_branch_edges() {
+    let source = "fn pick(n: i32) {\n    if (n < 2) {\n        print(1);\n    } else {\n        print(2);\n    }\n}\n\nfn main() {\n    pick(1);\n}\n";
+    let snippet = compile_snippet("cfg", source, &["--no-std", "--emit-cfg"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let cfg = snippet.cfg.unwrap();
+    assert!(cfg.starts_with("digraph cfg {\n"), "{}", cfg);
+    assert!(cfg.contains("subgraph \"cluster_pick\""), "{}", cfg);
+    assert!(cfg.contains("\"pick.0\" -> \"pick.2\" [label=\"taken\"];\n"), "{}", cfg);
+    assert!(cfg.contains("\"pick.0\" -> \"pick.1\" [label=\"not taken\"];\n"), "{}", cfg);
+}
+
+#[test]
+fn semantic_errors_point_at_the_offending_node() {
+    let cases = [
+        ("position-assign", "fn main() {\n    let a = 1;\n\n    y = a;\n}\n", r#""line":4,"column":5"#),
+        ("position-binary", "fn main() {\n    let a = 1;\n    let b = a + true;\n}\n", r#""line":3,"column":15"#),
+        ("position-call", "fn main() {\n    let a = 1;\n    print(missing(a));\n}\n", r#""line":3,"column":11"#),
+    ];
+    for (name, source, position) in cases {
+        let result = compile_snippet(name, source, &["--message-format", "json", "-q"]).result;
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        assert!(stderr.contains(position), "{}: {}", name, stderr);
+    }
+}
+
+#[test]
+fn variables_must_be_assigned_before_they_are_read() {
+    let result = compile_snippet("unassigned", "fn main() {\n    let x: i32;\n    print(x);\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Variable 'x' is used before being assigned"), "{}", stderr);
+    
+    let source = "fn main() {\n    let mut x: i32;\n    x = 4;\n    print(x);\n}\n";
+    let result = compile_snippet("assigned", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let source = "fn main() {\n    let n = 1;\n    let mut x: i32;\n    if (n > 0) {\n        x = 4;\n    }\n    print(x);\n}\n";
+    let result = compile_snippet("assigned-on-one-path", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Variable 'x' is used before being assigned"), "{}", stderr);
+}
+
+#[test]
+fn struct_literals_need_every_field() {
+    let source = "struct P {\n    x: i32,\n    y: i32,\n}\n\nfn main() {\n    let p = P { y: 2, x: 1 };\n    print(p.x);\n}\n";
+    let result = compile_snippet("struct-literal", source, &[]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    
+    let source = "struct P {\n    x: i32,\n    y: i32,\n}\n\nfn main() {\n    let p = P { x: 1 };\n}\n";
+    let result = compile_snippet("struct-literal-missing", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Missing field 'y' in 'P' literal"), "{}", stderr);
+}
+
+#[test]
+fn constant_while_loops_are_rewritten_at_o1() {
+    let source = "fn main() {\n    let mut n = 0;\n    while (true) {\n        n = n + 1;\n        if (n > 3) {\n            break;\n        }\n    }\n    while (false) {\n        print(99);\n    }\n    print(n);\n}\n";
+    let snippet = compile_snippet("constant-while", source, &["--no-std", "-O1", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    // The only test left is the `if`; `while (false)` emits nothing.
+    assert_eq!(asm.matches("    test rax, rax\n").count(), 1, "{}", asm);
+    assert!(!asm.contains("mov rax, 99"), "{}", asm);
+    
+    let snippet = compile_snippet("constant-while-o0", source, &["--no-std", "-q"]);
+    assert!(snippet.asm.unwrap().contains("mov rax, 99"));
+}
+
+#[test]
+fn malformed_input_is_a_lexer_error_not_a_crash() {
+    let inputs = ["\"abc", "'", "'\\", "\"\\", "/* open", "0x", "0b2", "1e", "'ab'", "\"\\u{110000}\"", "99999999999999999999999"];
+    for (i, input) in inputs.iter().enumerate() {
+        let source = format!("fn main() {{\n    let x = {}", input);
+        let result = compile_snippet(&format!("garbage-{}", i), &source, &["-q"]).result;
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        assert_eq!(result.status.code(), Some(1), "{:?}: {}", input, stderr);
+        assert!(stderr.starts_with("error: "), "{:?}: {}", input, stderr);
+    }
+}
+
+#[test]
+fn min_max_and_abs_compile_to_conditional_moves() {
+    let source = "fn main() {\n    let a = 1;\n    let b = 5;\n    print(max(a, b));\n    print(abs(a));\n}\n";
+    let snippet = compile_snippet("intrinsics", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    cmovl rax, rcx\n"), "{}", asm);
+    assert!(asm.contains("    cmovns rax, rcx\n"), "{}", asm);
+    assert!(!asm.contains("call max") && !asm.contains("call abs"), "{}", asm);
+    
+    let source = "fn main() {\n    let a = 1;\n    let b = 2.0;\n    print(max(a, b));\n}\n";
+    let result = compile_snippet("intrinsic-types", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Arguments to 'max' must have the same type, got i32 and f64"), "{}", stderr);
+}
+
+#[test]
+fn trailing_expressions_must_match_the_return_type() {
+    let result = compile_snippet("trailing-type", "fn f() -> i32 {\n    true\n}\n\nfn main() {\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Return type mismatch: expected I32, got Bool"), "{}", stderr);
+}
+
+#[test]
+fn a_trailing_if_else_returns_the_value_of_each_arm() {
+    let source = "fn pick(c: bool) -> i32 {\n    if (c) {\n        1\n    } else {\n        2\n    }\n}\n\nfn main() {\n    print(pick(true));\n}\n";
+    let snippet = compile_snippet("trailing-if", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    let pick = &asm[asm.find("\npick:\n").unwrap()..asm.find("\nmain:\n").unwrap()];
+    assert!(pick.contains("    jz L0\n    mov rax, 1\n    leave\n    ret\n"), "{}", pick);
+    assert!(pick.contains("L0:\n    mov rax, 2\n    leave\n    ret\n"), "{}", pick);
+    
+    let source = "fn pick(c: bool) -> i32 {\n    if (c) {\n        1\n    } else {\n        print(2);\n    }\n}\n\nfn main() {\n}\n";
+    let result = compile_snippet("trailing-if-no-value", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Function 'pick' must return a value of type i32 on every path"), "{}", stderr);
+}
+
+#[test]
+fn dead_stores_are_warned_about_and_dropped_at_o2() {
+    let source = "fn f() -> i32 {\n    return 1;\n}\n\nfn main() {\n    let mut x = f();\n    x = 2;\n    let mut y = 3;\n    y = 4;\n    print(x + y);\n}\n";
+    let snippet = compile_snippet("dead-store", source, &["--no-std", "-O2", "-q"]);
+    let stderr = String::from_utf8_lossy(&snippet.result.stderr);
+    assert!(stderr.contains("warning: value assigned to 'x' is never read"), "{}", stderr);
+    assert!(stderr.contains("warning: value assigned to 'y' is never read"), "{}", stderr);
+    let asm = snippet.asm.unwrap();
+    // The call is kept for its side effects; the constant store is gone.
+    assert!(asm.contains("    call f\n"), "{}", asm);
+    assert!(!asm.contains("    mov rax, 3\n"), "{}", asm);
+}
+
+#[test]
+fn float_division_uses_divsd_and_remainder_calls_fmod() {
+    let source = "fn main() {\n    let a = 5.0;\n    print(a / 2.0);\n    print(a % 2.0);\n}\n";
+    let snippet = compile_snippet("float-division", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    divsd xmm0, xmm1\n"), "{}", asm);
+    assert!(asm.contains("    extern fmod\n") && asm.contains("    call fmod\n"), "{}", asm);
+}
+
+#[test]
+fn print_frame_sizes_reports_large_arrays() {
+    let source = "fn buffer() -> i64 {\n    let zero: i64 = 0;\n    let data = [zero; 100];\n    return data[0];\n}\n\nfn main() {\n    print(buffer());\n}\n";
+    let snippet = compile_snippet("frame-sizes", source, &["--no-std", "--print-frame-sizes", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let table = String::from_utf8_lossy(&snippet.result.stdout);
+    let row = table.lines().find(|line| line.starts_with("buffer ")).unwrap();
+    let bytes: i32 = row.split_whitespace().nth(1).unwrap().parse().unwrap();
+    assert!(bytes >= 800, "{}", table);
+}
+
+#[test]
+fn a_byte_order_mark_and_leading_comment_keep_line_numbers() {
+    let source = "\u{feff}/* leading\n   comment */\nmodule demo;\n\nfn main() {\n    let x = $;\n}\n";
+    let result = compile_snippet("bom", source, &["--message-format", "json", "-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains(r#""message":"Unexpected character '$'","line":6,"column":13"#), "{}", stderr);
+    
+    let result = compile_snippet("late-module", "fn main() {\n}\n\nmodule late;\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("The module declaration must come before any other item"), "{}", stderr);
+}
+
+#[test]
+fn variadic_float_arguments_go_in_both_registers() {
+    let snippet = compile_snippet("float-print", "fn main() {\n    print(3.14);\n}\n", &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    mov rdx, rax\n    movq xmm1, rax\n"), "{}", asm);
+    
+    let source = "fn half(x: f64) -> f64 {\n    return x / 2.0;\n}\n\nfn main() {\n    print(half(3.0));\n}\n";
+    let snippet = compile_snippet("float-argument", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    assert!(snippet.asm.unwrap().contains("xmm0"));
+}
+
+#[test]
+fn function_attributes_rename_end_and_warn() {
+    let source = "@export(\"entry_point\")\nfn helper() -> i32 {\n    return 1;\n}\n\n@noreturn\nfn die() {\n    panic(\"x\");\n}\n\n@shiny\nfn main() {\n    print(helper());\n}\n";
+    let snippet = compile_snippet("attributes", source, &["--no-std", "-q"]);
+    let stderr = String::from_utf8_lossy(&snippet.result.stderr);
+    assert!(stderr.contains("warning: Unknown attribute '@shiny' on function 'main' is ignored"), "{}", stderr);
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    global entry_point\n") && asm.contains("\nentry_point:\n"), "{}", asm);
+    assert!(asm.contains("    call entry_point\n"), "{}", asm);
+    let die = &asm[asm.find("\ndie:\n").unwrap()..asm.find("\nmain:\n").unwrap()];
+    assert!(!die.contains("ret\n"), "{}", die);
+}
+
+#[test]
+fn array_lengths_can_be_inferred_but_must_agree() {
+    let source = "fn main() {\n    let a: [i32; _] = [1, 2, 3];\n    let b = [4, 5];\n    print(a[2] + b[1]);\n}\n";
+    let snippet = compile_snippet("inferred-length", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [1] a: [i32; 3]\n    [1] b: [i32; 2]\n"), "{}", symbols);
+    
+    let result = compile_snippet("length-mismatch", "fn main() {\n    let b: [i32; 2] = [1, 2, 3];\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Type mismatch: expected Array(I32, 2), got Array(I32, 3)"), "{}", stderr);
+}
+
+#[test]
+fn loop_values_take_the_type_of_their_break() {
+    let source = "fn main() {\n    let mut i = 0;\n    let n = loop {\n        i = i + 1;\n        if (i > 2) {\n            break 5000000000;\n        }\n    };\n    print(n);\n}\n";
+    let snippet = compile_snippet("loop-value", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [1] n: i64\n"), "{}", symbols);
+    
+    let result = compile_snippet("loop-no-value", "fn main() {\n    let n = loop {\n        break;\n    };\n}\n", &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("A loop used as a value must leave through a 'break' with a value"), "{}", stderr);
+}
+
+#[test]
+fn large_frames_are_probed_with_chkstk() {
+    let snippet = compile_snippet("chkstk", "fn main() {\n    let big = [0; 2048];\n    print(big[0]);\n}\n", &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("main:\n    push rbp\n    mov rbp, rsp\n    mov eax, 32800\n    call __chkstk\n    sub rsp, rax\n"), "{}", asm);
+    
+    let snippet = compile_snippet("no-chkstk", "fn main() {\n    let small = [0; 16];\n    print(small[0]);\n}\n", &["--no-std"]);
+    assert!(!snippet.asm.unwrap().contains("call __chkstk"));
+}
+
+#[test]
+fn ast_json_describes_functions_and_their_parameters() {
+    let source = "fn add(a: i32, b: i32) -> i32 {\n    return a + b;\n}\n\nfn main() {\n}\n";
+    let snippet = compile_snippet("ast-json-function", source, &["--no-std", "--ast-json"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let json = snippet.ast_json.unwrap();
+    assert!(json.contains(r#"{"kind":"Function","name":"add","params":[{"name":"a","type":"i32"},{"name":"b","type":"i32"}],"return_type":"i32""#), "{}", json);
+}
+
+#[test]
+fn gas_syntax_uses_att_operands() {
+    let source = "fn main() {\n    let x: i64 = 5;\n    print(x + 1);\n}\n";
+    let out_dir = output_dir("gas");
+    let file = out_dir.join("main.ss");
+    fs::write(&file, source).unwrap();
+    let output = out_dir.join("main");
+    let result = ssc(&[file.to_str().unwrap(), "-S", "--asm-syntax", "gas", "--no-std", "-o", output.to_str().unwrap()]);
+    let asm = fs::read_to_string(output.with_extension("s")).unwrap_or_default();
+    fs::remove_dir_all(&out_dir).ok();
+    
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(asm.contains("main:\n    push %rbp\n    mov %rsp, %rbp\n"), "{}", asm);
+    assert!(asm.contains("    mov $5, %rax\n    mov %rax, -8(%rbp)\n"), "{}", asm);
+}
+
+#[test]
+fn bitwise_operators_mixed_with_comparisons_warn() {
+    let source = "fn main() {\n    let a = 6;\n    let b = 2;\n    let c = true;\n    let d = a & b == c;\n}\n";
+    let result = compile_snippet("precedence-lint", source, &["--message-format", "json"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains(r#"{"severity":"warning","message":"'==' binds more tightly than '&'; add parentheses to make the grouping explicit","line":5,"column":15,"phase":"parser"}"#), "{}", stderr);
+    
+    let source = "fn main() {\n    let a = 6;\n    let b = 2;\n    print((a & b) == 2);\n}\n";
+    let result = compile_snippet("precedence-lint-grouped", source, &["--no-std", "-q"]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(result.stderr.is_empty(), "{}", String::from_utf8_lossy(&result.stderr));
+}
+
+#[test]
+fn expression_bodied_functions_compile_like_braced_returns() {
+    let short = compile_snippet("expression-body", "fn add(a: i32, b: i32) -> i32 = a + b;\n\nfn main() {\n    print(add(1, 2));\n}\n", &["--no-std"]);
+    let braced = compile_snippet("braced-body", "fn add(a: i32, b: i32) -> i32 {\n    return a + b;\n}\n\nfn main() {\n    print(add(1, 2));\n}\n", &["--no-std"]);
+    assert!(short.result.status.success(), "{}", String::from_utf8_lossy(&short.result.stderr));
+    assert_eq!(short.asm.unwrap(), braced.asm.unwrap());
+}
+
+#[test]
+fn unbalanced_brackets_point_at_the_unclosed_opener() {
+    let source = "fn main() {\n    let x = 1;\n    if (x > 0) {\n        print(x);\n\n";
+    let result = compile_snippet("unclosed-brace", source, &["--message-format", "json"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains(r#""message":"Unclosed '{' opened at line 3","line":3,"column":16"#), "{}", stderr);
+}
+
+#[test]
+fn printf_passes_its_arguments_through_and_checks_the_count() {
+    let source = "fn main() {\n    let a = 1;\n    let b = 2;\n    printf(\"%d-%d\\n\", a, b);\n}\n";
+    let snippet = compile_snippet("printf", source, &["--no-std", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    assert!(snippet.result.stderr.is_empty(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    mov rcx, [rbp-24]\n    mov rdx, [rbp-32]\n    mov r8, [rbp-40]\n    call printf\n"), "{}", asm);
+    
+    let source = "fn main() {\n    let a = 1;\n    printf(\"%d %d\\n\", a);\n}\n";
+    let result = compile_snippet("printf-count", source, &["--no-std", "-q"]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("warning: printf format expects 2 arguments, but 1 was given"), "{}", stderr);
+}
+
+#[test]
+fn const_fn_calls_fold_into_array_lengths() {
+    let source = "const fn square(x: i32) -> i32 {\n    return x * x;\n}\n\nfn main() {\n    const N: i32 = square(4);\n    let grid: [i32; N] = [0; 16];\n    let n = 5;\n    print(grid[15] + square(n));\n}\n";
+    let snippet = compile_snippet("const-fn", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [1] grid: [i32; 16]\n"), "{}", symbols);
+    // The call with a run-time argument is left for run time.
+    assert_eq!(snippet.asm.unwrap().matches("call square").count(), 1);
+    
+    let source = "const fn spin(x: i32) -> i32 {\n    while (true) {\n    }\n    return x;\n}\n\nfn main() {\n}\n";
+    let result = compile_snippet("const-fn-loop", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Function 'spin' is declared const but uses a loop"), "{}", stderr);
+}
+
+#[test]
+fn stack_protector_guards_functions_with_arrays() {
+    let source = "fn fill() -> i32 {\n    let a = [1, 2, 3];\n    return a[1];\n}\n\nfn plain() -> i32 {\n    return 1;\n}\n\nfn main() {\n    print(fill() + plain());\n}\n";
+    let snippet = compile_snippet("stack-protector", source, &["--no-std", "--stack-protector"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    let fill = &asm[asm.find("\nfill:\n").unwrap()..asm.find("\nplain:\n").unwrap()];
+    assert!(fill.contains("    mov rax, [rel __security_cookie]\n    xor rax, rbp\n    mov [rbp-8], rax\n"), "{}", fill);
+    assert!(fill.contains("    mov rcx, [rbp-8]\n    xor rcx, rbp\n    call __security_check_cookie\n    leave\n"), "{}", fill);
+    let plain = &asm[asm.find("\nplain:\n").unwrap()..asm.find("\nmain:\n").unwrap()];
+    assert!(!plain.contains("__security"), "{}", plain);
+    
+    let snippet = compile_snippet("no-stack-protector", source, &["--no-std"]);
+    assert!(!snippet.asm.unwrap().contains("__security"));
+}
+
+#[test]
+fn print_hex_and_print_char_use_their_own_formats() {
+    let snippet = compile_snippet("print-hex-char", "fn main() {\n    print_hex(255);\n    print_char('A');\n}\n", &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    str_0: db `%llx`, 0\n"), "{}", asm);
+    assert!(asm.contains("    call putchar\n"), "{}", asm);
+}
+
+#[test]
+fn repeated_subexpressions_are_computed_once_at_o2() {
+    let source = "fn main() {\n    let a = 6;\n    let b = 7;\n    let s = (a * b) + (a * b);\n    print(s);\n}\n";
+    let optimized = compile_snippet("cse", source, &["--no-std", "-O2"]).asm.unwrap();
+    assert_eq!(optimized.matches("imul").count(), 1, "{}", optimized);
+    let unoptimized = compile_snippet("no-cse", source, &["--no-std"]).asm.unwrap();
+    assert_eq!(unoptimized.matches("imul").count(), 2, "{}", unoptimized);
+}
+
+#[test]
+fn unsigned_integers_print_with_llu() {
+    let source = "fn main() {\n    let big: u64 = 18446744073709551615;\n    print(big);\n    let small: i64 = -1;\n    print(small);\n}\n";
+    let snippet = compile_snippet("print-unsigned", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    str_0: db `%llu\\n`, 0\n    str_1: db `%lld\\n`, 0\n"), "{}", asm);
+}
+
+#[test]
+fn time_report_json_lists_every_phase() {
+    let snippet = compile_snippet("time-report", "fn main() {\n    print(1);\n}\n", &["--no-std", "--time-report", "json"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let report = snippet.time_report.unwrap();
+    for phase in ["lexing", "parsing", "semantic", "codegen", "assembly"] {
+        let field = format!(r#"{{"phase":"{}","seconds":"#, phase);
+        let start = report.find(&field).unwrap_or_else(|| panic!("{}", report)) + field.len();
+        let seconds = &report[start..start + report[start..].find('}').unwrap()];
+        assert!(seconds.parse::<f64>().is_ok(), "{}", report);
+    }
+    assert!(report.contains(r#""total_seconds":"#), "{}", report);
+}
+
+#[test]
+fn non_ascii_function_names_are_mangled_into_labels() {
+    let source = "fn größe() -> i32 {\n    return 1;\n}\n\nfn main() {\n    let café = größe();\n    print(café);\n}\n";
+    let snippet = compile_snippet("unicode-names", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("\n_$gr$0000f6$0000dfe:\n"), "{}", asm);
+    assert!(asm.contains("    call _$gr$0000f6$0000dfe\n"), "{}", asm);
+    assert!(asm.is_ascii(), "{}", asm);
+}
+
+#[test]
+fn unions_are_as_large_as_their_largest_field() {
+    let source = "union Wide {\n    small: u8,\n    many: [i64; 3],\n}\n\nfn main() {\n    let before = 1;\n    let w = Wide { small: 7 };\n    let after = 2;\n    print(before + after + w.many[2]);\n}\n";
+    let snippet = compile_snippet("union-size", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [rbp-8] before: i32\n    [rbp-32] w: Wide\n    [rbp-40] after: i32\n"), "{}", symbols);
+}
+
+#[test]
+fn if_else_assignments_compile_to_cmov_at_o2() {
+    let source = "fn pick(c: bool, a: i32, b: i32) -> i32 {\n    let mut x = 0;\n    if (c) {\n        x = a;\n    } else {\n        x = b;\n    }\n    return x;\n}\n\nfn main() {\n    print(pick(true, 1, 2));\n}\n";
+    let asm = compile_snippet("cmov", source, &["--no-std", "-O2"]).asm.unwrap();
+    let pick = &asm[asm.find("\npick:\n").unwrap()..asm.find("\nmain:\n").unwrap()];
+    assert!(pick.contains("    test rdx, rdx\n    cmovz rax, rcx\n"), "{}", pick);
+    assert!(!pick.contains("jz") && !pick.contains("jmp"), "{}", pick);
+    
+    let asm = compile_snippet("no-cmov", source, &["--no-std"]).asm.unwrap();
+    assert!(!asm.contains("cmov"), "{}", asm);
+}
+
+#[test]
+fn mixed_width_operands_promote_to_the_wider_type() {
+    let source = "fn main() {\n    let a: u8 = 200;\n    let b: u16 = 1000;\n    let c = a + b;\n    print(c);\n}\n";
+    let snippet = compile_snippet("promotion", source, &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [1] c: u16\n"), "{}", symbols);
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    mov rax, [rbp-8]\n    movzx eax, al\n    mov rcx, [rbp-32]\n    add rax, rcx\n"), "{}", asm);
+}
+
+#[test]
+fn calls_to_deprecated_functions_warn() {
+    let source = "@deprecated(\"use bar instead\")\nfn foo() -> i32 {\n    return 1;\n}\n\nfn bar() -> i32 {\n    return 2;\n}\n\nfn main() {\n    print(foo() + bar());\n}\n";
+    let result = compile_snippet("deprecated", source, &["--no-std", "--message-format", "json"]).result;
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert_eq!(stderr.lines().count(), 1, "{}", stderr);
+    assert!(stderr.contains(r#""message":"Call to deprecated function 'foo': use bar instead","line":11,"column":11"#), "{}", stderr);
+}
+
+#[test]
+fn static_arrays_are_addressed_rip_relative() {
+    let source = "static mut TABLE: [i64; 4] = [1, 2, 3, 4];\nstatic mut SCRATCH: [i64; 2];\n\nfn main() {\n    let i = 1;\n    SCRATCH[i] = TABLE[2] + TABLE[i];\n    print(SCRATCH[1]);\n}\n";
+    let snippet = compile_snippet("static-arrays", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let asm = snippet.asm.unwrap();
+    let data = &asm[asm.find("section .data").unwrap()..asm.find("section .bss").unwrap()];
+    let bss = &asm[asm.find("section .bss").unwrap()..asm.find("section .text").unwrap()];
+    assert!(data.contains("    static_TABLE: dq 1, 2, 3, 4\n"), "{}", asm);
+    assert!(bss.contains("    static_SCRATCH: resq 2\n"), "{}", asm);
+    assert!(asm.contains("    lea rax, [rel static_TABLE]\n"), "{}", asm);
+    assert!(asm.contains("    lea rax, [rel static_SCRATCH]\n"), "{}", asm);
+    assert!(asm.contains("    mov rax, [rax+rcx*8]\n"), "{}", asm);
+    // Every access goes through a RIP-relative address.
+    assert_eq!(asm.matches("static_").count(), asm.matches("[rel static_").count() + 2, "{}", asm);
+}
+
+#[test]
+fn postfix_index_field_and_call_suffixes_chain() {
+    let source = "struct Inner {\n    y: i32,\n}\n\nstruct Outer {\n    x: Inner,\n}\n\nfn pair() -> [i32; 2] {\n    return [7, 8];\n}\n\nfn main() {\n    let grid = [[1, 2], [3, 4]];\n    let i = 1;\n    let j = 0;\n    print(grid[i][j]);\n    let p = Outer { x: Inner { y: 5 } };\n    print(p.x.y);\n    print(pair()[1]);\n}\n";
+    let snippet = compile_snippet("postfix-chains", source, &["--no-std", "--ast-json"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let ast = snippet.ast_json.unwrap();
+    assert!(ast.contains(r#"{"kind":"ArrayIndex","array":{"kind":"ArrayIndex","array":{"kind":"Identifier","name":"grid""#), "{}", ast);
+    assert!(ast.contains(r#"{"kind":"FieldAccess","object":{"kind":"FieldAccess","object":{"kind":"Identifier","name":"p""#), "{}", ast);
+    assert!(ast.contains(r#"{"kind":"ArrayIndex","array":{"kind":"FunctionCall","name":"pair","args":[]"#), "{}", ast);
+}
+
+#[test]
+fn early_returns_and_trailing_expressions_share_the_return_type() {
+    let source = "fn f(x: i32) -> i32 {\n    if (x < 0) {\n        return 0;\n    }\n    x * 2\n}\n\nfn main() {\n    print(f(3));\n}\n";
+    let snippet = compile_snippet("early-and-trailing", source, &["--no-std"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    
+    // A mismatch is reported whether it is in the trailing expression or the early return.
+    for (test, early, trailing) in [("trailing-mismatch", "0", "true"), ("early-mismatch", "false", "x")] {
+        let source = format!("fn f(x: i32) -> i32 {{\n    if (x < 0) {{\n        return {};\n    }}\n    {}\n}}\n\nfn main() {{\n}}\n", early, trailing);
+        let result = compile_snippet(test, &source, &[]).result;
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        assert!(stderr.contains("Return type mismatch: expected I32, got Bool"), "{}", stderr);
+    }
+    
+    let source = "fn f(x: i32) -> i32 {\n    if (x < 0) {\n        return 0;\n    }\n    print(x);\n}\n\nfn main() {\n}\n";
+    let result = compile_snippet("no-trailing-value", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Function 'f' must return a value of type i32 on every path"), "{}", stderr);
+}
+
+#[test]
+fn indexing_a_string_loads_a_zero_extended_byte() {
+    let snippet = compile_snippet("string-index", "fn main() {\n    let b = \"abc\"[1];\n    print(b);\n}\n", &["--no-std", "--dump-symbols", "-q"]);
+    assert!(snippet.result.status.success(), "{}", String::from_utf8_lossy(&snippet.result.stderr));
+    let symbols = String::from_utf8_lossy(&snippet.result.stdout);
+    assert!(symbols.contains("    [1] b: u8\n"), "{}", symbols);
+    let asm = snippet.asm.unwrap();
+    assert!(asm.contains("    str_0: db `abc`, 0\n"), "{}", asm);
+    assert!(asm.contains("    lea rax, [rel str_0]\n    mov rcx, [rbp-16]\n    movzx rax, byte [rax+rcx]\n"), "{}", asm);
+    
+    let source = "fn main() {\n    let s = \"abc\";\n    let b = s[1.0];\n}\n";
+    let result = compile_snippet("string-float-index", source, &[]).result;
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Index must be an integer, got f64"), "{}", stderr);
+}
@@ -0,0 +1,258 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+    str_4: db `%lld\n`, 0
+    str_5: db `%lld\n`, 0
+    str_6: db `%lld\n`, 0
+    str_7: db `%lld\n`, 0
+    str_8: db `%lld\n`, 0
+    str_9: db `%g\n`, 0
+    str_10: db `%llu\n`, 0
+    str_11: db `%llu\n`, 0
+    str_12: db `%lld\n`, 0
+    str_13: db `%llu\n`, 0
+    str_14: db `%lld\n`, 0
+    str_15: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov rax, 17
+    mov [rbp-8], rax
+    mov rax, 5
+    mov [rbp-16], rax
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    add rax, rcx
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    sub rax, rcx
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    imul rax, rcx
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    cqo
+    idiv rcx
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    cqo
+    idiv rcx
+    mov rax, rdx
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-8]
+    neg rax
+    mov rdx, rax
+    lea rcx, [rel str_5]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    sub rax, rcx
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-40]
+    add rax, rcx
+    mov rcx, [rbp-32]
+    imul rax, rcx
+    mov rdx, rax
+    lea rcx, [rel str_6]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 2
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    and rcx, 31
+    shl rax, cl
+    movsxd rax, eax
+    mov rdx, rax
+    lea rcx, [rel str_7]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    and rax, rcx
+    mov rdx, rax
+    lea rcx, [rel str_8]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0x3FF8000000000000
+    mov [rbp-24], rax
+    mov rax, 0x4010000000000000
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-32]
+    movq xmm0, rax
+    movq xmm1, rcx
+    mulsd xmm0, xmm1
+    movq rax, xmm0
+    mov rdx, rax
+    movq xmm1, rax
+    lea rcx, [rel str_9]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0xFFFFFFFFFFFFFFFF
+    mov [rbp-32], rax
+    mov rax, [rbp-32]
+    mov rdx, rax
+    lea rcx, [rel str_10]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0xFFFFFFFFFFFFFFFE
+    mov [rbp-40], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    sub rax, rcx
+    mov rdx, rax
+    lea rcx, [rel str_11]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-40], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    seta al
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_12]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 10
+    mov [rbp-40], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    xor rdx, rdx
+    div rcx
+    mov rdx, rax
+    lea rcx, [rel str_13]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+    mov rax, [rbp-8]
+    neg rax
+    mov rcx, [rbp-40]
+    cqo
+    idiv rcx
+    mov rdx, rax
+    lea rcx, [rel str_14]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+    mov rax, [rbp-8]
+    neg rax
+    mov rcx, [rbp-40]
+    cqo
+    idiv rcx
+    mov rax, rdx
+    mov rdx, rax
+    lea rcx, [rel str_15]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
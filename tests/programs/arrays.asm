@@ -0,0 +1,438 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+    str_4: db `%lld\n`, 0
+    str_5: db `%lld\n`, 0
+    str_6: db `%lld\n`, 0
+    str_7: db `[`, 0
+    str_8: db `, `, 0
+    str_9: db `%lld`, 0
+    str_10: db `]\n`, 0
+    str_11: db `[`, 0
+    str_12: db `, `, 0
+    str_13: db `%g`, 0
+    str_14: db `]\n`, 0
+    str_15: db `[`, 0
+    str_16: db `, `, 0
+    str_17: db `%lld`, 0
+    str_18: db `]\n`, 0
+    str_19: db `abc`, 0
+    str_20: db `%llu\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+sum:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov rax, 2
+    mov [rbp-16], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-16]
+    mov rax, [rax+rcx*8]
+    mov [rbp-24], rax
+    mov rax, 1
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-32]
+    mov rax, [rax+rcx*8]
+    mov [rbp-40], rax
+    mov rax, 0
+    mov [rbp-48], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-48]
+    mov rax, [rax+rcx*8]
+    mov rcx, [rbp-40]
+    add rax, rcx
+    mov rcx, [rbp-24]
+    add rax, rcx
+    leave
+    ret
+
+doubled:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 128
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 2
+    mov [rbp-48], rax
+    mov rax, 0
+    mov [rbp-56], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-56]
+    mov rax, [rax+rcx*8]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-40], rax
+    mov rax, 2
+    mov [rbp-64], rax
+    mov rax, 1
+    mov [rbp-72], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-72]
+    mov rax, [rax+rcx*8]
+    mov rcx, [rbp-64]
+    imul rax, rcx
+    mov [rbp-32], rax
+    mov rax, 2
+    mov [rbp-80], rax
+    mov rax, 2
+    mov [rbp-88], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-88]
+    mov rax, [rax+rcx*8]
+    mov rcx, [rbp-80]
+    imul rax, rcx
+    mov [rbp-24], rax
+    lea rax, [rbp-40]
+    mov rdx, [rbp-8]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    mov rcx, [rax+16]
+    mov [rdx+16], rcx
+    mov rax, [rbp-8]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 144
+
+    mov rax, 4
+    mov [rbp-48], rax
+    mov rax, 5
+    mov [rbp-40], rax
+    mov rax, 6
+    mov [rbp-32], rax
+    lea rax, [rbp-48]
+    lea rdx, [rbp-24]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    mov rcx, [rax+16]
+    mov [rdx+16], rcx
+    lea rax, [rbp-24]
+    mov [rbp-32], rax
+    mov rcx, [rbp-32]
+    call sum
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    lea rax, [rbp-72]
+    mov [rbp-80], rax
+    lea rax, [rbp-24]
+    mov [rbp-88], rax
+    mov rcx, [rbp-80]
+    mov rdx, [rbp-88]
+    call doubled
+    lea rdx, [rbp-48]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    mov rcx, [rax+16]
+    mov [rdx+16], rcx
+    mov rax, 2
+    mov [rbp-56], rax
+    lea rax, [rbp-48]
+    mov rcx, [rbp-56]
+    mov rax, [rax+rcx*8]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    lea rax, [rbp-72]
+    mov [rbp-80], rax
+    lea rax, [rbp-48]
+    mov [rbp-88], rax
+    mov rcx, [rbp-80]
+    mov rdx, [rbp-88]
+    call doubled
+    mov [rbp-88], rax
+    mov rcx, [rbp-88]
+    call sum
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-56], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-56]
+    mov rax, [rax+rcx*8]
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-72], rax
+    mov rax, 2
+    mov [rbp-64], rax
+    mov rax, 3
+    mov [rbp-56], rax
+    lea rax, [rbp-72]
+    mov [rbp-80], rax
+    mov rax, 1
+    mov [rbp-104], rax
+    mov rax, 2
+    mov [rbp-96], rax
+    mov rax, 3
+    mov [rbp-88], rax
+    lea rax, [rbp-104]
+    mov rcx, [rbp-80]
+    mov rdx, [rax+0]
+    cmp rdx, [rcx+0]
+    jne L3
+    mov rdx, [rax+8]
+    cmp rdx, [rcx+8]
+    jne L3
+    mov rdx, [rax+16]
+    cmp rdx, [rcx+16]
+    jne L3
+    mov rax, 1
+    jmp L4
+L3:
+    mov rax, 0
+L4:
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-64], rax
+    mov rax, 3
+    mov [rbp-56], rax
+    lea rax, [rbp-64]
+    mov [rbp-72], rax
+    mov rax, 1
+    mov [rbp-88], rax
+    mov rax, 2
+    mov [rbp-80], rax
+    lea rax, [rbp-88]
+    mov rcx, [rbp-72]
+    mov rdx, [rax+0]
+    cmp rdx, [rcx+0]
+    jne L5
+    mov rdx, [rax+8]
+    cmp rdx, [rcx+8]
+    jne L5
+    mov rax, 1
+    jmp L6
+L5:
+    mov rax, 0
+L6:
+    mov rdx, rax
+    lea rcx, [rel str_5]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    lea rax, [rbp-72]
+    mov [rbp-80], rax
+    lea rax, [rbp-24]
+    mov [rbp-88], rax
+    mov rcx, [rbp-80]
+    mov rdx, [rbp-88]
+    call doubled
+    mov [rbp-88], rax
+    lea rax, [rbp-48]
+    mov rcx, [rbp-88]
+    mov rdx, [rax+0]
+    cmp rdx, [rcx+0]
+    jne L7
+    mov rdx, [rax+8]
+    cmp rdx, [rcx+8]
+    jne L7
+    mov rdx, [rax+16]
+    cmp rdx, [rcx+16]
+    jne L7
+    mov rax, 0
+    jmp L8
+L7:
+    mov rax, 1
+L8:
+    mov rdx, rax
+    lea rcx, [rel str_6]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    lea rax, [rbp-24]
+    mov [rbp-56], rax
+    lea rcx, [rel str_7]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov qword [rbp-64], 0
+L9:
+    mov rax, [rbp-64]
+    cmp rax, 3
+    jge L11
+    test rax, rax
+    jz L10
+    lea rcx, [rel str_8]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+L10:
+    mov rax, [rbp-64]
+    imul rax, 8
+    add rax, [rbp-56]
+    mov rdx, [rax]
+    lea rcx, [rel str_9]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    inc qword [rbp-64]
+    jmp L9
+L11:
+    lea rcx, [rel str_10]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0x3FF8000000000000
+    mov [rbp-64], rax
+    mov rax, 0x4000000000000000
+    mov [rbp-56], rax
+    lea rax, [rbp-64]
+    mov [rbp-72], rax
+    lea rcx, [rel str_11]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov qword [rbp-80], 0
+L12:
+    mov rax, [rbp-80]
+    cmp rax, 2
+    jge L14
+    test rax, rax
+    jz L13
+    lea rcx, [rel str_12]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+L13:
+    mov rax, [rbp-80]
+    imul rax, 8
+    add rax, [rbp-72]
+    mov rdx, [rax]
+    movq xmm1, rdx
+    lea rcx, [rel str_13]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    inc qword [rbp-80]
+    jmp L12
+L14:
+    lea rcx, [rel str_14]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-64], rax
+    mov rax, 0
+    mov [rbp-56], rax
+    lea rax, [rbp-64]
+    mov [rbp-72], rax
+    lea rcx, [rel str_15]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov qword [rbp-80], 0
+L15:
+    mov rax, [rbp-80]
+    cmp rax, 2
+    jge L17
+    test rax, rax
+    jz L16
+    lea rcx, [rel str_16]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+L16:
+    mov rax, [rbp-80]
+    imul rax, 8
+    add rax, [rbp-72]
+    mov rdx, [rax]
+    lea rcx, [rel str_17]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    inc qword [rbp-80]
+    jmp L15
+L17:
+    lea rcx, [rel str_18]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-56], rax
+    lea rax, [rel str_19]
+    mov rcx, [rbp-56]
+    movzx rax, byte [rax+rcx]
+    mov rdx, rax
+    lea rcx, [rel str_20]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
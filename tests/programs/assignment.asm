@@ -0,0 +1,272 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+    str_4: db `%lld\n`, 0
+    str_5: db `%lld\n`, 0
+    str_6: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 176
+
+    mov rax, 1
+    mov [rbp-48], rax
+    mov rax, 2
+    mov [rbp-40], rax
+    mov rax, 3
+    mov [rbp-32], rax
+    lea rax, [rbp-48]
+    lea rdx, [rbp-24]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    mov rcx, [rax+16]
+    mov [rdx+16], rcx
+    mov rax, 1
+    mov [rbp-32], rax
+    mov rax, [rbp-32]
+    mov [rbp-40], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-40]
+    lea rax, [rax+rcx*8]
+    mov [rbp-48], rax
+    mov rax, [rax]
+    mov [rbp-56], rax
+    mov rax, 1
+    mov [rbp-64], rax
+    mov rax, [rbp-56]
+    mov rcx, [rbp-64]
+    add rax, rcx
+    mov rcx, [rbp-48]
+    mov [rcx], rax
+    mov rax, 0
+    mov [rbp-40], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-40]
+    lea rax, [rax+rcx*8]
+    mov [rbp-48], rax
+    mov rax, 7
+    mov rcx, [rbp-48]
+    mov [rcx], rax
+    mov rax, 2
+    mov [rbp-40], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-40]
+    lea rax, [rax+rcx*8]
+    mov [rbp-48], rax
+    mov rax, [rax]
+    mov [rbp-56], rax
+    mov rax, 1
+    mov [rbp-64], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-64]
+    mov rax, [rax+rcx*8]
+    mov [rbp-72], rax
+    mov rax, [rbp-56]
+    mov rcx, [rbp-72]
+    imul rax, rcx
+    mov rcx, [rbp-48]
+    mov [rcx], rax
+    mov rax, 0
+    mov [rbp-40], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-40]
+    mov rax, [rax+rcx*8]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-40], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-40]
+    mov rax, [rax+rcx*8]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 2
+    mov [rbp-40], rax
+    lea rax, [rbp-24]
+    mov rcx, [rbp-40]
+    mov rax, [rax+rcx*8]
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 3
+    mov [rbp-64], rax
+    mov rax, 4
+    mov [rbp-56], rax
+    lea rax, [rbp-64]
+    lea rdx, [rbp-48]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    lea rax, [rbp-48]
+    mov [rbp-56], rax
+    mov rax, [rax]
+    mov [rbp-64], rax
+    mov rax, 2
+    mov [rbp-72], rax
+    mov rax, [rbp-64]
+    mov rcx, [rbp-72]
+    imul rax, rcx
+    mov rcx, [rbp-56]
+    mov [rcx], rax
+    lea rax, [rbp-48]
+    lea rax, [rax+8]
+    mov [rbp-56], rax
+    mov rax, [rax]
+    mov [rbp-64], rax
+    mov rax, 1
+    mov [rbp-72], rax
+    mov rax, [rbp-64]
+    mov rcx, [rbp-72]
+    sub rax, rcx
+    mov rcx, [rbp-56]
+    mov [rcx], rax
+    lea rax, [rbp-48]
+    mov rax, [rax+0]
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    lea rax, [rbp-48]
+    mov rax, [rax+8]
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-128], rax
+    mov rax, 2
+    mov [rbp-120], rax
+    lea rax, [rbp-128]
+    lea rdx, [rbp-112]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    mov rax, 3
+    mov [rbp-144], rax
+    mov rax, 4
+    mov [rbp-136], rax
+    lea rax, [rbp-144]
+    lea rdx, [rbp-96]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    lea rax, [rbp-112]
+    lea rdx, [rbp-80]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    mov rcx, [rax+16]
+    mov [rdx+16], rcx
+    mov rcx, [rax+24]
+    mov [rdx+24], rcx
+    mov rax, 1
+    mov [rbp-88], rax
+    lea rax, [rbp-80]
+    mov rcx, [rbp-88]
+    imul rcx, rcx, 16
+    lea rax, [rax+rcx]
+    lea rax, [rax+8]
+    mov [rbp-96], rax
+    mov rax, [rax]
+    mov [rbp-104], rax
+    mov rax, 100
+    mov [rbp-112], rax
+    mov rax, [rbp-104]
+    mov rcx, [rbp-112]
+    add rax, rcx
+    mov rcx, [rbp-96]
+    mov [rcx], rax
+    mov rax, 1
+    mov [rbp-88], rax
+    lea rax, [rbp-80]
+    mov rcx, [rbp-88]
+    imul rcx, rcx, 16
+    lea rax, [rax+rcx]
+    mov rax, [rax+8]
+    mov rdx, rax
+    lea rcx, [rel str_5]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 5
+    mov [rbp-88], rax
+    mov rax, 3
+    mov [rbp-96], rax
+    mov rax, [rbp-88]
+    mov rcx, [rbp-96]
+    add rax, rcx
+    mov [rbp-88], rax
+    mov rax, [rbp-88]
+    mov rdx, rax
+    lea rcx, [rel str_6]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,263 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+    str_4: db `%lld\n`, 0
+    str_5: db `%lld\n`, 0
+    str_6: db `%lld\n`, 0
+    str_7: db `%lld\n`, 0
+    str_8: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+differ:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-1], cl
+    mov [rbp-16], rdx
+    mov [rbp-24], r8
+    mov [rbp-32], r9
+    mov rax, [rbp+48]
+    mov [rbp-33], al
+    movzx eax, byte [rbp-33]
+    mov [rbp-48], rax
+    movzx eax, byte [rbp-1]
+    mov rcx, [rbp-48]
+    cmp rax, rcx
+    setne al
+    movzx rax, al
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 128
+
+    mov rax, 3
+    mov [rbp-8], rax
+    mov rax, 5
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    mov [rbp-9], al
+    mov rax, 5
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    setg al
+    movzx rax, al
+    mov [rbp-10], al
+    mov rax, 7
+    mov [rbp-24], rax
+    movzx eax, byte [rbp-9]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    movzx eax, byte [rbp-10]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-56], rax
+    mov rax, 4
+    mov [rbp-48], rax
+    lea rax, [rbp-56]
+    mov [rbp-64], rax
+    mov rax, [rbp-64]
+    mov rax, [rax+0]
+    mov [rbp-25], al
+    mov rax, [rbp-64]
+    mov rax, [rax+8]
+    mov [rbp-40], rax
+    movzx eax, byte [rbp-25]
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    movzx eax, byte [rbp-9]
+    mov [rbp-48], rax
+    mov rax, 1
+    mov [rbp-56], rax
+    mov rax, 2
+    mov [rbp-64], rax
+    mov rax, 3
+    mov [rbp-72], rax
+    movzx eax, byte [rbp-10]
+    mov [rbp-80], rax
+    mov rax, [rbp-80]
+    mov [rsp+32], rax
+    mov rcx, [rbp-48]
+    mov rdx, [rbp-56]
+    mov r8, [rbp-64]
+    mov r9, [rbp-72]
+    call differ
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    movzx eax, byte [rbp-9]
+    mov [rbp-48], rax
+    mov rax, 1
+    mov [rbp-56], rax
+    mov rax, 2
+    mov [rbp-64], rax
+    mov rax, 3
+    mov [rbp-72], rax
+    movzx eax, byte [rbp-9]
+    mov [rbp-80], rax
+    mov rax, [rbp-80]
+    mov [rsp+32], rax
+    mov rcx, [rbp-48]
+    mov rdx, [rbp-56]
+    mov r8, [rbp-64]
+    mov r9, [rbp-72]
+    call differ
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    movzx eax, byte [rbp-9]
+    test rax, rax
+    jz L3
+    mov rax, [rbp-40]
+    movsxd rax, eax
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    add rax, rcx
+    mov [rbp-24], rax
+    jmp L4
+L3:
+L4:
+    mov rax, [rbp-24]
+    mov rdx, rax
+    lea rcx, [rel str_5]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-41], al
+    movzx eax, byte [rbp-41]
+    test rax, rax
+    setz al
+    movzx rax, al
+    mov [rbp-41], al
+    movzx eax, byte [rbp-41]
+    mov rdx, rax
+    lea rcx, [rel str_6]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-56], rax
+    mov rax, 5
+    mov rcx, [rbp-56]
+    cmp rax, rcx
+    setne al
+    movzx rax, al
+    mov [rbp-64], rax
+    mov rax, 0
+    mov [rbp-72], rax
+    mov rax, 3
+    mov rcx, [rbp-72]
+    cmp rax, rcx
+    setne al
+    movzx rax, al
+    mov rcx, [rbp-64]
+    test rax, rax
+    setne al
+    test rcx, rcx
+    setne cl
+    and al, cl
+    movzx rax, al
+    mov [rbp-42], al
+    movzx eax, byte [rbp-42]
+    mov rdx, rax
+    lea rcx, [rel str_7]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    movzx eax, byte [rbp-10]
+    mov [rbp-56], rax
+    mov rax, 0
+    mov [rbp-64], rax
+    mov rax, 2
+    mov [rbp-72], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-72]
+    and rax, rcx
+    mov rcx, [rbp-64]
+    cmp rax, rcx
+    setne al
+    movzx rax, al
+    mov rcx, [rbp-56]
+    or rax, rcx
+    setne al
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_8]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
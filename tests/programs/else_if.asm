@@ -0,0 +1,258 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+    str_4: db `%lld\n`, 0
+    str_5: db `%lld\n`, 0
+    str_6: db `%lld\n`, 0
+    str_7: db `%lld\n`, 0
+    str_8: db `%lld\n`, 0
+    str_9: db `%lld\n`, 0
+    str_10: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+classify:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 96
+
+    mov [rbp-8], rcx
+    mov rax, 0
+    mov [rbp-16], rax
+    mov rax, 0
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L3
+    mov rax, 10
+    mov [rbp-32], rax
+    mov rax, 1
+    mov [rbp-16], rax
+    mov rax, [rbp-32]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    jmp L4
+L3:
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-32]
+    cmp rax, rcx
+    sete al
+    movzx rax, al
+    test rax, rax
+    jz L5
+    mov rax, 1
+    mov [rbp-33], al
+    mov rax, 2
+    mov [rbp-16], rax
+    movzx eax, byte [rbp-33]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    jmp L6
+L5:
+    mov rax, 10
+    mov [rbp-40], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L7
+    mov rax, 30
+    mov [rbp-48], rax
+    mov rax, 3
+    mov [rbp-16], rax
+    mov rax, [rbp-48]
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    jmp L8
+L7:
+    mov rax, 100
+    mov [rbp-48], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-48]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L9
+    mov rax, 40
+    mov [rbp-56], rax
+    mov rax, 4
+    mov [rbp-16], rax
+    mov rax, [rbp-56]
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    jmp L10
+L9:
+    mov rax, 50
+    mov [rbp-56], rax
+    mov rax, 5
+    mov [rbp-16], rax
+    mov rax, [rbp-56]
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+L10:
+L8:
+L6:
+L4:
+    mov rax, [rbp-16]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 64
+
+    mov rax, -5
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call classify
+    mov rdx, rax
+    lea rcx, [rel str_5]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call classify
+    mov rdx, rax
+    lea rcx, [rel str_6]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 7
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call classify
+    mov rdx, rax
+    lea rcx, [rel str_7]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 42
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call classify
+    mov rdx, rax
+    lea rcx, [rel str_8]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 500
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call classify
+    mov rdx, rax
+    lea rcx, [rel str_9]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 15
+    mov [rbp-8], rax
+    mov rax, 10
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L11
+    mov rax, 1
+    jmp L12
+L11:
+    mov rax, 100
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-32]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L13
+    mov rax, 2
+    jmp L14
+L13:
+    mov rax, 3
+L14:
+L12:
+    mov [rbp-16], rax
+    mov rax, [rbp-16]
+    mov rdx, rax
+    lea rcx, [rel str_10]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
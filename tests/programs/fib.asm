@@ -0,0 +1,109 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+fib:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov rax, 2
+    mov [rbp-16], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-16]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L3
+    mov rax, [rbp-8]
+    leave
+    ret
+    jmp L4
+L3:
+L4:
+    mov rax, 2
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    sub rax, rcx
+    mov [rbp-32], rax
+    mov rcx, [rbp-32]
+    call fib
+    mov [rbp-24], rax
+    mov rax, 1
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-32]
+    sub rax, rcx
+    mov [rbp-40], rax
+    mov rcx, [rbp-40]
+    call fib
+    mov rcx, [rbp-24]
+    add rax, rcx
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 48
+
+    mov rax, 10
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call fib
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,232 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+    str_4: db `%g\n`, 0
+    str_5: db `%lld\n`, 0
+    str_6: db `%lld\n`, 0
+    str_7: db `%lld\n`, 0
+    str_8: db `%lld\n`, 0
+    str_9: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov rax, 0x3FF8000000000000
+    mov [rbp-8], rax
+    mov rax, 0x4004000000000000
+    mov [rbp-16], rax
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm1, xmm0
+    seta al
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm0, xmm1
+    seta al
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0x3FF8000000000000
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm1, xmm0
+    setae al
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0x3FF8000000000000
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm0, xmm1
+    sete al
+    setnp cl
+    and al, cl
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    movq xmm0, rax
+    movq xmm1, rcx
+    addsd xmm0, xmm1
+    movq rax, xmm0
+    mov rdx, rax
+    movq xmm1, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0x0000000000000000
+    mov [rbp-32], rax
+    mov rax, 0x0000000000000000
+    mov rcx, [rbp-32]
+    movq xmm0, rax
+    movq xmm1, rcx
+    divsd xmm0, xmm1
+    movq rax, xmm0
+    mov [rbp-24], rax
+    mov rax, [rbp-24]
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-32]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm0, xmm1
+    sete al
+    setnp cl
+    and al, cl
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_5]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-24]
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-32]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm0, xmm1
+    setne al
+    setp cl
+    or al, cl
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_6]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-8]
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-32]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm1, xmm0
+    seta al
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_7]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0x0000000000000000
+    mov [rbp-32], rax
+    mov rax, 0x8000000000000000
+    mov [rbp-40], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm0, xmm1
+    sete al
+    setnp cl
+    and al, cl
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_8]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-32]
+    btc rax, 63
+    mov [rbp-40], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    movq xmm0, rax
+    movq xmm1, rcx
+    ucomisd xmm0, xmm1
+    setne al
+    setp cl
+    or al, cl
+    movzx rax, al
+    mov rdx, rax
+    lea rcx, [rel str_9]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,168 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%llu\n`, 0
+    str_4: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+sign:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 64
+
+    mov [rbp-8], rcx
+    mov rax, 0
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L3
+    mov rax, -1
+    jmp L4
+L3:
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-32]
+    cmp rax, rcx
+    sete al
+    movzx rax, al
+    test rax, rax
+    jz L5
+    mov rax, 0
+    jmp L6
+L5:
+    mov rax, 1
+L6:
+L4:
+    mov [rbp-16], rax
+    mov rax, [rbp-16]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 64
+
+    mov rax, -5
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call sign
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call sign
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 9
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call sign
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-16], rax
+    mov rax, 3
+    mov [rbp-24], rax
+    mov rcx, [rbp-24]
+    call sign
+    mov rcx, [rbp-16]
+    cmp rax, rcx
+    setg al
+    movzx rax, al
+    test rax, rax
+    jz L7
+    mov rax, 200
+    jmp L8
+L7:
+    mov rax, 3
+L8:
+    mov [rbp-8], rax
+    mov rax, [rbp-8]
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 100
+    mov [rbp-16], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-16]
+    cmp rax, rcx
+    seta al
+    movzx rax, al
+    test rax, rax
+    jz L9
+    mov rax, 1
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    jmp L10
+L9:
+L10:
+    xor eax, eax
+    leave
+    ret
+
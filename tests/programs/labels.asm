@@ -0,0 +1,261 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 112
+
+    mov rax, 0
+    mov [rbp-8], rax
+    mov rax, 0
+    mov [rbp-16], rax
+L3:
+    mov rax, 0
+    mov [rbp-24], rax
+L5:
+    mov rax, 10
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-32]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L6
+    mov rax, 23
+    mov [rbp-40], rax
+    mov rax, [rbp-24]
+    mov [rbp-48], rax
+    mov rax, 10
+    mov [rbp-56], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-56]
+    imul rax, rcx
+    mov rcx, [rbp-48]
+    add rax, rcx
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    sete al
+    movzx rax, al
+    test rax, rax
+    jz L7
+    jmp L4
+    jmp L8
+L7:
+L8:
+    mov rax, 1
+    mov [rbp-64], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-64]
+    add rax, rcx
+    mov [rbp-16], rax
+    mov rax, 1
+    mov [rbp-64], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-64]
+    add rax, rcx
+    mov [rbp-24], rax
+    jmp L5
+L6:
+    mov rax, 1
+    mov [rbp-40], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-40]
+    add rax, rcx
+    mov [rbp-8], rax
+    jmp L3
+L4:
+    mov rax, [rbp-8]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, 4
+    mov [rbp-40], rax
+L9:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L10
+    mov rax, 0
+    mov [rbp-48], rax
+    mov rax, 4
+    mov [rbp-56], rax
+L12:
+    mov rax, [rbp-48]
+    mov rcx, [rbp-56]
+    cmp rax, rcx
+    jge L13
+    mov rax, [rbp-32]
+    mov [rbp-64], rax
+    mov rax, [rbp-48]
+    mov rcx, [rbp-64]
+    cmp rax, rcx
+    setg al
+    movzx rax, al
+    test rax, rax
+    jz L15
+    jmp L11
+    jmp L16
+L15:
+L16:
+    mov rax, 1
+    mov [rbp-72], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-72]
+    add rax, rcx
+    mov [rbp-24], rax
+L14:
+    mov rax, [rbp-48]
+    inc rax
+    mov [rbp-48], rax
+    jmp L12
+L13:
+L11:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L9
+L10:
+    mov rax, [rbp-24]
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, 0
+    mov [rbp-40], rax
+L17:
+    mov rax, 1
+    test rax, rax
+    jz L18
+    mov rax, 1
+    mov [rbp-48], rax
+    mov rax, [rbp-40]
+    mov rcx, [rbp-48]
+    add rax, rcx
+    mov [rbp-40], rax
+    mov rax, 2
+    mov [rbp-48], rax
+    mov rax, [rbp-40]
+    mov rcx, [rbp-48]
+    cmp rax, rcx
+    setg al
+    movzx rax, al
+    test rax, rax
+    jz L19
+    mov rax, 0
+    mov [rbp-56], rax
+    mov rax, 2
+    mov [rbp-64], rax
+    mov rax, [rbp-40]
+    mov rcx, [rbp-64]
+    cqo
+    idiv rcx
+    mov rax, rdx
+    mov rcx, [rbp-56]
+    cmp rax, rcx
+    sete al
+    movzx rax, al
+    test rax, rax
+    jz L21
+    mov rax, 9
+    mov [rbp-72], rax
+    mov rax, [rbp-40]
+    mov rcx, [rbp-72]
+    cmp rax, rcx
+    setg al
+    movzx rax, al
+    test rax, rax
+    jz L23
+    jmp L18
+    jmp L24
+L23:
+L24:
+    jmp L17
+    jmp L22
+L21:
+L22:
+    mov rax, 1
+    mov [rbp-72], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-72]
+    add rax, rcx
+    mov [rbp-32], rax
+    jmp L20
+L19:
+L20:
+    jmp L17
+L18:
+    mov rax, [rbp-32]
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,207 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `unreachable\n`, 0
+    str_4: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 96
+
+    mov rax, 0
+    mov [rbp-8], rax
+    mov rax, 0
+    mov [rbp-16], rax
+    mov rax, 10
+    mov [rbp-24], rax
+L3:
+    mov rax, [rbp-16]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    jge L4
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, 2
+    mov [rbp-40], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-40]
+    cqo
+    idiv rcx
+    mov rax, rdx
+    mov rcx, [rbp-32]
+    cmp rax, rcx
+    sete al
+    movzx rax, al
+    test rax, rax
+    jz L6
+    jmp L5
+    jmp L7
+L6:
+L7:
+    mov rax, [rbp-16]
+    mov [rbp-48], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-48]
+    add rax, rcx
+    mov [rbp-8], rax
+L5:
+    mov rax, [rbp-16]
+    inc rax
+    mov [rbp-16], rax
+    jmp L3
+L4:
+    mov rax, [rbp-8]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-16], rax
+L8:
+    mov rax, 5
+    mov [rbp-24], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    setl al
+    movzx rax, al
+    test rax, rax
+    jz L9
+    mov rax, 1
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-32]
+    add rax, rcx
+    mov [rbp-16], rax
+    jmp L8
+L9:
+    mov rax, [rbp-16]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+L10:
+    mov rax, 3
+    mov [rbp-40], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-40]
+    add rax, rcx
+    mov [rbp-16], rax
+    mov rax, 20
+    mov [rbp-40], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    setg al
+    movzx rax, al
+    test rax, rax
+    jz L12
+    mov rax, [rbp-16]
+    jmp L11
+    jmp L13
+L12:
+L13:
+    jmp L10
+L11:
+    mov [rbp-32], rax
+    mov rax, [rbp-32]
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 3
+    mov [rbp-40], rax
+    mov rax, 0
+    mov [rbp-48], rax
+L14:
+    mov rax, [rbp-40]
+    test rax, rax
+    jz L15
+    mov rax, 1
+    mov [rbp-56], rax
+    mov rax, [rbp-40]
+    mov rcx, [rbp-56]
+    sub rax, rcx
+    mov [rbp-40], rax
+    mov rax, 1
+    mov [rbp-56], rax
+    mov rax, [rbp-48]
+    mov rcx, [rbp-56]
+    add rax, rcx
+    mov [rbp-48], rax
+    jmp L14
+L15:
+    mov rax, 3
+    mov [rbp-56], rax
+    mov rax, [rbp-48]
+    mov rcx, [rbp-56]
+    sub rax, rcx
+    test rax, rax
+    jz L16
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    jmp L17
+L16:
+L17:
+    mov rax, [rbp-48]
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,145 @@
+section .data data align=8
+    str_0: db `%g\n`, 0
+    str_1: db `%g\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+Point__norm_squared:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 64
+
+    mov [rbp-8], rcx
+    mov rax, [rbp-8]
+    mov rax, [rax+8]
+    mov [rbp-16], rax
+    mov rax, [rbp-8]
+    mov rax, [rax+8]
+    mov rcx, [rbp-16]
+    movq xmm0, rax
+    movq xmm1, rcx
+    mulsd xmm0, xmm1
+    movq rax, xmm0
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rax, [rax+0]
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rax, [rax+0]
+    mov rcx, [rbp-32]
+    movq xmm0, rax
+    movq xmm1, rcx
+    mulsd xmm0, xmm1
+    movq rax, xmm0
+    mov rcx, [rbp-24]
+    movq xmm0, rax
+    movq xmm1, rcx
+    addsd xmm0, xmm1
+    movq rax, xmm0
+    leave
+    ret
+
+Point__scaled_x:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 64
+
+    mov [rbp-8], rcx
+    movq [rbp-16], xmm1
+    mov rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, [rbp-8]
+    mov rax, [rax+0]
+    mov rcx, [rbp-24]
+    movq xmm0, rax
+    movq xmm1, rcx
+    mulsd xmm0, xmm1
+    movq rax, xmm0
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 64
+
+    mov rax, 0x4008000000000000
+    mov [rbp-32], rax
+    mov rax, 0x4010000000000000
+    mov [rbp-24], rax
+    lea rax, [rbp-32]
+    lea rdx, [rbp-16]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    lea rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rcx, [rbp-24]
+    call Point__norm_squared
+    mov rdx, rax
+    movq xmm1, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    lea rax, [rbp-16]
+    mov [rbp-24], rax
+    mov rax, 0x4000000000000000
+    mov [rbp-32], rax
+    mov rcx, [rbp-24]
+    movq xmm1, [rbp-32]
+    call Point__scaled_x
+    mov rdx, rax
+    movq xmm1, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,182 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 96
+
+    mov rax, 0
+    mov [rbp-8], rax
+    mov rax, 0
+    mov [rbp-16], rax
+    mov rax, 0
+    mov [rbp-24], rax
+L3:
+    mov rax, [rbp-16]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    jge L4
+    mov rax, 1
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-32]
+    add rax, rcx
+    mov [rbp-8], rax
+L5:
+    mov rax, [rbp-16]
+    inc rax
+    mov [rbp-16], rax
+    jmp L3
+L4:
+    mov rax, [rbp-8]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 0
+    mov [rbp-16], rax
+    mov rax, 0
+    mov [rbp-24], rax
+L6:
+    mov rax, [rbp-16]
+    mov rcx, [rbp-24]
+    cmp rax, rcx
+    jg L7
+    mov rax, 10
+    mov [rbp-32], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-32]
+    add rax, rcx
+    mov [rbp-8], rax
+L8:
+    mov rax, [rbp-16]
+    cmp rax, [rbp-24]
+    jge L7
+    inc rax
+    mov [rbp-16], rax
+    jmp L6
+L7:
+    mov rax, [rbp-8]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 255
+    mov [rbp-16], rax
+    mov rax, 250
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov [rbp-40], rax
+    mov rax, [rbp-16]
+    mov [rbp-48], rax
+L9:
+    mov rax, [rbp-40]
+    mov rcx, [rbp-48]
+    cmp rax, rcx
+    jg L10
+    mov rax, 1
+    mov [rbp-56], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-56]
+    add rax, rcx
+    mov [rbp-32], rax
+L11:
+    mov rax, [rbp-40]
+    cmp rax, [rbp-48]
+    jge L10
+    inc rax
+    mov [rbp-40], rax
+    jmp L9
+L10:
+    mov rax, [rbp-32]
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 5
+    mov [rbp-40], rax
+    mov rax, 3
+    mov [rbp-48], rax
+L12:
+    mov rax, [rbp-40]
+    mov rcx, [rbp-48]
+    cmp rax, rcx
+    jg L13
+    mov rax, 100
+    mov [rbp-56], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-56]
+    add rax, rcx
+    mov [rbp-8], rax
+L14:
+    mov rax, [rbp-40]
+    cmp rax, [rbp-48]
+    jge L13
+    inc rax
+    mov [rbp-40], rax
+    jmp L12
+L13:
+    mov rax, [rbp-8]
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
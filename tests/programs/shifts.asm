@@ -0,0 +1,127 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%llu\n`, 0
+    str_2: db `%llu\n`, 0
+    str_3: db `%llu\n`, 0
+    str_4: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov rax, -8
+    mov [rbp-8], rax
+    mov rax, 1
+    mov [rbp-16], rax
+    mov rax, [rbp-8]
+    mov rcx, [rbp-16]
+    and rcx, 31
+    sar rax, cl
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 2147483648
+    mov [rbp-16], rax
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-24]
+    and rcx, 31
+    shr rax, cl
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 129
+    mov [rbp-24], rax
+    mov rax, 1
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-32]
+    and rcx, 7
+    shl rax, cl
+    movzx eax, al
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 7
+    mov [rbp-32], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-32]
+    and rcx, 7
+    shr rax, cl
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 1
+    mov [rbp-32], rax
+    mov rax, 65
+    mov [rbp-40], rax
+    mov rax, [rbp-40]
+    mov [rbp-48], rax
+    mov rax, [rbp-32]
+    mov rcx, [rbp-48]
+    and rcx, 63
+    shl rax, cl
+    mov rdx, rax
+    lea rcx, [rel str_4]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,109 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    static_counter: dq 0
+
+section .bss bss align=8
+    static_total: resq 1
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+bump:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 48
+
+    mov [rbp-8], rcx
+    mov rax, 1
+    mov [rbp-16], rax
+    mov rax, [rel static_counter]
+    mov rcx, [rbp-16]
+    add rax, rcx
+    mov [rel static_counter], rax
+    mov rax, [rbp-8]
+    mov [rbp-16], rax
+    mov rax, [rel static_total]
+    mov rcx, [rbp-16]
+    add rax, rcx
+    mov [rel static_total], rax
+    xor eax, eax
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 48
+
+    mov rax, [rel static_total]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 5
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call bump
+    mov rax, 7
+    mov [rbp-8], rax
+    mov rcx, [rbp-8]
+    call bump
+    mov rax, [rel static_counter]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rel static_total]
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
@@ -0,0 +1,156 @@
+section .data data align=8
+    str_0: db `%lld\n`, 0
+    str_1: db `%lld\n`, 0
+    str_2: db `%lld\n`, 0
+    str_3: db `%lld\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+div_mod:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 96
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov [rbp-24], r8
+    mov rax, [rbp-24]
+    mov [rbp-48], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-48]
+    cqo
+    idiv rcx
+    mov [rbp-40], rax
+    mov rax, [rbp-24]
+    mov [rbp-56], rax
+    mov rax, [rbp-16]
+    mov rcx, [rbp-56]
+    cqo
+    idiv rcx
+    mov rax, rdx
+    mov [rbp-32], rax
+    lea rax, [rbp-40]
+    mov rdx, [rbp-8]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    mov rax, [rbp-8]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 96
+
+    lea rax, [rbp-32]
+    mov [rbp-40], rax
+    mov rax, 17
+    mov [rbp-48], rax
+    mov rax, 5
+    mov [rbp-56], rax
+    mov rcx, [rbp-40]
+    mov rdx, [rbp-48]
+    mov r8, [rbp-56]
+    call div_mod
+    mov [rbp-48], rax
+    mov rax, [rbp-48]
+    mov rax, [rax+0]
+    mov [rbp-8], rax
+    mov rax, [rbp-48]
+    mov rax, [rax+8]
+    mov [rbp-16], rax
+    mov rax, [rbp-8]
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, [rbp-16]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 40
+    mov [rbp-48], rax
+    mov rax, 1
+    mov [rbp-40], rax
+    lea rax, [rbp-48]
+    lea rdx, [rbp-32]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    mov rcx, [rax+8]
+    mov [rdx+8], rcx
+    lea rax, [rbp-32]
+    mov [rbp-56], rax
+    mov rax, [rbp-56]
+    mov rax, [rax+0]
+    mov [rbp-40], rax
+    mov rax, [rbp-56]
+    mov rax, [rax+8]
+    mov [rbp-41], al
+    mov rax, 2
+    mov [rbp-56], rax
+    mov rax, [rbp-40]
+    mov rcx, [rbp-56]
+    add rax, rcx
+    mov rdx, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    movzx eax, byte [rbp-41]
+    mov rdx, rax
+    lea rcx, [rel str_3]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+
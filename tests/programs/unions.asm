@@ -0,0 +1,104 @@
+section .data data align=8
+    str_0: db `%llu\n`, 0
+    str_1: db `%llx`, 0
+    str_2: db `%g\n`, 0
+
+section .bss bss align=8
+
+section .text code align=16
+    global main
+    extern ExitProcess
+    extern printf
+    extern putchar
+    extern fflush
+    extern fmod
+    extern __chkstk
+
+pow:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 80
+
+    mov [rbp-8], rcx
+    mov [rbp-16], rdx
+    mov rax, 1
+    mov [rbp-24], rax
+    mov rax, 0
+    mov [rbp-32], rax
+    mov rax, [rbp-16]
+    mov [rbp-40], rax
+L0:
+    mov rax, [rbp-32]
+    mov rcx, [rbp-40]
+    cmp rax, rcx
+    jge L1
+    mov rax, [rbp-8]
+    mov [rbp-48], rax
+    mov rax, [rbp-24]
+    mov rcx, [rbp-48]
+    imul rax, rcx
+    mov [rbp-24], rax
+L2:
+    mov rax, [rbp-32]
+    inc rax
+    mov [rbp-32], rax
+    jmp L0
+L1:
+    mov rax, [rbp-24]
+    leave
+    ret
+
+main:
+    push rbp
+    mov rbp, rsp
+    sub rsp, 48
+
+    mov qword [rbp-16], 0
+    mov rax, 4660
+    mov [rbp-16], rax
+    lea rax, [rbp-16]
+    lea rdx, [rbp-8]
+    mov rcx, [rax+0]
+    mov [rdx+0], rcx
+    lea rax, [rbp-8]
+    mov rax, [rax+0]
+    movzx eax, al
+    mov rdx, rax
+    lea rcx, [rel str_0]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    lea rax, [rbp-8]
+    mov [rbp-16], rax
+    mov rax, 0x3FF0000000000000
+    mov rcx, [rbp-16]
+    mov [rcx], rax
+    lea rax, [rbp-8]
+    mov rax, [rax+0]
+    mov rdx, rax
+    lea rcx, [rel str_1]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    mov rax, 10
+    mov ecx, eax
+    sub rsp, 32
+    call putchar
+    add rsp, 32
+    lea rax, [rbp-8]
+    mov [rbp-16], rax
+    mov rax, 4611686018427387904
+    mov rcx, [rbp-16]
+    mov [rcx], rax
+    lea rax, [rbp-8]
+    mov rax, [rax+0]
+    mov rdx, rax
+    movq xmm1, rax
+    lea rcx, [rel str_2]
+    sub rsp, 32
+    call printf
+    add rsp, 32
+    xor eax, eax
+    leave
+    ret
+